@@ -0,0 +1,103 @@
+//! Patching full values with partials.
+
+use crate::complete::{CompletionError, FromPartial};
+use crate::merge::Merge;
+use crate::path::PathSegment;
+
+/// A type whose values can be updated in place with a partial, overwriting only
+/// what the partial provides. Generated by `#[derive(AsPartial)]` with `#[aspartial(apply)]`.
+///
+/// Values that the partial introduces (e.g. a different enum variant, or
+/// `Some` where there was `None`) must be complete, see [FromPartial]. Parts of
+/// the partial that can't be applied are reported, while the rest is still applied.
+pub trait ApplyPartial: FromPartial {
+    fn apply_partial(&mut self, partial: Self::Partial) -> Result<(), CompletionError>;
+}
+
+impl CompletionError {
+    /// Applies a patch that is known to be present, recording any problems under `segment`
+    #[doc(hidden)]
+    pub fn apply_value<T: ApplyPartial>(&mut self, segment: Option<PathSegment>, target: &mut T, partial: T::Partial) {
+        if let Err(err) = target.apply_partial(partial) {
+            self.extend(err.nest(segment));
+        }
+    }
+
+    /// Applies a patch that may be absent, recording any problems under `segment`
+    #[doc(hidden)]
+    pub fn apply_field<T: ApplyPartial>(&mut self, segment: Option<PathSegment>, target: &mut T, partial: Option<T::Partial>) {
+        if let Some(partial) = partial {
+            self.apply_value(segment, target, partial)
+        }
+    }
+}
+
+/// `None` clears the value, and `Some` patches it, or creates it if there was none
+impl<T: ApplyPartial> ApplyPartial for Option<T> {
+    fn apply_partial(&mut self, partial: Self::Partial) -> Result<(), CompletionError> {
+        match (self.as_mut(), partial) {
+            (_, None) => *self = None,
+            (Some(value), Some(partial)) => value.apply_partial(partial)?,
+            (None, Some(partial)) => *self = Some(T::from_partial(partial)?),
+        }
+        Ok(())
+    }
+}
+
+/// The patch replaces the whole list, but items that already existed at the
+/// same index are patched rather than created anew
+impl<T: ApplyPartial> ApplyPartial for Vec<T> {
+    fn apply_partial(&mut self, partial: Self::Partial) -> Result<(), CompletionError> {
+        let mut errors = CompletionError::default();
+        self.truncate(partial.len());
+        let mut new_items = Vec::new();
+        for (idx, item_partial) in partial.into_iter().enumerate() {
+            match self.get_mut(idx) {
+                Some(item) => errors.apply_value(Some(idx.into()), item, item_partial),
+                None => new_items.extend(errors.complete_value(Some(idx.into()), item_partial)),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors)
+        }
+        self.extend(new_items);
+        Ok(())
+    }
+}
+
+impl ApplyPartial for std::sync::Arc<str> {
+    fn apply_partial(&mut self, partial: Self::Partial) -> Result<(), CompletionError> {
+        *self = partial.into();
+        Ok(())
+    }
+}
+
+/// Keys in the patch are merged into the existing map
+impl ApplyPartial for serde_json::Map<String, serde_json::Value> {
+    fn apply_partial(&mut self, partial: Self::Partial) -> Result<(), CompletionError> {
+        self.merge(partial);
+        Ok(())
+    }
+}
+
+impl ApplyPartial for std::collections::HashMap<String, serde_json::Value> {
+    fn apply_partial(&mut self, partial: Self::Partial) -> Result<(), CompletionError> {
+        Merge::merge(self, partial);
+        Ok(())
+    }
+}
+
+impl ApplyPartial for std::collections::BTreeMap<String, serde_json::Value> {
+    fn apply_partial(&mut self, partial: Self::Partial) -> Result<(), CompletionError> {
+        Merge::merge(self, partial);
+        Ok(())
+    }
+}
+
+#[cfg(feature="iso8601")]
+impl ApplyPartial for iso8601_timestamp::Timestamp {
+    fn apply_partial(&mut self, partial: Self::Partial) -> Result<(), CompletionError> {
+        *self = Self::from_partial(partial)?;
+        Ok(())
+    }
+}
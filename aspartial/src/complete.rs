@@ -0,0 +1,257 @@
+//! Turning partials back into the full types they represent.
+
+use crate::AsPartial;
+use crate::path::{FieldPath, PathSegment};
+use crate::tag::{TagMatch, UnknownTag};
+
+/// A type that can be rebuilt from its partial representation, as long as
+/// enough of it is present. Generated by `#[derive(AsPartial)]` with `#[aspartial(complete)]`.
+pub trait FromPartial: AsPartial + Sized {
+    fn from_partial(partial: Self::Partial) -> Result<Self, CompletionError>;
+
+    /// The value to use when a field of this type is absent altogether, if
+    /// serde would accept that (like `None` for [Option])
+    fn when_missing() -> Option<Self> {
+        None
+    }
+}
+
+/// Something that prevented a partial from being completed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompletionProblem {
+    /// A required field is absent
+    Missing(FieldPath),
+    /// No variant of an enum is populated
+    NoVariant(FieldPath),
+    /// Several variants of an enum are populated, and the tag doesn't say which one is right
+    AmbiguousVariant { path: FieldPath, candidates: Vec<&'static str> },
+    /// The tag of an enum names none of its variants
+    UnknownVariant { path: FieldPath, tag: String },
+    /// The value is present, but can't be converted into the full type
+    Invalid { path: FieldPath, message: String },
+}
+
+impl CompletionProblem {
+    pub fn path(&self) -> &FieldPath {
+        match self {
+            Self::Missing(path) | Self::NoVariant(path) => path,
+            Self::AmbiguousVariant { path, .. } | Self::UnknownVariant { path, .. } | Self::Invalid { path, .. } => path,
+        }
+    }
+
+    fn path_mut(&mut self) -> &mut FieldPath {
+        match self {
+            Self::Missing(path) | Self::NoVariant(path) => path,
+            Self::AmbiguousVariant { path, .. } | Self::UnknownVariant { path, .. } | Self::Invalid { path, .. } => path,
+        }
+    }
+}
+
+impl std::fmt::Display for CompletionProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing(path) => write!(f, "missing {path}"),
+            Self::NoVariant(path) => write!(f, "no variant at {path}"),
+            Self::AmbiguousVariant { path, candidates } => {
+                write!(f, "ambiguous variant at {path} (one of {})", candidates.join(", "))
+            },
+            Self::UnknownVariant { path, tag } => write!(f, "unknown variant {tag:?} at {path}"),
+            Self::Invalid { path, message } => write!(f, "invalid {path}: {message}"),
+        }
+    }
+}
+
+/// Everything that prevented a partial from being completed, with the paths
+/// (in their serialized form) where the problems are
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompletionError {
+    problems: Vec<CompletionProblem>,
+}
+
+impl From<CompletionProblem> for CompletionError {
+    fn from(value: CompletionProblem) -> Self {
+        Self { problems: vec![value] }
+    }
+}
+
+impl CompletionError {
+    pub fn problems(&self) -> &[CompletionProblem] {
+        &self.problems
+    }
+
+    /// The paths of all required fields that were absent
+    pub fn missing_paths(&self) -> impl Iterator<Item = &FieldPath> {
+        self.problems.iter().filter_map(|problem| match problem {
+            CompletionProblem::Missing(path) => Some(path),
+            _ => None,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    pub(crate) fn extend(&mut self, other: Self) {
+        self.problems.extend(other.problems)
+    }
+
+    /// Makes all problems relative to the parent of the value that produced them
+    pub(crate) fn nest(mut self, segment: Option<PathSegment>) -> Self {
+        let Some(segment) = segment else {
+            return self
+        };
+        for problem in &mut self.problems {
+            let path = problem.path_mut();
+            *path = std::iter::once(segment.clone()).chain(path.segments().iter().cloned()).collect();
+        }
+        self
+    }
+
+    /// Completes a value that is known to be present, recording any problems under `segment`
+    #[doc(hidden)]
+    pub fn complete_value<T: FromPartial>(&mut self, segment: Option<PathSegment>, partial: T::Partial) -> Option<T> {
+        match T::from_partial(partial) {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.extend(err.nest(segment));
+                None
+            },
+        }
+    }
+
+    /// Completes a value that may be absent, recording any problems under `segment`
+    #[doc(hidden)]
+    pub fn complete_field<T: FromPartial>(&mut self, segment: Option<PathSegment>, partial: Option<T::Partial>) -> Option<T> {
+        if let Some(partial) = partial {
+            return self.complete_value(segment, partial)
+        }
+        let value = T::when_missing();
+        if value.is_none() {
+            self.problems.push(CompletionProblem::Missing(segment.into_iter().collect()));
+        }
+        value
+    }
+
+    /// Runs `f` with an empty error, recording the problems it finds under `segment`
+    #[doc(hidden)]
+    pub fn nested<R>(&mut self, segment: Option<PathSegment>, f: impl FnOnce(&mut Self) -> R) -> R {
+        let mut nested = Self::default();
+        let out = f(&mut nested);
+        self.extend(nested.nest(segment));
+        out
+    }
+
+    /// Completes a variant with its own partial type (struct-like, tuple or unit)
+    /// that may be absent, recording any problems under `segment`
+    #[doc(hidden)]
+    pub fn complete_with<P, T>(
+        &mut self, segment: Option<PathSegment>, partial: Option<P>, complete: impl FnOnce(&mut Self, P) -> Option<T>,
+    ) -> Option<T> {
+        let Some(partial) = partial else {
+            self.problems.push(CompletionProblem::Missing(segment.into_iter().collect()));
+            return None
+        };
+        self.nested(segment, |errors| complete(errors, partial))
+    }
+
+    /// Picks which variant of a partial enum to complete, given which of them are populated
+    #[doc(hidden)]
+    pub fn select_variant(
+        variant_tags: &[&'static str], populated: &[bool], tag_match: Option<&TagMatch>, unknown_tag: Option<&UnknownTag>,
+    ) -> Result<usize, Self> {
+        if let Some(tag) = tag_match.and_then(TagMatch::variant)
+            && let Some(index) = variant_tags.iter().position(|variant_tag| *variant_tag == tag)
+        {
+            return Ok(index)
+        }
+        let mut candidates = populated.iter().enumerate().filter(|(_, populated)| **populated);
+        match (candidates.next(), candidates.next()) {
+            (None, _) => Err(match unknown_tag {
+                Some(unknown_tag) => CompletionProblem::UnknownVariant { path: FieldPath::root(), tag: unknown_tag.tag.clone() },
+                None => CompletionProblem::NoVariant(FieldPath::root()),
+            }.into()),
+            (Some((index, _)), None) => Ok(index),
+            (Some(_), Some(_)) => Err(CompletionProblem::AmbiguousVariant {
+                path: FieldPath::root(),
+                candidates: variant_tags.iter()
+                    .zip(populated)
+                    .filter(|(_, populated)| **populated)
+                    .map(|(tag, _)| *tag)
+                    .collect(),
+            }.into()),
+        }
+    }
+}
+
+impl std::fmt::Display for CompletionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "could not complete partial: ")?;
+        for (idx, problem) in self.problems.iter().enumerate() {
+            if idx > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{problem}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for CompletionError {}
+
+impl<T: FromPartial> FromPartial for Option<T> {
+    fn from_partial(partial: Self::Partial) -> Result<Self, CompletionError> {
+        partial.map(T::from_partial).transpose()
+    }
+
+    fn when_missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl<T: FromPartial> FromPartial for Vec<T> {
+    fn from_partial(partial: Self::Partial) -> Result<Self, CompletionError> {
+        let mut errors = CompletionError::default();
+        let items: Vec<Option<T>> = partial.into_iter()
+            .enumerate()
+            .map(|(idx, item)| errors.complete_value(Some(idx.into()), item))
+            .collect();
+        if !errors.is_empty() {
+            return Err(errors)
+        }
+        Ok(items.into_iter().flatten().collect())
+    }
+}
+
+impl FromPartial for std::sync::Arc<str> {
+    fn from_partial(partial: Self::Partial) -> Result<Self, CompletionError> {
+        Ok(partial.into())
+    }
+}
+
+impl FromPartial for serde_json::Map<String, serde_json::Value> {
+    fn from_partial(partial: Self::Partial) -> Result<Self, CompletionError> {
+        Ok(partial)
+    }
+}
+
+impl FromPartial for std::collections::HashMap<String, serde_json::Value> {
+    fn from_partial(partial: Self::Partial) -> Result<Self, CompletionError> {
+        Ok(partial)
+    }
+}
+
+impl FromPartial for std::collections::BTreeMap<String, serde_json::Value> {
+    fn from_partial(partial: Self::Partial) -> Result<Self, CompletionError> {
+        Ok(partial)
+    }
+}
+
+#[cfg(feature="iso8601")]
+impl FromPartial for iso8601_timestamp::Timestamp {
+    fn from_partial(partial: Self::Partial) -> Result<Self, CompletionError> {
+        Self::parse(&partial).ok_or_else(|| CompletionProblem::Invalid {
+            path: FieldPath::root(),
+            message: format!("{partial:?} is not an ISO 8601 timestamp"),
+        }.into())
+    }
+}
@@ -0,0 +1,95 @@
+//! The partial representation of fields with serde defaults.
+
+use crate::Merge;
+
+/// The partial of a field with `#[serde(default)]`, which remembers whether the
+/// value was supplied or filled in from the default.
+///
+/// It dereferences to the value either way. Defaulted fields are left out of
+/// serialized partials, since they carry no information about the document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Defaulted<T> {
+    /// The value was in the document
+    Supplied(T),
+    /// The value is the default, since the field wasn't in the document
+    Default(T),
+}
+
+impl<T> Defaulted<T> {
+    pub fn is_supplied(&self) -> bool {
+        matches!(self, Self::Supplied(_))
+    }
+
+    pub fn is_default(&self) -> bool {
+        matches!(self, Self::Default(_))
+    }
+
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::Supplied(value) | Self::Default(value) => value,
+        }
+    }
+
+    /// The value, if it was supplied
+    pub fn supplied(self) -> Option<T> {
+        match self {
+            Self::Supplied(value) => Some(value),
+            Self::Default(_) => None,
+        }
+    }
+}
+
+impl<T: Default> Default for Defaulted<T> {
+    fn default() -> Self {
+        Self::Default(T::default())
+    }
+}
+
+impl<T> std::ops::Deref for Defaulted<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        match self {
+            Self::Supplied(value) | Self::Default(value) => value,
+        }
+    }
+}
+
+impl<T> std::ops::DerefMut for Defaulted<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        match self {
+            Self::Supplied(value) | Self::Default(value) => value,
+        }
+    }
+}
+
+/// Compares the value, regardless of where it came from
+impl<T: PartialEq> PartialEq<T> for Defaulted<T> {
+    fn eq(&self, other: &T) -> bool {
+        **self == *other
+    }
+}
+
+/// Supplied overlays are merged into the base. A default never overrides anything.
+impl<T: Merge> Merge for Defaulted<T> {
+    fn merge(&mut self, overlay: Self) {
+        match (self, overlay) {
+            (_, Self::Default(_)) => (),
+            (Self::Supplied(base), Self::Supplied(overlay)) => base.merge(overlay),
+            (base, overlay) => *base = overlay,
+        }
+    }
+}
+
+impl<T: serde::Serialize> serde::Serialize for Defaulted<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (**self).serialize(serializer)
+    }
+}
+
+/// Always produces [Defaulted::Supplied]. Derived partials fill in
+/// [Defaulted::Default] through `#[serde(default = ...)]`.
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for Defaulted<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Self::Supplied)
+    }
+}
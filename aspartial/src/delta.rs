@@ -0,0 +1,186 @@
+//! Accumulation of the deltas sent by chat-completion style streaming APIs.
+//!
+//! Those APIs send each tool call in pieces: every chunk carries an `index`
+//! identifying the call, and text fields (most notably the JSON text of the
+//! arguments) must be concatenated across chunks. Other fields may be sent
+//! again whole in later chunks.
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use crate::AsPartial;
+use crate::json::{Error, IncrementalParser};
+
+/// The keys whose strings are sent in fragments, to be appended to each other
+const FRAGMENT_KEYS: &[&str] = &["content", "arguments", "name", "text", "refusal", "reasoning_content"];
+
+/// Merges `delta` into `target`.
+///
+/// Strings under the keys that streaming APIs send in fragments (e.g. `content`
+/// and `arguments`) are appended, objects are merged key by key and arrays whose
+/// entries are objects with an `index` field are merged entry by entry, while
+/// other arrays are appended. `null` in the delta carries no information and is
+/// ignored; any other value, such as an `id` or `type` sent again in a later
+/// chunk, overwrites the target.
+pub fn merge_delta(target: &mut Value, delta: Value) {
+    merge_delta_at(target, delta, None)
+}
+
+/// Merges `delta` into `target`, which sits under `key` in its parent object
+fn merge_delta_at(target: &mut Value, delta: Value, key: Option<&str>) {
+    match (target, delta) {
+        (_, Value::Null) => (),
+        (Value::String(target), Value::String(delta)) if key.is_some_and(|key| FRAGMENT_KEYS.contains(&key)) => {
+            target.push_str(&delta)
+        },
+        (Value::Object(target), Value::Object(delta)) => {
+            for (key, delta_value) in delta {
+                match target.get_mut(&key) {
+                    Some(target_value) => merge_delta_at(target_value, delta_value, Some(&key)),
+                    None => {
+                        target.insert(key, delta_value);
+                    },
+                }
+            }
+        },
+        (Value::Array(target), Value::Array(delta)) => {
+            for delta_entry in delta {
+                let entry_index = delta_entry.get("index").and_then(Value::as_u64);
+                let existing = entry_index.and_then(|index| {
+                    target.iter_mut().find(|entry| entry.get("index").and_then(Value::as_u64) == Some(index))
+                });
+                match existing {
+                    Some(existing) => merge_delta(existing, delta_entry),
+                    None => target.push(delta_entry),
+                }
+            }
+        },
+        (target, delta) => *target = delta,
+    }
+}
+
+/// Removes the value at a JSON `pointer` from its parent object
+fn take_pointer(value: &mut Value, pointer: &str) -> Option<Value> {
+    let (parent, key) = pointer.rsplit_once('/')?;
+    let key = key.replace("~1", "/").replace("~0", "~");
+    value.pointer_mut(parent)?.as_object_mut()?.remove(&key)
+}
+
+/// A single tool call, as accumulated from its deltas
+pub struct ToolCallBuffer {
+    fields: Value,
+    arguments: String,
+    parser: IncrementalParser,
+}
+
+impl ToolCallBuffer {
+    fn new() -> Self {
+        Self {
+            fields: Value::Object(Default::default()),
+            arguments: String::new(),
+            parser: IncrementalParser::new(),
+        }
+    }
+
+    /// The call id, if it has been received
+    pub fn id(&self) -> Option<&str> {
+        self.fields.get("id").and_then(Value::as_str)
+    }
+
+    /// The name of the function being called, as far as it has been received
+    pub fn name(&self) -> Option<&str> {
+        self.fields.pointer("/function/name").and_then(Value::as_str)
+    }
+
+    /// All merged fields of the call, except for the arguments
+    pub fn fields(&self) -> &Value {
+        &self.fields
+    }
+
+    /// The raw text of the arguments received so far
+    pub fn arguments(&self) -> &str {
+        &self.arguments
+    }
+
+    /// The arguments received so far, deserialized into the partial representation of `T`
+    pub fn partial<T>(&self) -> Result<Option<T::Partial>, Error>
+    where
+        T: AsPartial<Partial: serde::de::DeserializeOwned>,
+    {
+        self.parser.partial::<T>()
+    }
+
+    /// Whether the arguments have been received in their entirety
+    pub fn is_complete(&self) -> bool {
+        self.parser.is_complete()
+    }
+}
+
+/// Merges tool-call deltas into one running buffer per call `index`.
+///
+/// By default the arguments are expected at `/function/arguments` of each delta,
+/// as sent by OpenAI-compatible APIs.
+pub struct ToolCallAccumulator {
+    arguments_pointer: String,
+    calls: BTreeMap<u64, ToolCallBuffer>,
+}
+
+impl Default for ToolCallAccumulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ToolCallAccumulator {
+    pub fn new() -> Self {
+        Self { arguments_pointer: "/function/arguments".to_owned(), calls: BTreeMap::new() }
+    }
+
+    /// Looks for the argument fragments at a different JSON pointer inside each delta
+    pub fn with_arguments_pointer(mut self, pointer: impl Into<String>) -> Self {
+        self.arguments_pointer = pointer.into();
+        self
+    }
+
+    /// Merges a tool-call delta, or an array of them (e.g. the `tool_calls` of a
+    /// chunk). Deltas without an `index` are taken to belong to call 0.
+    ///
+    /// Deltas are always merged, even if their arguments aren't valid JSON: the
+    /// error is reported afterwards, and the raw arguments and other fields of
+    /// the call stay available. With several deltas, the first error is returned.
+    pub fn push(&mut self, delta: Value) -> Result<(), Error> {
+        let Value::Array(deltas) = delta else {
+            return self.push_one(delta)
+        };
+        let mut result = Ok(());
+        for delta in deltas {
+            let pushed = self.push_one(delta);
+            if result.is_ok() {
+                result = pushed;
+            }
+        }
+        result
+    }
+
+    fn push_one(&mut self, mut delta: Value) -> Result<(), Error> {
+        let index = delta.get("index").and_then(Value::as_u64).unwrap_or(0);
+        let call = self.calls.entry(index).or_insert_with(ToolCallBuffer::new);
+        let fragment = take_pointer(&mut delta, &self.arguments_pointer);
+        merge_delta(&mut call.fields, delta);
+        if let Some(Value::String(fragment)) = fragment {
+            call.arguments.push_str(&fragment);
+            call.parser.push(fragment.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, index: u64) -> Option<&ToolCallBuffer> {
+        self.calls.get(&index)
+    }
+
+    /// All calls seen so far, ordered by index
+    pub fn calls(&self) -> impl Iterator<Item = (u64, &ToolCallBuffer)> {
+        self.calls.iter().map(|(index, call)| (*index, call))
+    }
+}
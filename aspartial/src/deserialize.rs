@@ -0,0 +1,226 @@
+//! Deserialization of partials out of values that may still be arriving.
+//!
+//! A [Value] doesn't tell whether a string in it is complete, but partial enums
+//! need to know that about their tag: a tag like `"Get"` is final once its closing
+//! quote arrived, but while it's still being received it may turn into `"GetAll"`.
+//! [from_value_with_completion] keeps track of where in the value each partial is
+//! deserialized from, so that they can look themselves up in the [Completion].
+
+use std::cell::RefCell;
+
+use serde::de::value::BorrowedStrDeserializer;
+use serde::de::{DeserializeOwned, DeserializeSeed, MapAccess, SeqAccess, Visitor};
+use serde::Deserializer;
+use serde_json::Value;
+
+use crate::json::Completion;
+use crate::path::{FieldPath, PathSegment};
+
+struct Context {
+    completion: Completion,
+    /// Where the value currently being deserialized sits in the whole document
+    path: FieldPath,
+    /// The paths at which [pending_key] was asked for
+    key_lookups: Vec<FieldPath>,
+}
+
+thread_local! {
+    static CONTEXT: RefCell<Option<Context>> = const { RefCell::new(None) };
+}
+
+/// Restores the previous context when a deserialization is done, even if it panicked
+struct ContextGuard(Option<Context>);
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        CONTEXT.with_borrow_mut(|context| *context = previous);
+    }
+}
+
+/// Deserializes `T` out of `value`, where `completion` tells which parts of `value`
+/// are still arriving (see [crate::json::IncrementalParser::completion])
+pub fn from_value_with_completion<T>(value: &Value, completion: &Completion) -> Result<T, serde_json::Error>
+where
+    T: DeserializeOwned,
+{
+    from_value_with_key_lookups(value, completion).0
+}
+
+/// Like [from_value_with_completion], also returning the paths at which partials looked
+/// for keys without a value yet. Changes to pending keys elsewhere can't change `T`
+pub(crate) fn from_value_with_key_lookups<T>(
+    value: &Value, completion: &Completion,
+) -> (Result<T, serde_json::Error>, Vec<FieldPath>)
+where
+    T: DeserializeOwned,
+{
+    let context = Context { completion: completion.clone(), path: FieldPath::root(), key_lookups: Vec::new() };
+    let _guard = ContextGuard(CONTEXT.with_borrow_mut(|current| current.replace(context)));
+    let out = T::deserialize(ValueAt(value));
+    let key_lookups = CONTEXT.with_borrow_mut(|context| {
+        context.as_mut().map(|context| std::mem::take(&mut context.key_lookups)).unwrap_or_default()
+    });
+    (out, key_lookups)
+}
+
+fn at_segment<R>(segment: impl Into<PathSegment>, f: impl FnOnce() -> R) -> R {
+    let is_tracked = CONTEXT.with_borrow_mut(|context| match context {
+        Some(context) => {
+            context.path.push(segment);
+            true
+        },
+        None => false,
+    });
+    let out = f();
+    if is_tracked {
+        CONTEXT.with_borrow_mut(|context| context.as_mut().map(|context| context.path.pop()));
+    }
+    out
+}
+
+/// Whether the string at `segment` inside of the value being deserialized (or the value
+/// itself) is still being received. Nothing is, outside of [from_value_with_completion]
+#[doc(hidden)]
+pub fn is_streaming(segment: Option<&str>) -> bool {
+    CONTEXT.with_borrow(|context| {
+        let Some(context) = context else {
+            return false
+        };
+        let Some(leaf) = context.completion.streaming_leaf() else {
+            return false
+        };
+        match segment {
+            Some(segment) => *leaf == context.path.join(segment),
+            None => *leaf == context.path,
+        }
+    })
+}
+
+/// The key of the value being deserialized that has no value yet, and whether it is
+/// still being received (see [Completion::pending_key])
+#[doc(hidden)]
+pub fn pending_key() -> Option<(String, bool)> {
+    CONTEXT.with_borrow_mut(|context| {
+        let context = context.as_mut()?;
+        if !context.key_lookups.contains(&context.path) {
+            context.key_lookups.push(context.path.clone());
+        }
+        if context.completion.in_progress() != Some(&context.path) {
+            return None
+        }
+        let key = context.completion.pending_key()?;
+        Some((key.to_owned(), context.completion.key_is_streaming()))
+    })
+}
+
+/// Deserializes a value nested at `segment` inside of the one being deserialized
+/// (or the same one, for e.g. internally tagged payloads)
+#[doc(hidden)]
+pub fn from_nested_value<T>(value: &Value, segment: Option<&str>) -> Result<T, serde_json::Error>
+where
+    T: DeserializeOwned,
+{
+    match segment {
+        Some(segment) => at_segment(segment, || T::deserialize(ValueAt(value))),
+        None => T::deserialize(ValueAt(value)),
+    }
+}
+
+/// A deserializer for a borrowed [Value] that tracks the path to the entries and
+/// items it hands out
+struct ValueAt<'v>(&'v Value);
+
+impl<'de> Deserializer<'de> for ValueAt<'de> {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Object(map) => {
+                let mut entries = EntriesAt { entries: map.iter(), value: None };
+                let out = visitor.visit_map(&mut entries)?;
+                match entries.entries.len() {
+                    0 => Ok(out),
+                    _ => Err(serde::de::Error::invalid_length(map.len(), &"fewer elements in map")),
+                }
+            },
+            Value::Array(items) => {
+                let mut remaining = ItemsAt { items: items.iter().enumerate() };
+                let out = visitor.visit_seq(&mut remaining)?;
+                match remaining.items.len() {
+                    0 => Ok(out),
+                    _ => Err(serde::de::Error::invalid_length(items.len(), &"fewer elements in array")),
+                }
+            },
+            scalar => scalar.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self, name: &'static str, variants: &'static [&'static str], visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.0.deserialize_enum(name, variants, visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct EntriesAt<'v> {
+    entries: serde_json::map::Iter<'v>,
+    value: Option<(&'v String, &'v Value)>,
+}
+
+impl<'de> MapAccess<'de> for EntriesAt<'de> {
+    type Error = serde_json::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+        let Some((key, value)) = self.entries.next() else {
+            return Ok(None)
+        };
+        self.value = Some((key, value));
+        seed.deserialize(BorrowedStrDeserializer::new(key)).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+        let (key, value) = self.value.take()
+            .ok_or_else(|| <serde_json::Error as serde::de::Error>::custom("value requested before its key"))?;
+        at_segment(key.as_str(), || seed.deserialize(ValueAt(value)))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+struct ItemsAt<'v> {
+    items: std::iter::Enumerate<std::slice::Iter<'v, Value>>,
+}
+
+impl<'de> SeqAccess<'de> for ItemsAt<'de> {
+    type Error = serde_json::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error> {
+        let Some((idx, item)) = self.items.next() else {
+            return Ok(None)
+        };
+        at_segment(idx, || seed.deserialize(ValueAt(item))).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
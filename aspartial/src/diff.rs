@@ -0,0 +1,167 @@
+//! Minimal partials describing how a value changed.
+
+use serde_json::Value;
+
+use crate::AsPartial;
+use crate::path::FieldPath;
+
+/// A type whose values can be compared into the smallest partial that turns one
+/// into the other (see [crate::ApplyPartial]). Generated by `#[derive(AsPartial)]` with `#[aspartial(diff)]`.
+pub trait Diff: AsPartial + Sized {
+    /// The partial that turns `self` into `new`, holding only what changed, or
+    /// `new` itself, given back, if nothing did
+    fn diff_or_same(&self, new: Self) -> Result<Self::Partial, Self>;
+
+    /// The partial that turns `self` into `new`, or `None` if they are equal
+    fn diff(&self, new: Self) -> Option<Self::Partial> {
+        self.diff_or_same(new).ok()
+    }
+}
+
+/// `None` in the diff clears the value
+impl<T: Diff> Diff for Option<T> {
+    fn diff_or_same(&self, new: Self) -> Result<Self::Partial, Self> {
+        match (self, new) {
+            (Some(old), Some(new)) => old.diff_or_same(new).map(Some).map_err(Some),
+            (None, None) => Err(None),
+            (Some(_), None) => Ok(None),
+            (None, Some(new)) => Ok(Some(new.to_partial())),
+        }
+    }
+}
+
+/// Any change produces the whole new list, with the items that kept their
+/// index diffed against their old versions
+impl<T: Diff> Diff for Vec<T> {
+    fn diff_or_same(&self, new: Self) -> Result<Self::Partial, Self> {
+        let same_len = self.len() == new.len();
+        let mut items: Vec<Result<T::Partial, T>> = Vec::with_capacity(new.len());
+        let mut new = new.into_iter();
+        for (old, new) in self.iter().zip(new.by_ref()) {
+            items.push(old.diff_or_same(new));
+        }
+        items.extend(new.map(Err));
+        if same_len && items.iter().all(Result::is_err) {
+            return Err(items.into_iter().filter_map(Result::err).collect())
+        }
+        Ok(items.into_iter().map(|item| item.unwrap_or_else(AsPartial::to_partial)).collect())
+    }
+}
+
+/// A field of a type deriving `Diff`. Fields whose type implements [Diff] are diffed with it,
+/// while other fields with `PartialEq` are replaced as a whole when they changed: the
+/// `diff_field` of `(&DiffField(old))` resolves to [DiffFieldByDiff] if it can, and
+/// otherwise to [DiffFieldByEq], which takes one more reference.
+#[doc(hidden)]
+pub struct DiffField<'old, T>(pub &'old T);
+
+#[doc(hidden)]
+pub trait DiffFieldByDiff<T: AsPartial> {
+    fn diff_field(&self, new: T) -> Result<T::Partial, T>;
+}
+
+impl<T: Diff> DiffFieldByDiff<T> for DiffField<'_, T> {
+    fn diff_field(&self, new: T) -> Result<T::Partial, T> {
+        self.0.diff_or_same(new)
+    }
+}
+
+#[doc(hidden)]
+pub trait DiffFieldByEq<T: AsPartial> {
+    fn diff_field(&self, new: T) -> Result<T::Partial, T>;
+}
+
+impl<T: AsPartial + PartialEq> DiffFieldByEq<T> for &DiffField<'_, T> {
+    fn diff_field(&self, new: T) -> Result<T::Partial, T> {
+        if *self.0 == new { Err(new) } else { Ok(new.to_partial()) }
+    }
+}
+
+impl Diff for std::sync::Arc<str> {
+    fn diff_or_same(&self, new: Self) -> Result<Self::Partial, Self> {
+        if *self == new { Err(new) } else { Ok(new.to_partial()) }
+    }
+}
+
+impl Diff for serde_json::Map<String, Value> {
+    fn diff_or_same(&self, new: Self) -> Result<Self::Partial, Self> {
+        if *self == new { Err(new) } else { Ok(new) }
+    }
+}
+
+impl Diff for std::collections::HashMap<String, Value> {
+    fn diff_or_same(&self, new: Self) -> Result<Self::Partial, Self> {
+        if *self == new { Err(new) } else { Ok(new) }
+    }
+}
+
+impl Diff for std::collections::BTreeMap<String, Value> {
+    fn diff_or_same(&self, new: Self) -> Result<Self::Partial, Self> {
+        if *self == new { Err(new) } else { Ok(new) }
+    }
+}
+
+#[cfg(feature="iso8601")]
+impl Diff for iso8601_timestamp::Timestamp {
+    fn diff_or_same(&self, new: Self) -> Result<Self::Partial, Self> {
+        if *self == new { Err(new) } else { Ok(new.to_partial()) }
+    }
+}
+
+/// A single difference between two values, at a path in their serialized form
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub path: FieldPath,
+    /// The old value, or `None` if it was absent
+    pub old: Option<Value>,
+    /// The new value, or `None` if it was removed
+    pub new: Option<Value>,
+}
+
+/// Rendered like `address.city: "Lisboa" -> "Porto"`
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: ", self.path)?;
+        match &self.old {
+            Some(old) => write!(f, "{old}")?,
+            None => write!(f, "(absent)")?,
+        }
+        write!(f, " -> ")?;
+        match &self.new {
+            Some(new) => write!(f, "{new}"),
+            None => write!(f, "(absent)"),
+        }
+    }
+}
+
+fn collect_changes(old: Option<&Value>, new: Option<&Value>, path: &mut FieldPath, out: &mut Vec<Change>) {
+    match (old, new) {
+        (Some(Value::Object(old)), Some(Value::Object(new))) => {
+            let removed = old.keys().filter(|key| !new.contains_key(*key));
+            for key in new.keys().chain(removed) {
+                path.push(key.as_str());
+                collect_changes(old.get(key), new.get(key), path, out);
+                path.pop();
+            }
+        },
+        (Some(Value::Array(old)), Some(Value::Array(new))) => {
+            for idx in 0..old.len().max(new.len()) {
+                path.push(idx);
+                collect_changes(old.get(idx), new.get(idx), path, out);
+                path.pop();
+            }
+        },
+        (old, new) if old == new => (),
+        (old, new) => out.push(Change { path: path.clone(), old: old.cloned(), new: new.cloned() }),
+    }
+}
+
+/// Lists every leaf that differs between the serialized forms of `old` and
+/// `new`, e.g. for audit logs
+pub fn changes<T: serde::Serialize>(old: &T, new: &T) -> Result<Vec<Change>, serde_json::Error> {
+    let old = serde_json::to_value(old)?;
+    let new = serde_json::to_value(new)?;
+    let mut out = Vec::new();
+    collect_changes(Some(&old), Some(&new), &mut FieldPath::root(), &mut out);
+    Ok(out)
+}
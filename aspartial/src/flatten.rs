@@ -0,0 +1,91 @@
+//! The partials of `#[serde(flatten)]` fields.
+//!
+//! Serde reads a flattened field from the entries of the struct that the other fields
+//! left over, and externally or adjacently tagged enums take the entries they read, so
+//! that a catch-all map flattened after them doesn't see those. Partial enums are read
+//! from a whole [serde_json::Value] and would take nothing, so derived partials read and
+//! write their flattened fields through [Flatten] instead, with the functions in this
+//! module as `#[serde(with = "::aspartial::flatten")]`.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::de::{DeserializeOwned, Error as _, MapAccess, Visitor};
+use serde::ser::{Error as _, SerializeMap};
+use serde::{Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+/// How a partial is read from and written to the entries of the struct it is flattened into.
+/// Derived partials and maps implement it; by default serde is left to do both.
+pub trait Flatten: Sized {
+    fn deserialize_flattened<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+    where Self: DeserializeOwned
+    {
+        Self::deserialize(deserializer)
+    }
+
+    fn serialize_flattened<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where Self: Serialize
+    {
+        self.serialize(serializer)
+    }
+}
+
+impl<V> Flatten for HashMap<String, V> {}
+
+impl<V> Flatten for BTreeMap<String, V> {}
+
+impl Flatten for Map<String, Value> {}
+
+/// Reads a partial from the entries with the given `keys` only, taking them away from
+/// the fields flattened after it, like serde does for externally and adjacently tagged enums
+pub fn deserialize_entries<'de, D, T>(deserializer: D, name: &'static str, keys: &'static [&'static str]) -> Result<T, D::Error>
+where D: Deserializer<'de>, T: DeserializeOwned
+{
+    struct EntriesVisitor;
+
+    impl<'de> Visitor<'de> for EntriesVisitor {
+        type Value = Map<String, Value>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a map")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Self::Value, A::Error> {
+            let mut entries = Map::new();
+            while let Some((key, value)) = access.next_entry::<String, Value>()? {
+                entries.insert(key, value);
+            }
+            Ok(entries)
+        }
+    }
+
+    let entries = deserializer.deserialize_struct(name, keys, EntriesVisitor)?;
+    T::deserialize(Value::Object(entries)).map_err(D::Error::custom)
+}
+
+/// Writes the partial of an externally tagged enum, turning a unit variant (serialized as
+/// its bare tag) into a `tag: null` entry like serde does for flattened unit variants
+pub fn serialize_external<S: Serializer, T: Serialize>(partial: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    match serde_json::to_value(partial).map_err(S::Error::custom)? {
+        Value::String(tag) => {
+            let mut map = serializer.serialize_map(Some(1))?;
+            map.serialize_entry(&tag, &())?;
+            map.end()
+        },
+        value => value.serialize(serializer),
+    }
+}
+
+/// Serializes the partial of a flattened field, which is left out if it hasn't arrived
+pub fn serialize<S: Serializer, T: Flatten + Serialize>(partial: &Option<T>, serializer: S) -> Result<S::Ok, S::Error> {
+    match partial {
+        Some(partial) => partial.serialize_flattened(serializer),
+        None => serializer.serialize_map(Some(0))?.end(),
+    }
+}
+
+/// Deserializes the partial of a flattened field. Like serde does for flattened `Option`s,
+/// entries that can't be read as `T` leave it missing rather than failing the whole struct.
+pub fn deserialize<'de, D: Deserializer<'de>, T: Flatten + DeserializeOwned>(deserializer: D) -> Result<Option<T>, D::Error> {
+    Ok(T::deserialize_flattened(deserializer).ok())
+}
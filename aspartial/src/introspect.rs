@@ -0,0 +1,207 @@
+//! Checking how much of a partial has arrived.
+
+use crate::path::{FieldPath, PathSegment};
+use crate::{Defaulted, Tristate};
+
+/// A partial type that can list which of its fields are present and which are
+/// still missing. Generated by `#[derive(AsPartial)]` for partial types.
+///
+/// Paths point at leaves: scalars, empty lists, `null`s and fields that haven't
+/// arrived, through struct fields, list indices and the fields of the variant
+/// that a partial enum resolves to. Fields that hold a serde default that
+/// wasn't supplied are neither present nor missing.
+pub trait Introspect {
+    /// Records the leaves of this partial into `report`, under its current path
+    fn report_fields(&self, report: &mut FieldReport);
+
+    fn field_report(&self) -> FieldReport {
+        let mut report = FieldReport::default();
+        self.report_fields(&mut report);
+        report
+    }
+
+    fn present_paths(&self) -> Vec<FieldPath> {
+        self.field_report().present
+    }
+
+    fn missing_paths(&self) -> Vec<FieldPath> {
+        self.field_report().missing
+    }
+
+    /// Whether nothing has arrived yet
+    fn is_empty(&self) -> bool {
+        self.field_report().present.is_empty()
+    }
+
+    /// Whether every field has arrived
+    fn is_complete(&self) -> bool {
+        self.field_report().missing.is_empty()
+    }
+
+    /// The fraction of leaves that are present, from 0.0 to 1.0
+    fn completeness(&self) -> f64 {
+        self.field_report().completeness()
+    }
+}
+
+/// The leaves of a partial, split into those that are present and those that are missing
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldReport {
+    pub present: Vec<FieldPath>,
+    pub missing: Vec<FieldPath>,
+    path: FieldPath,
+}
+
+impl FieldReport {
+    /// The fraction of leaves that are present. Reports without leaves are complete.
+    pub fn completeness(&self) -> f64 {
+        let total = self.present.len() + self.missing.len();
+        if total == 0 {
+            return 1.0
+        }
+        self.present.len() as f64 / total as f64
+    }
+
+    /// Records the current path as present
+    #[doc(hidden)]
+    pub fn present(&mut self) {
+        self.present.push(self.path.clone());
+    }
+
+    /// Records the current path as missing
+    #[doc(hidden)]
+    pub fn missing(&mut self) {
+        self.missing.push(self.path.clone());
+    }
+
+    /// Records the leaves of `partial` under `segment`
+    #[doc(hidden)]
+    pub fn field<P: Introspect + ?Sized>(&mut self, segment: Option<PathSegment>, partial: &P) {
+        let Some(segment) = segment else {
+            return partial.report_fields(self)
+        };
+        self.path.push(segment);
+        partial.report_fields(self);
+        self.path.pop();
+    }
+
+    /// Records `segment` itself as present or missing
+    #[doc(hidden)]
+    pub fn leaf(&mut self, segment: Option<PathSegment>, is_present: bool) {
+        let nested = segment.map(|segment| self.path.push(segment)).is_some();
+        match is_present {
+            true => self.present(),
+            false => self.missing(),
+        }
+        if nested {
+            self.path.pop();
+        }
+    }
+}
+
+/// `None` is an explicit `null`, which is present
+impl<T: Introspect> Introspect for Option<T> {
+    fn report_fields(&self, report: &mut FieldReport) {
+        match self {
+            Some(value) => value.report_fields(report),
+            None => report.present(),
+        }
+    }
+}
+
+/// Empty lists are present leaves
+impl<T: Introspect> Introspect for Vec<T> {
+    fn report_fields(&self, report: &mut FieldReport) {
+        if self.is_empty() {
+            return report.present()
+        }
+        for (idx, item) in self.iter().enumerate() {
+            report.field(Some(PathSegment::Index(idx)), item);
+        }
+    }
+}
+
+impl<T: Introspect> Introspect for Tristate<T> {
+    fn report_fields(&self, report: &mut FieldReport) {
+        match self {
+            Self::Missing => report.missing(),
+            Self::Null => report.present(),
+            Self::Value(value) => value.report_fields(report),
+        }
+    }
+}
+
+impl<T: Introspect> Introspect for Defaulted<T> {
+    fn report_fields(&self, report: &mut FieldReport) {
+        if let Self::Supplied(value) = self {
+            value.report_fields(report)
+        }
+    }
+}
+
+impl Introspect for serde_json::Map<String, serde_json::Value> {
+    fn report_fields(&self, report: &mut FieldReport) {
+        report.present()
+    }
+}
+
+impl Introspect for std::collections::HashMap<String, serde_json::Value> {
+    fn report_fields(&self, report: &mut FieldReport) {
+        report.present()
+    }
+}
+
+impl Introspect for std::collections::BTreeMap<String, serde_json::Value> {
+    fn report_fields(&self, report: &mut FieldReport) {
+        report.present()
+    }
+}
+
+/// A field of a derived partial. Fields implementing [Introspect] report their leaves,
+/// while hand-written partials without it are leaves themselves: `report_field` of
+/// `(&&&IntrospectField(&partial))` resolves to [IntrospectFieldByIntrospect] if it can,
+/// otherwise to [IntrospectFieldByWrapper] for the wrappers of derived fields, which
+/// tell whether the field arrived, and otherwise to [IntrospectFieldAsLeaf].
+#[doc(hidden)]
+pub struct IntrospectField<'p, T>(pub &'p T);
+
+#[doc(hidden)]
+pub trait IntrospectFieldByIntrospect {
+    fn report_field(&self, report: &mut FieldReport, segment: Option<PathSegment>);
+}
+
+impl<T: Introspect> IntrospectFieldByIntrospect for &&IntrospectField<'_, T> {
+    fn report_field(&self, report: &mut FieldReport, segment: Option<PathSegment>) {
+        report.field(segment, self.0)
+    }
+}
+
+#[doc(hidden)]
+pub trait IntrospectFieldByWrapper {
+    fn report_field(&self, report: &mut FieldReport, segment: Option<PathSegment>);
+}
+
+impl<T> IntrospectFieldByWrapper for &IntrospectField<'_, Tristate<T>> {
+    fn report_field(&self, report: &mut FieldReport, segment: Option<PathSegment>) {
+        report.leaf(segment, !matches!(self.0, Tristate::Missing))
+    }
+}
+
+impl<T> IntrospectFieldByWrapper for &IntrospectField<'_, Defaulted<T>> {
+    fn report_field(&self, report: &mut FieldReport, segment: Option<PathSegment>) {
+        if let Defaulted::Supplied(_) = self.0 {
+            report.leaf(segment, true)
+        }
+    }
+}
+
+#[doc(hidden)]
+pub trait IntrospectFieldAsLeaf {
+    fn report_field(&self, report: &mut FieldReport, segment: Option<PathSegment>);
+}
+
+impl<T> IntrospectFieldAsLeaf for IntrospectField<'_, T> {
+    fn report_field(&self, report: &mut FieldReport, segment: Option<PathSegment>) {
+        report.leaf(segment, true)
+    }
+}
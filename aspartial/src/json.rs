@@ -0,0 +1,777 @@
+//! Parsing of JSON text that may still be arriving, or that got truncated.
+
+use serde_json::{Map, Value};
+
+use crate::AsPartial;
+use crate::deserialize::from_value_with_completion;
+use crate::path::FieldPath;
+
+/// The text fed so far is not the beginning of any valid JSON document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    offset: usize,
+    message: &'static str,
+}
+
+impl SyntaxError {
+    /// Byte offset in the input where the error was detected
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl std::fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at byte {}", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for SyntaxError {}
+
+/// Errors produced while turning (possibly incomplete) JSON text into a partial
+#[derive(Debug)]
+pub enum Error {
+    /// The text is not the beginning of a valid JSON document
+    Syntax(SyntaxError),
+    /// No value has started arriving yet
+    Empty,
+    /// The recovered value does not fit the requested partial type
+    Deserialize(serde_json::Error),
+    /// The text could not be read from its source
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Syntax(err) => write!(f, "invalid JSON: {err}"),
+            Self::Empty => write!(f, "no JSON value found"),
+            Self::Deserialize(err) => write!(f, "could not deserialize: {err}"),
+            Self::Io(err) => write!(f, "could not read: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Syntax(err) => Some(err),
+            Self::Empty => None,
+            Self::Deserialize(err) => Some(err),
+            Self::Io(err) => Some(err),
+        }
+    }
+}
+
+impl From<SyntaxError> for Error {
+    fn from(value: SyntaxError) -> Self {
+        Self::Syntax(value)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Deserialize(value)
+    }
+}
+
+/// Which parts of a streamed document have been completely received.
+///
+/// JSON arrives in order, so everything received so far is complete except
+/// for the values along a single path, from the root down to the value that
+/// is currently arriving. Paths follow the serialized shape of the data, e.g.
+/// for an internally tagged enum the variant's fields sit right next to the tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    open: Option<FieldPath>,
+    open_is_leaf: bool,
+    /// A key of the innermost open object that has no value yet, and whether
+    /// it is still being received
+    pending_key: Option<(String, bool)>,
+}
+
+impl Completion {
+    /// Whether the whole document has been received
+    pub fn is_done(&self) -> bool {
+        self.open.is_none()
+    }
+
+    /// The innermost value that is still open
+    pub fn in_progress(&self) -> Option<&FieldPath> {
+        self.open.as_ref()
+    }
+
+    /// The leaf value (e.g. a string) that is currently being received, if any
+    pub fn streaming_leaf(&self) -> Option<&FieldPath> {
+        self.open.as_ref().filter(|_| self.open_is_leaf)
+    }
+
+    /// A key of the object at [Self::in_progress] that has no value yet, e.g. the
+    /// `"Get` of `{"Get` or the `"Get"` of `{"Get": `. Such keys are left out of
+    /// [IncrementalParser::value]
+    pub fn pending_key(&self) -> Option<&str> {
+        self.pending_key.as_ref().map(|(key, _)| key.as_str())
+    }
+
+    /// Whether the [Self::pending_key] is still being received, so it may grow longer
+    pub fn key_is_streaming(&self) -> bool {
+        self.pending_key.as_ref().is_some_and(|(_, is_streaming)| *is_streaming)
+    }
+
+    /// Whether the value at `path`, if present, will not change anymore
+    pub fn is_complete(&self, path: &FieldPath) -> bool {
+        match &self.open {
+            None => true,
+            Some(open) => !open.starts_with(path),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ObjectExpect {
+    FirstKeyOrEnd,
+    Key,
+    Colon,
+    Value,
+    CommaOrEnd,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ArrayExpect {
+    FirstValueOrEnd,
+    Value,
+    CommaOrEnd,
+}
+
+/// A container that is still open. `cut` is the offset where the text can be
+/// truncated to drop the member currently being received
+enum Frame {
+    Object{ map: Map<String, Value>, key: Option<String>, expect: ObjectExpect, cut: usize },
+    Array{ items: Vec<Value>, expect: ArrayExpect, cut: usize },
+}
+
+#[derive(Clone, Copy)]
+enum Escape {
+    None,
+    Backslash,
+    Unicode{ code: u32, digits: u8 },
+}
+
+struct StringToken {
+    is_key: bool,
+    /// Decoded contents, as UTF-8. The tail may be an incomplete multi-byte sequence
+    decoded: Vec<u8>,
+    escape: Escape,
+    high_surrogate: Option<u32>,
+    /// Continuation bytes still missing from the last multi-byte character
+    utf8_missing: u8,
+    /// Offset right after the last fully received character
+    safe_end: usize,
+}
+
+enum StringStep {
+    Continue,
+    Done(String),
+}
+
+impl StringToken {
+    fn push_char(&mut self, c: char) {
+        let mut buf = [0u8; 4];
+        self.decoded.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+    }
+
+    fn push_code_unit(&mut self, code: u32) -> Result<(), &'static str> {
+        match (self.high_surrogate.take(), code) {
+            (None, 0xD800..=0xDBFF) => {
+                self.high_surrogate = Some(code);
+                return Ok(())
+            },
+            (None, 0xDC00..=0xDFFF) => return Err("lone surrogate in string"),
+            (None, code) => self.push_char(char::from_u32(code).ok_or("invalid escape")?),
+            (Some(high), 0xDC00..=0xDFFF) => {
+                let combined = 0x10000 + ((high - 0xD800) << 10) + (code - 0xDC00);
+                self.push_char(char::from_u32(combined).ok_or("invalid escape")?)
+            },
+            (Some(_), _) => return Err("lone surrogate in string"),
+        }
+        Ok(())
+    }
+
+    fn step(&mut self, b: u8) -> Result<StringStep, &'static str> {
+        match self.escape {
+            Escape::None => {
+                if self.high_surrogate.is_some() && b != b'\\' {
+                    return Err("lone surrogate in string")
+                }
+                match b {
+                    b'"' => {
+                        let decoded = std::mem::take(&mut self.decoded);
+                        let text = String::from_utf8(decoded).map_err(|_| "invalid UTF-8 in string")?;
+                        return Ok(StringStep::Done(text))
+                    },
+                    b'\\' => self.escape = Escape::Backslash,
+                    0x00..=0x1F => return Err("control character in string"),
+                    _ => {
+                        self.utf8_missing = match b {
+                            0x80..=0xBF => self.utf8_missing.saturating_sub(1),
+                            0xC0..=0xDF => 1,
+                            0xE0..=0xEF => 2,
+                            0xF0..=0xFF => 3,
+                            _ => 0,
+                        };
+                        self.decoded.push(b)
+                    },
+                }
+            },
+            Escape::Backslash => {
+                if self.high_surrogate.is_some() && b != b'u' {
+                    return Err("lone surrogate in string")
+                }
+                self.escape = Escape::None;
+                let unescaped = match b {
+                    b'"' => '"',
+                    b'\\' => '\\',
+                    b'/' => '/',
+                    b'b' => '\u{8}',
+                    b'f' => '\u{c}',
+                    b'n' => '\n',
+                    b'r' => '\r',
+                    b't' => '\t',
+                    b'u' => {
+                        self.escape = Escape::Unicode { code: 0, digits: 0 };
+                        return Ok(StringStep::Continue)
+                    },
+                    _ => return Err("invalid escape"),
+                };
+                self.push_char(unescaped);
+            },
+            Escape::Unicode { code, digits } => {
+                let digit = (b as char).to_digit(16).ok_or("invalid unicode escape")?;
+                let code = code * 16 + digit;
+                if digits + 1 < 4 {
+                    self.escape = Escape::Unicode { code, digits: digits + 1 };
+                } else {
+                    self.escape = Escape::None;
+                    self.push_code_unit(code)?;
+                }
+            },
+        }
+        Ok(StringStep::Continue)
+    }
+
+    fn ends_on_char_boundary(&self) -> bool {
+        matches!(self.escape, Escape::None) && self.high_surrogate.is_none() && self.utf8_missing == 0
+    }
+
+    /// The longest prefix of the contents that is known to be valid
+    fn text_so_far(&self) -> String {
+        let valid = match std::str::from_utf8(&self.decoded) {
+            Ok(text) => text,
+            Err(err) => std::str::from_utf8(&self.decoded[..err.valid_up_to()]).unwrap_or_default(),
+        };
+        valid.to_owned()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum NumberState {
+    Minus,
+    Zero,
+    Int,
+    Dot,
+    Frac,
+    Exp,
+    ExpSign,
+    ExpDigits,
+}
+
+impl NumberState {
+    fn is_terminal(self) -> bool {
+        matches!(self, Self::Zero | Self::Int | Self::Frac | Self::ExpDigits)
+    }
+
+    fn next(self, b: u8) -> Option<Self> {
+        Some(match (self, b) {
+            (Self::Minus, b'0') => Self::Zero,
+            (Self::Minus, b'1'..=b'9') => Self::Int,
+            (Self::Int, b'0'..=b'9') => Self::Int,
+            (Self::Zero | Self::Int, b'.') => Self::Dot,
+            (Self::Dot | Self::Frac, b'0'..=b'9') => Self::Frac,
+            (Self::Zero | Self::Int | Self::Frac, b'e' | b'E') => Self::Exp,
+            (Self::Exp, b'+' | b'-') => Self::ExpSign,
+            (Self::Exp | Self::ExpSign | Self::ExpDigits, b'0'..=b'9') => Self::ExpDigits,
+            _ => return None,
+        })
+    }
+}
+
+struct NumberToken {
+    text: String,
+    state: NumberState,
+}
+
+impl NumberToken {
+    fn to_value(&self) -> Option<Value> {
+        if !self.state.is_terminal() {
+            return None
+        }
+        serde_json::from_str(&self.text).ok()
+    }
+}
+
+struct LiteralToken {
+    expected: &'static str,
+    matched: usize,
+    value: Value,
+}
+
+enum Scalar {
+    String(StringToken),
+    Number(NumberToken),
+    Literal(LiteralToken),
+}
+
+/// What to do with a number at the very end of the text received so far.
+///
+/// Such a number might not be complete yet: a truncated `12` could really be
+/// `123` or `12.5`. Literals like `true` and `null` are never shown until
+/// they are complete, since a prefix like `tr` has no meaningful value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TrailingScalars {
+    /// Leave the number out until a delimiter shows that it is complete, so
+    /// that values never change once they show up in a partial
+    #[default]
+    Withhold,
+    /// Include the number as far as it has arrived
+    Include,
+}
+
+/// A push-based JSON parser that can be fed chunks of bytes as they arrive and
+/// that can produce a partial representation of the value at any point.
+///
+/// Objects, arrays and strings that haven't been closed yet are treated as
+/// "still arriving": they show up with whatever contents were received so far.
+/// Object keys that don't have a value yet are left out, and so are numbers
+/// that might still be arriving (see [TrailingScalars]).
+///
+/// State is kept between chunks, so each byte is only ever looked at once.
+pub struct IncrementalParser {
+    stack: Vec<Frame>,
+    scalar: Option<Scalar>,
+    root: Option<Value>,
+    offset: usize,
+    error: Option<SyntaxError>,
+    trailing_scalars: TrailingScalars,
+    generation: u64,
+    /// How many of the changes counted by `generation` only concerned pending keys
+    key_changes: u64,
+}
+
+impl Default for IncrementalParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IncrementalParser {
+    pub fn new() -> Self {
+        Self {
+            stack: Vec::new(),
+            scalar: None,
+            root: None,
+            offset: 0,
+            error: None,
+            trailing_scalars: TrailingScalars::default(),
+            generation: 0,
+            key_changes: 0,
+        }
+    }
+
+    pub fn with_trailing_scalars(mut self, trailing_scalars: TrailingScalars) -> Self {
+        self.trailing_scalars = trailing_scalars;
+        self
+    }
+
+    /// The raw text of the number or literal currently being received, e.g. `12`
+    /// or `tr`. These are not part of [Self::value] by default
+    pub fn pending_scalar(&self) -> Option<&str> {
+        match &self.scalar {
+            Some(Scalar::Number(token)) => Some(&token.text),
+            Some(Scalar::Literal(token)) => Some(&token.expected[..token.matched]),
+            _ => None,
+        }
+    }
+
+    /// Feeds the next chunk of JSON text. Chunks can be split anywhere, even in
+    /// the middle of multi-byte characters.
+    ///
+    /// Once an error is returned, the parser refuses any further input.
+    pub fn push(&mut self, chunk: &[u8]) -> Result<(), SyntaxError> {
+        if let Some(err) = &self.error {
+            return Err(err.clone())
+        }
+        for &b in chunk {
+            if let Err(message) = self.step(b) {
+                let err = SyntaxError { offset: self.offset, message };
+                self.error = Some(err.clone());
+                return Err(err)
+            }
+            self.offset += 1;
+        }
+        Ok(())
+    }
+
+    /// Number of bytes consumed so far
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// A counter that changes whenever [Self::value] may have changed, a string in
+    /// it has ended, or a key without a value yet has grown or ended. Comparing it
+    /// is much cheaper than comparing snapshots, which are rebuilt from scratch on
+    /// every call
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Like [Self::generation], leaving out the changes to keys without a value yet
+    pub(crate) fn value_generation(&self) -> u64 {
+        self.generation - self.key_changes
+    }
+
+    /// Whether the top-level value has been completely received
+    pub fn is_complete(&self) -> bool {
+        self.root.is_some()
+    }
+
+    /// A snapshot of the value received so far, or `None` if no value has started yet
+    pub fn value(&self) -> Option<Value> {
+        if let Some(root) = &self.root {
+            return Some(root.clone())
+        }
+        let mut tip = self.scalar.as_ref().and_then(|scalar| match scalar {
+            Scalar::String(token) if !token.is_key => Some(Value::String(token.text_so_far())),
+            Scalar::String(_) => None,
+            Scalar::Number(token) => self.trailing_number(token),
+            Scalar::Literal(_) => None,
+        });
+        for frame in self.stack.iter().rev() {
+            tip = Some(match frame {
+                Frame::Object { map, key, expect, .. } => {
+                    let mut map = map.clone();
+                    if let (ObjectExpect::Value, Some(key), Some(tip)) = (expect, key, tip) {
+                        map.insert(key.clone(), tip);
+                    }
+                    Value::Object(map)
+                },
+                Frame::Array { items, .. } => {
+                    let mut items = items.clone();
+                    items.extend(tip);
+                    Value::Array(items)
+                },
+            });
+        }
+        tip
+    }
+
+    /// Which parts of the value returned by [Self::value] are complete
+    pub fn completion(&self) -> Completion {
+        if self.root.is_some() {
+            return Completion { open: None, open_is_leaf: false, pending_key: None }
+        }
+        let mut open = FieldPath::root();
+        let mut frames = self.stack.iter().peekable();
+        while let Some(frame) = frames.next() {
+            let has_open_member = match frames.peek() {
+                Some(_) => true,
+                None => matches!(&self.scalar, Some(Scalar::Number(_) | Scalar::Literal(_)))
+                    || matches!(&self.scalar, Some(Scalar::String(token)) if !token.is_key),
+            };
+            if !has_open_member {
+                break
+            }
+            match frame {
+                Frame::Object { key: Some(key), .. } => open.push(key.as_str()),
+                Frame::Object { key: None, .. } => break,
+                Frame::Array { items, .. } => open.push(items.len()),
+            }
+        }
+        let open_is_leaf = match &self.scalar {
+            Some(Scalar::String(token)) => !token.is_key,
+            Some(_) => true,
+            None => false,
+        };
+        let pending_key = match (self.stack.last(), &self.scalar) {
+            (Some(Frame::Object { .. }), Some(Scalar::String(token))) if token.is_key => {
+                Some((token.text_so_far(), true))
+            },
+            (Some(Frame::Object { key: Some(key), expect: ObjectExpect::Colon | ObjectExpect::Value, .. }), None) => {
+                Some((key.clone(), false))
+            },
+            _ => None,
+        };
+        Completion { open: Some(open), open_is_leaf, pending_key }
+    }
+
+    /// Deserializes the value received so far into the partial representation of `T`.
+    ///
+    /// Returns `Ok(None)` if no value has started arriving yet.
+    pub fn partial<T>(&self) -> Result<Option<T::Partial>, Error>
+    where
+        T: AsPartial<Partial: serde::de::DeserializeOwned>,
+    {
+        let Some(value) = self.value() else {
+            return Ok(None)
+        };
+        Ok(Some(from_value_with_completion(&value, &self.completion())?))
+    }
+
+    /// Signals the end of the input, returning the complete value.
+    ///
+    /// This is needed for top-level numbers, which have no closing delimiter.
+    pub fn finish(mut self) -> Result<Value, SyntaxError> {
+        if let Some(err) = self.error {
+            return Err(err)
+        }
+        self.end_top_level_number();
+        self.root.ok_or(SyntaxError { offset: self.offset, message: "unexpected end of input" })
+    }
+
+    /// Where to truncate the text received so far and what to append to it so
+    /// that it becomes a complete document equivalent to [Self::value]
+    fn repair(&self) -> Option<(usize, String)> {
+        if self.root.is_some() {
+            return Some((self.offset, String::new()))
+        }
+        let (cut, mut suffix) = match &self.scalar {
+            Some(Scalar::String(token)) if !token.is_key => (token.safe_end, "\"".to_owned()),
+            Some(Scalar::Number(token)) if self.trailing_number(token).is_some() => (self.offset, String::new()),
+            _ => match self.stack.last() {
+                Some(Frame::Object { cut, .. } | Frame::Array { cut, .. }) => (*cut, String::new()),
+                None => return None,
+            },
+        };
+        if self.stack.is_empty() && self.scalar.is_some() {
+            // a top-level string or number
+            return Some((cut, suffix))
+        }
+        for frame in self.stack.iter().rev() {
+            suffix.push(match frame {
+                Frame::Object { .. } => '}',
+                Frame::Array { .. } => ']',
+            });
+        }
+        Some((cut, suffix))
+    }
+
+    /// Completes a top-level number, taking the end of the input as its end
+    fn end_top_level_number(&mut self) {
+        if self.stack.is_empty() && let Some(Scalar::Number(token)) = &self.scalar && let Some(value) = token.to_value() {
+            self.scalar = None;
+            self.complete_value(value);
+        }
+    }
+
+    fn trailing_number(&self, token: &NumberToken) -> Option<Value> {
+        match self.trailing_scalars {
+            TrailingScalars::Withhold => None,
+            TrailingScalars::Include => token.to_value(),
+        }
+    }
+
+    fn complete_value(&mut self, value: Value) {
+        self.generation += 1;
+        self.store_value(value);
+    }
+
+    /// Stores a value that was already part of [Self::value] as it was arriving
+    fn store_value(&mut self, value: Value) {
+        match self.stack.last_mut() {
+            None => self.root = Some(value),
+            Some(Frame::Object { map, key, expect, cut }) => {
+                if let Some(key) = key.take() {
+                    map.insert(key, value);
+                }
+                *expect = ObjectExpect::CommaOrEnd;
+                *cut = self.offset + 1;
+            },
+            Some(Frame::Array { items, expect, cut }) => {
+                items.push(value);
+                *expect = ArrayExpect::CommaOrEnd;
+                *cut = self.offset + 1;
+            },
+        }
+    }
+
+    fn close_frame(&mut self) {
+        let value = match self.stack.pop() {
+            Some(Frame::Object { map, .. }) => Value::Object(map),
+            Some(Frame::Array { items, .. }) => Value::Array(items),
+            None => return,
+        };
+        // the contents of a container show up as they arrive, so closing it changes nothing
+        self.store_value(value);
+    }
+
+    fn begin_value(&mut self, b: u8) -> Result<(), &'static str> {
+        let literal = |expected: &'static str, value: Value| Scalar::Literal(LiteralToken { expected, matched: 1, value });
+        if matches!(b, b'{' | b'[' | b'"') || self.trailing_scalars == TrailingScalars::Include {
+            self.generation += 1;
+        }
+        match b {
+            b'{' => self.stack.push(Frame::Object {
+                map: Map::new(), key: None, expect: ObjectExpect::FirstKeyOrEnd, cut: self.offset + 1
+            }),
+            b'[' => self.stack.push(Frame::Array {
+                items: Vec::new(), expect: ArrayExpect::FirstValueOrEnd, cut: self.offset + 1
+            }),
+            b'"' => self.begin_string(false),
+            b'-' => self.scalar = Some(Scalar::Number(NumberToken { text: "-".into(), state: NumberState::Minus })),
+            b'0' => self.scalar = Some(Scalar::Number(NumberToken { text: "0".into(), state: NumberState::Zero })),
+            b'1'..=b'9' => self.scalar = Some(Scalar::Number(NumberToken { text: (b as char).into(), state: NumberState::Int })),
+            b't' => self.scalar = Some(literal("true", Value::Bool(true))),
+            b'f' => self.scalar = Some(literal("false", Value::Bool(false))),
+            b'n' => self.scalar = Some(literal("null", Value::Null)),
+            _ => return Err("expected value"),
+        }
+        Ok(())
+    }
+
+    fn begin_string(&mut self, is_key: bool) {
+        self.scalar = Some(Scalar::String(StringToken {
+            is_key,
+            decoded: Vec::new(),
+            escape: Escape::None,
+            high_surrogate: None,
+            utf8_missing: 0,
+            safe_end: self.offset + 1,
+        }));
+    }
+
+    fn step(&mut self, b: u8) -> Result<(), &'static str> {
+        match &mut self.scalar {
+            None => (),
+            Some(Scalar::String(token)) => {
+                // keys still arriving are part of the completion (see [Completion::pending_key])
+                self.generation += 1;
+                if token.is_key {
+                    self.key_changes += 1;
+                }
+                let step = token.step(b)?;
+                if token.ends_on_char_boundary() {
+                    token.safe_end = self.offset + 1;
+                }
+                if let StringStep::Done(text) = step {
+                    let is_key = token.is_key;
+                    self.scalar = None;
+                    if is_key {
+                        if let Some(Frame::Object { key, expect, .. }) = self.stack.last_mut() {
+                            *key = Some(text);
+                            *expect = ObjectExpect::Colon;
+                        }
+                    } else {
+                        self.complete_value(Value::String(text));
+                    }
+                }
+                return Ok(())
+            },
+            Some(Scalar::Literal(token)) => {
+                if token.expected.as_bytes()[token.matched] != b {
+                    return Err("invalid literal")
+                }
+                token.matched += 1;
+                if token.matched == token.expected.len() {
+                    let value = token.value.take();
+                    self.scalar = None;
+                    self.complete_value(value);
+                }
+                return Ok(())
+            },
+            Some(Scalar::Number(token)) => {
+                if let Some(state) = token.state.next(b) {
+                    if self.trailing_scalars == TrailingScalars::Include {
+                        self.generation += 1;
+                    }
+                    token.state = state;
+                    token.text.push(b as char);
+                    return Ok(())
+                }
+                let value = token.to_value().ok_or("invalid number")?;
+                self.scalar = None;
+                self.complete_value(value);
+                // the current byte is not part of the number, so it's handled below
+            },
+        }
+
+        if b.is_ascii_whitespace() {
+            return Ok(())
+        }
+        let Some(frame) = self.stack.last_mut() else {
+            if self.root.is_some() {
+                return Err("trailing characters after value")
+            }
+            return self.begin_value(b)
+        };
+        match frame {
+            Frame::Object { expect, cut, .. } => match (*expect, b) {
+                (ObjectExpect::FirstKeyOrEnd | ObjectExpect::CommaOrEnd, b'}') => self.close_frame(),
+                (ObjectExpect::FirstKeyOrEnd | ObjectExpect::Key, b'"') => {
+                    self.generation += 1;
+                    self.key_changes += 1;
+                    self.begin_string(true);
+                },
+                (ObjectExpect::Colon, b':') => *expect = ObjectExpect::Value,
+                (ObjectExpect::Value, _) => self.begin_value(b)?,
+                (ObjectExpect::CommaOrEnd, b',') => {
+                    *expect = ObjectExpect::Key;
+                    *cut = self.offset;
+                },
+                (ObjectExpect::FirstKeyOrEnd | ObjectExpect::Key, _) => return Err("expected object key"),
+                (ObjectExpect::Colon, _) => return Err("expected ':'"),
+                (ObjectExpect::CommaOrEnd, _) => return Err("expected ',' or '}'"),
+            },
+            Frame::Array { expect, cut, .. } => match (*expect, b) {
+                (ArrayExpect::FirstValueOrEnd | ArrayExpect::CommaOrEnd, b']') => self.close_frame(),
+                (ArrayExpect::FirstValueOrEnd | ArrayExpect::Value, _) => self.begin_value(b)?,
+                (ArrayExpect::CommaOrEnd, b',') => {
+                    *expect = ArrayExpect::Value;
+                    *cut = self.offset;
+                },
+                (ArrayExpect::CommaOrEnd, _) => return Err("expected ',' or ']'"),
+            },
+        }
+        Ok(())
+    }
+}
+
+/// Closes any dangling strings, arrays and objects in a truncated JSON text,
+/// dropping whatever can't be salvaged (e.g. a `"key":` without a value, or a
+/// half-written escape sequence). Complete documents are returned untouched.
+///
+/// Nested numbers at the end of `text` are dropped, as they may have been cut
+/// short, but a top-level number is the whole document, and is kept.
+pub fn repair_truncated(text: &str) -> Result<String, Error> {
+    let mut parser = IncrementalParser::new();
+    parser.push(text.as_bytes())?;
+    parser.end_top_level_number();
+    let (cut, suffix) = parser.repair().ok_or(Error::Empty)?;
+    Ok(format!("{}{suffix}", &text[..cut]))
+}
+
+/// Deserializes the partial representation of `T` out of a possibly truncated JSON text.
+/// Like in [repair_truncated], a top-level number is taken as complete.
+pub fn from_str_truncated<T>(text: &str) -> Result<T::Partial, Error>
+where
+    T: AsPartial<Partial: serde::de::DeserializeOwned>,
+{
+    let mut parser = IncrementalParser::new();
+    parser.push(text.as_bytes())?;
+    parser.end_top_level_number();
+    parser.partial::<T>()?.ok_or(Error::Empty)
+}
@@ -1,5 +1,34 @@
 #![doc = include_str!("../README.md")]
 
+pub mod apply;
+pub mod complete;
+pub mod defaulted;
+pub mod delta;
+pub mod deserialize;
+pub mod diff;
+pub mod flatten;
+pub mod introspect;
+pub mod json;
+pub mod merge;
+pub mod patch;
+pub mod path;
+pub mod resolve;
+pub mod sse;
+pub mod stream;
+pub mod tag;
+pub mod testing;
+pub mod tristate;
+
+pub use apply::ApplyPartial;
+pub use complete::{CompletionError, FromPartial};
+pub use defaulted::Defaulted;
+pub use diff::Diff;
+pub use introspect::Introspect;
+pub use json::from_str_truncated;
+pub use merge::Merge;
+pub use tag::{TagMatch, UnknownTag};
+pub use tristate::Tristate;
+
 /// A type that can have a "partial" or "incomplete" representation. These are
 /// usually serializable types, and their "partial" representations are objects
 /// with missing fields in some serialized format like JSON.
@@ -15,7 +44,8 @@ pub use ::aspartial_derive::AsPartial;
 /// to handle incomplete data in self-describing formats (e.g. JSON, YAML).
 /// For convenience, the [AsSerializablePartial] trait is blanket-implemented
 /// for all types that implement [AsPartial] and whose partial version is also
-/// serializable
+/// serializable. Derived partials are, as long as the partials of their fields
+/// are, and leave out the fields that haven't arrived.
 pub trait AsSerializablePartial: AsPartial<Partial: serde::Serialize + serde::de::DeserializeOwned>
 {}
 
@@ -31,8 +61,40 @@ macro_rules! impl_AsPartial_as_Self { ( $type:ty ) => {
             self
         }
     }
+
+    impl FromPartial for $type{
+        fn from_partial(partial: Self::Partial) -> Result<Self, CompletionError> {
+            Ok(partial)
+        }
+    }
+
+    impl ApplyPartial for $type{
+        fn apply_partial(&mut self, partial: Self::Partial) -> Result<(), CompletionError> {
+            *self = partial;
+            Ok(())
+        }
+    }
+
+    impl Diff for $type{
+        fn diff_or_same(&self, new: Self) -> Result<Self::Partial, Self> {
+            if *self == new { Err(new) } else { Ok(new) }
+        }
+    }
+
+    impl Merge for $type{
+        fn merge(&mut self, overlay: Self) {
+            *self = overlay
+        }
+    }
+
+    impl Introspect for $type{
+        fn report_fields(&self, report: &mut introspect::FieldReport) {
+            report.present()
+        }
+    }
 };}
 
+impl_AsPartial_as_Self!(());
 impl_AsPartial_as_Self!(String);
 impl_AsPartial_as_Self!(bool);
 impl_AsPartial_as_Self!(usize);
@@ -60,7 +122,8 @@ impl AsPartial for std::sync::Arc<str>{
     }
 }
 
-//FIXME: T::Partial and not Option<T::Partial>??
+/// Derived partials represent fields spelled `Option<T>` with a [Tristate]
+/// instead, so that a missing field can be told apart from a `null` one
 impl<T: AsPartial> AsPartial for Option<T>{
     type Partial = Option<T::Partial>;
 
@@ -84,6 +147,21 @@ impl AsPartial for serde_json::Map<String, serde_json::Value>{
     }
 }
 
+/// Usually a `#[serde(flatten)]` catch-all for the keys the other fields don't claim
+impl AsPartial for std::collections::HashMap<String, serde_json::Value>{
+    type Partial = Self;
+    fn to_partial(self) -> Self::Partial {
+        self
+    }
+}
+
+impl AsPartial for std::collections::BTreeMap<String, serde_json::Value>{
+    type Partial = Self;
+    fn to_partial(self) -> Self::Partial {
+        self
+    }
+}
+
 #[cfg(feature="iso8601")]
 impl AsPartial for iso8601_timestamp::Timestamp {
     type Partial = String;
@@ -0,0 +1,162 @@
+//! Overlaying partials on top of each other.
+
+use std::marker::PhantomData;
+
+use serde_json::Value;
+
+use crate::defaulted::Defaulted;
+use crate::tristate::Tristate;
+
+/// A partial that can be combined with another one of the same type.
+/// Generated by `#[derive(AsPartial)]` for the partial types it creates.
+pub trait Merge {
+    /// Overlays `overlay` on top of `self`: whatever is present in `overlay`
+    /// wins, and nested partials are merged recursively rather than replaced.
+    fn merge(&mut self, overlay: Self);
+
+    /// Like [Merge::merge], but by value
+    fn merged(mut self, overlay: Self) -> Self
+    where
+        Self: Sized,
+    {
+        self.merge(overlay);
+        self
+    }
+}
+
+/// An absent overlay leaves `self` untouched, and an absent `self` takes the overlay as is
+impl<T: Merge> Merge for Option<T> {
+    fn merge(&mut self, overlay: Self) {
+        match (self, overlay) {
+            (_, None) => (),
+            (Some(base), Some(overlay)) => base.merge(overlay),
+            (base, overlay) => *base = overlay,
+        }
+    }
+}
+
+/// Items are merged by index, and any items past the end of `self` are appended
+impl<T: Merge> Merge for Vec<T> {
+    fn merge(&mut self, overlay: Self) {
+        let mut overlay = overlay.into_iter();
+        for (base, overlay) in self.iter_mut().zip(overlay.by_ref()) {
+            base.merge(overlay);
+        }
+        self.extend(overlay);
+    }
+}
+
+/// Objects are merged key by key; any other overlay replaces `self`
+impl Merge for Value {
+    fn merge(&mut self, overlay: Self) {
+        match (self, overlay) {
+            (Value::Object(base), Value::Object(overlay)) => base.merge(overlay),
+            (base, overlay) => *base = overlay,
+        }
+    }
+}
+
+impl Merge for serde_json::Map<String, Value> {
+    fn merge(&mut self, overlay: Self) {
+        for (key, overlay) in overlay {
+            match self.get_mut(&key) {
+                Some(base) => base.merge(overlay),
+                None => {
+                    self.insert(key, overlay);
+                },
+            }
+        }
+    }
+}
+
+/// Values under the same key are merged, and the other keys of the overlay are added
+impl<V: Merge> Merge for std::collections::HashMap<String, V> {
+    fn merge(&mut self, overlay: Self) {
+        for (key, overlay) in overlay {
+            match self.get_mut(&key) {
+                Some(base) => base.merge(overlay),
+                None => {
+                    self.insert(key, overlay);
+                },
+            }
+        }
+    }
+}
+
+impl<V: Merge> Merge for std::collections::BTreeMap<String, V> {
+    fn merge(&mut self, overlay: Self) {
+        for (key, overlay) in overlay {
+            match self.get_mut(&key) {
+                Some(base) => base.merge(overlay),
+                None => {
+                    self.insert(key, overlay);
+                },
+            }
+        }
+    }
+}
+
+/// A field of a derived partial. Fields implementing [Merge] are merged with it, while
+/// hand-written partials without it are replaced as a whole: `merge_field` of
+/// `(&&&MergeField::of(&base))` resolves to [MergeFieldByMerge] if it can, otherwise to
+/// [MergeFieldByOverlay] for the wrappers of derived fields, which keeps the base when
+/// the overlay is absent, and otherwise to [MergeFieldByReplace], one reference further.
+#[doc(hidden)]
+pub struct MergeField<T>(PhantomData<T>);
+
+impl<T> MergeField<T> {
+    pub fn of(_base: &T) -> Self {
+        Self(PhantomData)
+    }
+}
+
+#[doc(hidden)]
+pub trait MergeFieldByMerge<T> {
+    fn merge_field(&self, base: &mut T, overlay: T);
+}
+
+impl<T: Merge> MergeFieldByMerge<T> for &&MergeField<T> {
+    fn merge_field(&self, base: &mut T, overlay: T) {
+        base.merge(overlay)
+    }
+}
+
+#[doc(hidden)]
+pub trait MergeFieldByOverlay<T> {
+    fn merge_field(&self, base: &mut T, overlay: T);
+}
+
+impl<T> MergeFieldByOverlay<Option<T>> for &MergeField<Option<T>> {
+    fn merge_field(&self, base: &mut Option<T>, overlay: Option<T>) {
+        if overlay.is_some() {
+            *base = overlay;
+        }
+    }
+}
+
+impl<T> MergeFieldByOverlay<Tristate<T>> for &MergeField<Tristate<T>> {
+    fn merge_field(&self, base: &mut Tristate<T>, overlay: Tristate<T>) {
+        if !matches!(overlay, Tristate::Missing) {
+            *base = overlay;
+        }
+    }
+}
+
+impl<T> MergeFieldByOverlay<Defaulted<T>> for &MergeField<Defaulted<T>> {
+    fn merge_field(&self, base: &mut Defaulted<T>, overlay: Defaulted<T>) {
+        if let Defaulted::Supplied(_) = overlay {
+            *base = overlay;
+        }
+    }
+}
+
+#[doc(hidden)]
+pub trait MergeFieldByReplace<T> {
+    fn merge_field(&self, base: &mut T, overlay: T);
+}
+
+impl<T> MergeFieldByReplace<T> for MergeField<T> {
+    fn merge_field(&self, base: &mut T, overlay: T) {
+        *base = overlay
+    }
+}
@@ -0,0 +1,220 @@
+//! Interoperability with JSON Merge Patch (RFC 7386) and JSON Patch (RFC 6902).
+//!
+//! A merge patch is just a partial document, so partials convert to and from
+//! merge patches directly. JSON Patch operation lists can be generated from the
+//! difference between two values, and applied to documents.
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::AsPartial;
+use crate::path::FieldPath;
+
+/// Serializes a partial as a merge patch document.
+///
+/// Derived partials leave absent fields out when serialized, so the patch only
+/// touches fields that are present. Optional fields that are explicitly
+/// [crate::Tristate::Null] serialize as `null`, which removes them.
+pub fn to_merge_patch<P: serde::Serialize>(partial: &P) -> Result<Value, serde_json::Error> {
+    serde_json::to_value(partial)
+}
+
+/// Deserializes a merge patch document into the partial representation of `T`
+pub fn from_merge_patch<T>(patch: Value) -> Result<T::Partial, serde_json::Error>
+where
+    T: AsPartial<Partial: DeserializeOwned>,
+{
+    serde_json::from_value(patch)
+}
+
+/// Applies a merge patch to `target`, as described in RFC 7386: objects are
+/// merged recursively, `null` removes a key and anything else replaces the target
+pub fn merge_patch(target: &mut Value, patch: Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch;
+        return
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let Value::Object(target) = target else {
+        unreachable!("target was just made an object")
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(&key);
+        } else {
+            merge_patch(target.entry(key).or_insert(Value::Null), value);
+        }
+    }
+}
+
+/// Applies a merge patch to a document, after checking that the patch is a
+/// valid partial of `T`. Invalid patches are rejected without touching the document.
+pub fn apply_merge_patch<T>(document: &mut Value, patch: Value) -> Result<(), serde_json::Error>
+where
+    T: AsPartial<Partial: DeserializeOwned>,
+{
+    from_merge_patch::<T>(patch.clone())?;
+    merge_patch(document, patch);
+    Ok(())
+}
+
+/// A single JSON Patch operation. Paths are JSON pointers.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: Value },
+}
+
+fn collect_operations(old: &Value, new: &Value, path: &mut FieldPath, out: &mut Vec<PatchOperation>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, old_value) in old {
+                path.push(key.as_str());
+                match new.get(key) {
+                    Some(new_value) => collect_operations(old_value, new_value, path, out),
+                    None => out.push(PatchOperation::Remove { path: path.to_json_pointer() }),
+                }
+                path.pop();
+            }
+            for (key, new_value) in new.iter().filter(|(key, _)| !old.contains_key(*key)) {
+                out.push(PatchOperation::Add { path: path.join(key.as_str()).to_json_pointer(), value: new_value.clone() });
+            }
+        },
+        (Value::Array(old), Value::Array(new)) => {
+            for (idx, (old_item, new_item)) in old.iter().zip(new).enumerate() {
+                path.push(idx);
+                collect_operations(old_item, new_item, path, out);
+                path.pop();
+            }
+            // remove from the end, so that earlier indices stay valid
+            for idx in (new.len()..old.len()).rev() {
+                out.push(PatchOperation::Remove { path: path.join(idx).to_json_pointer() });
+            }
+            for (idx, new_item) in new.iter().enumerate().skip(old.len()) {
+                out.push(PatchOperation::Add { path: path.join(idx).to_json_pointer(), value: new_item.clone() });
+            }
+        },
+        (old, new) if old == new => (),
+        (_, new) => out.push(PatchOperation::Replace { path: path.to_json_pointer(), value: new.clone() }),
+    }
+}
+
+/// The JSON Patch operations that turn the serialized form of `old` into that of `new`
+pub fn json_patch<T: serde::Serialize>(old: &T, new: &T) -> Result<Vec<PatchOperation>, serde_json::Error> {
+    let old = serde_json::to_value(old)?;
+    let new = serde_json::to_value(new)?;
+    let mut out = Vec::new();
+    collect_operations(&old, &new, &mut FieldPath::root(), &mut out);
+    Ok(out)
+}
+
+/// Why a JSON Patch could not be applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchError {
+    /// Nothing exists at this pointer, or its parent can't hold it
+    InvalidPointer(String),
+    /// A `test` operation found a different value at this pointer
+    TestFailed(String),
+}
+
+impl std::fmt::Display for PatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidPointer(pointer) => write!(f, "invalid pointer {pointer:?}"),
+            Self::TestFailed(pointer) => write!(f, "test failed at {pointer:?}"),
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+/// Splits a pointer into the pointer of its parent and its last (unescaped) token
+fn split_pointer(pointer: &str) -> Result<(&str, String), PatchError> {
+    let (parent, token) = pointer.rsplit_once('/').ok_or_else(|| PatchError::InvalidPointer(pointer.to_owned()))?;
+    Ok((parent, token.replace("~1", "/").replace("~0", "~")))
+}
+
+fn add(document: &mut Value, pointer: &str, value: Value) -> Result<(), PatchError> {
+    if pointer.is_empty() {
+        *document = value;
+        return Ok(())
+    }
+    let invalid = || PatchError::InvalidPointer(pointer.to_owned());
+    let (parent, token) = split_pointer(pointer)?;
+    match document.pointer_mut(parent).ok_or_else(invalid)? {
+        Value::Object(object) => {
+            object.insert(token, value);
+        },
+        Value::Array(items) if token == "-" => items.push(value),
+        Value::Array(items) => {
+            let idx: usize = token.parse().map_err(|_| invalid())?;
+            if idx > items.len() {
+                return Err(invalid())
+            }
+            items.insert(idx, value);
+        },
+        _ => return Err(invalid()),
+    }
+    Ok(())
+}
+
+fn remove(document: &mut Value, pointer: &str) -> Result<Value, PatchError> {
+    let invalid = || PatchError::InvalidPointer(pointer.to_owned());
+    let (parent, token) = split_pointer(pointer)?;
+    match document.pointer_mut(parent).ok_or_else(invalid)? {
+        Value::Object(object) => object.remove(&token).ok_or_else(invalid),
+        Value::Array(items) => {
+            let idx: usize = token.parse().map_err(|_| invalid())?;
+            if idx >= items.len() {
+                return Err(invalid())
+            }
+            Ok(items.remove(idx))
+        },
+        _ => Err(invalid()),
+    }
+}
+
+fn apply_operation(document: &mut Value, operation: &PatchOperation) -> Result<(), PatchError> {
+    match operation {
+        PatchOperation::Add { path, value } => add(document, path, value.clone()),
+        PatchOperation::Remove { path } => remove(document, path).map(drop),
+        PatchOperation::Replace { path, value } => {
+            let target = document.pointer_mut(path).ok_or_else(|| PatchError::InvalidPointer(path.clone()))?;
+            *target = value.clone();
+            Ok(())
+        },
+        PatchOperation::Move { from, path } => {
+            let value = remove(document, from)?;
+            add(document, path, value)
+        },
+        PatchOperation::Copy { from, path } => {
+            let value = document.pointer(from).ok_or_else(|| PatchError::InvalidPointer(from.clone()))?.clone();
+            add(document, path, value)
+        },
+        PatchOperation::Test { path, value } => {
+            let found = document.pointer(path).ok_or_else(|| PatchError::InvalidPointer(path.clone()))?;
+            if found != value {
+                return Err(PatchError::TestFailed(path.clone()))
+            }
+            Ok(())
+        },
+    }
+}
+
+/// Applies JSON Patch operations to a document. Either all of them are applied,
+/// or the document is left untouched.
+pub fn apply_json_patch(document: &mut Value, operations: &[PatchOperation]) -> Result<(), PatchError> {
+    let mut patched = document.clone();
+    for operation in operations {
+        apply_operation(&mut patched, operation)?;
+    }
+    *document = patched;
+    Ok(())
+}
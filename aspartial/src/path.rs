@@ -0,0 +1,113 @@
+//! Paths pointing into (partial) values, as they appear in their serialized form.
+
+/// One step in a [FieldPath]: either an object key or an array index
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+impl From<&str> for PathSegment {
+    fn from(value: &str) -> Self {
+        Self::Key(value.to_owned())
+    }
+}
+
+impl From<String> for PathSegment {
+    fn from(value: String) -> Self {
+        Self::Key(value)
+    }
+}
+
+impl From<usize> for PathSegment {
+    fn from(value: usize) -> Self {
+        Self::Index(value)
+    }
+}
+
+/// A path from the root of a value to one of its (possibly nested) fields.
+///
+/// Keys are the serialized field names, so paths can be used both with partial
+/// types and with the JSON they came from. Displayed like `items[2].address.city`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FieldPath {
+    segments: Vec<PathSegment>,
+}
+
+impl FieldPath {
+    /// The empty path, pointing at the value itself
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    pub fn push(&mut self, segment: impl Into<PathSegment>) {
+        self.segments.push(segment.into())
+    }
+
+    pub fn pop(&mut self) -> Option<PathSegment> {
+        self.segments.pop()
+    }
+
+    /// A new path pointing at `segment` inside of `self`
+    pub fn join(&self, segment: impl Into<PathSegment>) -> Self {
+        let mut out = self.clone();
+        out.push(segment);
+        out
+    }
+
+    /// Whether `self` is `prefix` or points somewhere inside of it
+    pub fn starts_with(&self, prefix: &FieldPath) -> bool {
+        self.segments.starts_with(&prefix.segments)
+    }
+
+    /// This path as an RFC 6901 JSON pointer, e.g. `/items/2/address/city`
+    pub fn to_json_pointer(&self) -> String {
+        let mut out = String::new();
+        for segment in &self.segments {
+            out.push('/');
+            match segment {
+                PathSegment::Key(key) => out.push_str(&key.replace('~', "~0").replace('/', "~1")),
+                PathSegment::Index(idx) => out.push_str(&idx.to_string()),
+            }
+        }
+        out
+    }
+}
+
+impl FromIterator<PathSegment> for FieldPath {
+    fn from_iter<I: IntoIterator<Item = PathSegment>>(iter: I) -> Self {
+        Self { segments: iter.into_iter().collect() }
+    }
+}
+
+impl std::fmt::Display for FieldPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_root() {
+            return write!(f, ".")
+        }
+        for (idx, segment) in self.segments.iter().enumerate() {
+            match segment {
+                PathSegment::Index(i) => write!(f, "[{i}]")?,
+                PathSegment::Key(key) => {
+                    let is_plain = !key.is_empty()
+                        && !key.starts_with(|c: char| c.is_ascii_digit())
+                        && key.chars().all(|c| c.is_alphanumeric() || c == '_');
+                    match (is_plain, idx) {
+                        (true, 0) => write!(f, "{key}")?,
+                        (true, _) => write!(f, ".{key}")?,
+                        (false, _) => write!(f, "[{key:?}]")?,
+                    }
+                },
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+//! Picking the most likely variant of a partial enum.
+//!
+//! Untagged enums (and tagged ones whose tag hasn't arrived) try every variant, so
+//! a payload often populates several of them at once. The `candidates`, `resolve`
+//! and `best_variant` methods generated for partial enums rank those variants.
+
+use std::cmp::Ordering;
+
+use crate::Introspect;
+use crate::introspect::FieldReport;
+use crate::tag::TagMatch;
+
+/// How well a populated variant of a partial enum fits the payload.
+///
+/// Variants that the tag names exactly rank first, then those with more present
+/// leaves, then those with fewer missing ones. Variants whose partial couldn't be
+/// deserialized at all (e.g. because of `deny_unknown_fields`) aren't candidates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VariantScore {
+    /// Whether the tag names exactly this variant
+    pub exact_tag: bool,
+    /// How many leaves of the variant's partial are present
+    pub present: usize,
+    /// How many leaves of the variant's partial are still missing
+    pub missing: usize,
+}
+
+impl VariantScore {
+    /// Scores the partial of the variant with tag `variant_tag`
+    pub fn of<P: Introspect + ?Sized>(variant_tag: &str, tag_match: Option<&TagMatch>, partial: &P) -> Self {
+        Self::from_report(variant_tag, tag_match, &partial.field_report())
+    }
+
+    /// Scores the variant with tag `variant_tag` from the leaves reported by its partial
+    pub fn from_report(variant_tag: &str, tag_match: Option<&TagMatch>, report: &FieldReport) -> Self {
+        Self {
+            exact_tag: tag_match.is_some_and(|tag_match| matches!(tag_match, TagMatch::Exact(tag) if *tag == variant_tag)),
+            present: report.present.len(),
+            missing: report.missing.len(),
+        }
+    }
+}
+
+impl Ord for VariantScore {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.exact_tag.cmp(&other.exact_tag)
+            .then(self.present.cmp(&other.present))
+            .then(other.missing.cmp(&self.missing))
+    }
+}
+
+impl PartialOrd for VariantScore {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A populated variant of a partial enum, viewed as `V`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate<V> {
+    pub tag: &'static str,
+    pub variant: V,
+    pub score: VariantScore,
+}
+
+/// The most likely variant of a partial enum, and the other populated ones
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resolution<V> {
+    pub best: Candidate<V>,
+    /// The other candidates, best first
+    pub runner_ups: Vec<Candidate<V>>,
+}
+
+impl<V> Resolution<V> {
+    /// Splits candidates that are already ranked, best first. `None` if there are none.
+    pub fn from_ranked(candidates: Vec<Candidate<V>>) -> Option<Self> {
+        let mut candidates = candidates.into_iter();
+        let best = candidates.next()?;
+        Some(Self { best, runner_ups: candidates.collect() })
+    }
+
+    /// Whether the best candidate scores better than every runner-up
+    pub fn is_clear(&self) -> bool {
+        self.runner_ups.iter().all(|runner_up| runner_up.score < self.best.score)
+    }
+}
+
+/// Sorts candidates best first. Ties keep their order, i.e. the order of the variants.
+#[doc(hidden)]
+pub fn rank<V>(candidates: &mut [Candidate<V>]) {
+    candidates.sort_by_key(|candidate| std::cmp::Reverse(candidate.score));
+}
@@ -0,0 +1,314 @@
+//! Server-Sent Events framing, for APIs that stream JSON in `data:` lines.
+//!
+//! [SseDecoder] splits a byte stream into [Event]s. [SsePartialDecoder] goes one
+//! step further and deserializes the JSON in each event into a partial, stopping
+//! at the `[DONE]` sentinel. [SseReader] and (with the `futures` feature)
+//! [SseStream] drive it from a blocking reader or from a stream of byte chunks.
+
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+
+use serde::de::DeserializeOwned;
+
+use crate::AsPartial;
+use crate::json::Error;
+
+/// The `data` many streaming APIs send to signal that nothing else will follow
+pub const DONE_SENTINEL: &str = "[DONE]";
+
+/// A single server-sent event
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Event {
+    /// The `event:` field, if any
+    pub event: Option<String>,
+    /// All `data:` lines of the event, joined with `\n`
+    pub data: String,
+    /// The last `id:` seen in the stream, at the time this event was dispatched
+    pub id: Option<String>,
+    /// The `retry:` field, if any
+    pub retry: Option<u64>,
+}
+
+impl Event {
+    /// Whether this event is the `[DONE]` sentinel
+    pub fn is_done(&self) -> bool {
+        self.data == DONE_SENTINEL
+    }
+}
+
+/// Splits a byte stream into server-sent events.
+///
+/// Lines may end with `\n`, `\r\n` or `\r`; an empty line dispatches the event
+/// being built, and lines starting with `:` are comments. As in browsers, an
+/// event without any data (no `data:` lines, or a single empty one) is not
+/// dispatched, nor is an unterminated event at the end of the stream.
+#[derive(Default)]
+pub struct SseDecoder {
+    line: Vec<u8>,
+    after_cr: bool,
+    started: bool,
+    event: Option<String>,
+    data: Option<String>,
+    retry: Option<u64>,
+    last_id: Option<String>,
+}
+
+impl SseDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds the next chunk, returning the events it completed
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<Event> {
+        let mut events = Vec::new();
+        for &b in chunk {
+            let after_cr = std::mem::replace(&mut self.after_cr, b == b'\r');
+            match b {
+                b'\n' if after_cr => (),
+                b'\n' | b'\r' => events.extend(self.end_line()),
+                _ => self.line.push(b),
+            }
+        }
+        events
+    }
+
+    fn end_line(&mut self) -> Option<Event> {
+        let line = std::mem::take(&mut self.line);
+        let mut line = String::from_utf8_lossy(&line).into_owned();
+        if !self.started {
+            self.started = true;
+            if let Some(stripped) = line.strip_prefix('\u{feff}') {
+                line = stripped.to_owned();
+            }
+        }
+        if line.is_empty() {
+            return self.dispatch()
+        }
+        if line.starts_with(':') {
+            return None
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line.as_str(), ""),
+        };
+        match field {
+            "event" => self.event = Some(value.to_owned()),
+            "data" => match &mut self.data {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                },
+                None => self.data = Some(value.to_owned()),
+            },
+            "id" if !value.contains('\0') => self.last_id = Some(value.to_owned()),
+            "retry" => {
+                if let Ok(retry) = value.parse() {
+                    self.retry = Some(retry)
+                }
+            },
+            _ => (),
+        }
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<Event> {
+        let event = self.event.take();
+        let retry = self.retry.take();
+        // events without any data are not dispatched
+        let data = self.data.take().filter(|data| !data.is_empty())?;
+        Some(Event { event, data, id: self.last_id.clone(), retry })
+    }
+}
+
+/// The partial deserialized from the data of a server-sent event
+pub struct Message<T: AsPartial> {
+    pub event: Option<String>,
+    pub id: Option<String>,
+    pub partial: T::Partial,
+}
+
+impl<T> std::fmt::Debug for Message<T>
+where
+    T: AsPartial<Partial: std::fmt::Debug>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Message")
+            .field("event", &self.event)
+            .field("id", &self.id)
+            .field("partial", &self.partial)
+            .finish()
+    }
+}
+
+/// Turns a byte stream of server-sent events into partials of `T`, one per
+/// event, until the `[DONE]` sentinel arrives.
+///
+/// The data of each event is parsed leniently with [crate::from_str_truncated],
+/// so events cut short still produce a partial. Events that can't be parsed
+/// produce an error, and decoding carries on with the next one.
+pub struct SsePartialDecoder<T> {
+    framing: SseDecoder,
+    done: bool,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for SsePartialDecoder<T>
+where
+    T: AsPartial<Partial: DeserializeOwned>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SsePartialDecoder<T>
+where
+    T: AsPartial<Partial: DeserializeOwned>,
+{
+    pub fn new() -> Self {
+        Self { framing: SseDecoder::new(), done: false, marker: PhantomData }
+    }
+
+    /// Whether the `[DONE]` sentinel has been received
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Feeds the next chunk, returning a partial for each event it completed
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<Result<Message<T>, Error>> {
+        if self.done {
+            return Vec::new()
+        }
+        let mut out = Vec::new();
+        for event in self.framing.push(chunk) {
+            if event.is_done() {
+                self.done = true;
+                break
+            }
+            out.push(crate::from_str_truncated::<T>(&event.data).map(|partial| Message {
+                event: event.event,
+                id: event.id,
+                partial,
+            }));
+        }
+        out
+    }
+}
+
+/// Reads server-sent events from a [std::io::BufRead], yielding a partial of
+/// `T` for each of them.
+///
+/// Iteration stops at the `[DONE]` sentinel, at the end of the input or after an I/O error.
+pub struct SseReader<T: AsPartial, R> {
+    reader: R,
+    decoder: Option<SsePartialDecoder<T>>,
+    pending: VecDeque<Result<Message<T>, Error>>,
+}
+
+impl<T, R> SseReader<T, R>
+where
+    T: AsPartial<Partial: DeserializeOwned>,
+    R: std::io::BufRead,
+{
+    pub fn new(reader: R) -> Self {
+        Self { reader, decoder: Some(SsePartialDecoder::new()), pending: VecDeque::new() }
+    }
+}
+
+impl<T, R> Iterator for SseReader<T, R>
+where
+    T: AsPartial<Partial: DeserializeOwned>,
+    R: std::io::BufRead,
+{
+    type Item = Result<Message<T>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.pending.pop_front() {
+                return Some(item)
+            }
+            let decoder = self.decoder.as_mut().filter(|decoder| !decoder.is_done())?;
+            let chunk = match self.reader.fill_buf() {
+                Ok(chunk) => chunk,
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(err) => {
+                    self.decoder = None;
+                    return Some(Err(err.into()))
+                },
+            };
+            if chunk.is_empty() {
+                self.decoder = None;
+                return None
+            }
+            let chunk_len = chunk.len();
+            self.pending.extend(decoder.push(chunk));
+            self.reader.consume(chunk_len);
+        }
+    }
+}
+
+#[cfg(feature = "futures")]
+pub use self::async_adapters::SseStream;
+
+#[cfg(feature = "futures")]
+mod async_adapters {
+    use std::collections::VecDeque;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use futures_core::Stream;
+    use serde::de::DeserializeOwned;
+
+    use super::{Message, SsePartialDecoder};
+    use crate::AsPartial;
+    use crate::json::Error;
+
+    /// Receives server-sent events from a stream of byte chunks, producing a
+    /// partial of `T` for each of them.
+    ///
+    /// The stream ends at the `[DONE]` sentinel or when the source ends.
+    /// Sources that are not [Unpin] can be pinned with [Box::pin].
+    pub struct SseStream<T: AsPartial, S> {
+        source: S,
+        decoder: SsePartialDecoder<T>,
+        pending: VecDeque<Result<Message<T>, Error>>,
+    }
+
+    // finished messages are never pinned, only the source is polled through a pin
+    impl<T: AsPartial, S: Unpin> Unpin for SseStream<T, S> {}
+
+    impl<T, S> SseStream<T, S>
+    where
+        T: AsPartial<Partial: DeserializeOwned>,
+        S: Stream<Item: AsRef<[u8]>> + Unpin,
+    {
+        pub fn new(source: S) -> Self {
+            Self { source, decoder: SsePartialDecoder::new(), pending: VecDeque::new() }
+        }
+    }
+
+    impl<T, S> Stream for SseStream<T, S>
+    where
+        T: AsPartial<Partial: DeserializeOwned>,
+        S: Stream<Item: AsRef<[u8]>> + Unpin,
+    {
+        type Item = Result<Message<T>, Error>;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            let this = self.get_mut();
+            loop {
+                if let Some(item) = this.pending.pop_front() {
+                    return Poll::Ready(Some(item))
+                }
+                if this.decoder.is_done() {
+                    return Poll::Ready(None)
+                }
+                match Pin::new(&mut this.source).poll_next(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(Some(chunk)) => this.pending.extend(this.decoder.push(chunk.as_ref())),
+                    Poll::Ready(None) => return Poll::Ready(None),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,450 @@
+//! Adapters that turn a source of JSON text into successive partials.
+//!
+//! [PartialDecoder] and [NdjsonDecoder] do the actual work and can be fed from
+//! anywhere. [PartialReader] and [NdjsonReader] drive them from a blocking
+//! [std::io::BufRead]; with the `futures` feature, [PartialStream] and
+//! [NdjsonStream] drive them from a [futures_core::Stream] of byte chunks.
+
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+
+use serde::de::DeserializeOwned;
+
+use crate::AsPartial;
+use crate::deserialize::from_value_with_key_lookups;
+use crate::json::{Completion, Error, IncrementalParser};
+use crate::path::FieldPath;
+
+/// Something new learned about a document that is being received
+pub enum Update<T: AsPartial> {
+    /// The document is still arriving, and this is how it looks so far
+    Partial(T::Partial),
+    /// The document arrived in its entirety
+    Complete(T),
+}
+
+impl<T> std::fmt::Debug for Update<T>
+where
+    T: AsPartial<Partial: std::fmt::Debug> + std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Partial(partial) => f.debug_tuple("Partial").field(partial).finish(),
+            Self::Complete(complete) => f.debug_tuple("Complete").field(complete).finish(),
+        }
+    }
+}
+
+/// A single record of an NDJSON (newline-delimited JSON) stream
+pub struct Record<T: AsPartial> {
+    /// Position of this record in the stream, not counting blank lines
+    pub index: usize,
+    pub update: Update<T>,
+}
+
+impl<T> std::fmt::Debug for Record<T>
+where
+    T: AsPartial<Partial: std::fmt::Debug> + std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Record").field("index", &self.index).field("update", &self.update).finish()
+    }
+}
+
+/// Turns chunks of a single JSON document into partials of `T`, only producing
+/// a new partial when the received value may have changed, or when the string
+/// that was arriving has ended (which settles e.g. enum tags)
+pub struct PartialDecoder<T> {
+    parser: IncrementalParser,
+    last_generation: Option<u64>,
+    last_value_generation: Option<u64>,
+    /// Where the last partial looked for keys without a value yet
+    key_lookups: Vec<FieldPath>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for PartialDecoder<T>
+where
+    T: AsPartial<Partial: DeserializeOwned> + DeserializeOwned,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> PartialDecoder<T>
+where
+    T: AsPartial<Partial: DeserializeOwned> + DeserializeOwned,
+{
+    pub fn new() -> Self {
+        Self::with_parser(IncrementalParser::new())
+    }
+
+    /// Uses a pre-configured parser, e.g. one that includes trailing numbers
+    pub fn with_parser(parser: IncrementalParser) -> Self {
+        Self {
+            parser,
+            last_generation: None,
+            last_value_generation: None,
+            key_lookups: Vec::new(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Feeds the next chunk, returning a new partial if anything changed
+    pub fn push(&mut self, chunk: &[u8]) -> Result<Option<T::Partial>, Error> {
+        self.parser.push(chunk)?;
+        let generation = self.parser.generation();
+        if self.last_generation == Some(generation) {
+            return Ok(None)
+        }
+        let completion = self.parser.completion();
+        // a key without a value yet only matters to the partials that look for it
+        // (e.g. externally tagged enums), which are the same ones as last time
+        let value_generation = self.parser.value_generation();
+        if self.last_value_generation == Some(value_generation)
+            && !completion.in_progress().is_some_and(|path| self.key_lookups.contains(path))
+        {
+            self.last_generation = Some(generation);
+            return Ok(None)
+        }
+        let Some(value) = self.parser.value() else {
+            return Ok(None)
+        };
+        self.last_generation = Some(generation);
+        self.last_value_generation = Some(value_generation);
+        let (partial, key_lookups) = from_value_with_key_lookups(&value, &completion);
+        self.key_lookups = key_lookups;
+        Ok(Some(partial?))
+    }
+
+    /// Whether the whole document has been received
+    pub fn is_complete(&self) -> bool {
+        self.parser.is_complete()
+    }
+
+    pub fn completion(&self) -> Completion {
+        self.parser.completion()
+    }
+
+    /// Signals the end of the input, deserializing the complete document
+    pub fn finish(self) -> Result<T, Error> {
+        let value = self.parser.finish()?;
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+/// Turns chunks of NDJSON text into partials of each record as it arrives.
+///
+/// A record that fails to parse produces an error, and decoding resumes on the next line.
+pub struct NdjsonDecoder<T> {
+    current: PartialDecoder<T>,
+    index: usize,
+    started: bool,
+    skipping_line: bool,
+}
+
+impl<T> Default for NdjsonDecoder<T>
+where
+    T: AsPartial<Partial: DeserializeOwned> + DeserializeOwned,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> NdjsonDecoder<T>
+where
+    T: AsPartial<Partial: DeserializeOwned> + DeserializeOwned,
+{
+    pub fn new() -> Self {
+        Self {
+            current: PartialDecoder::new(),
+            index: 0,
+            started: false,
+            skipping_line: false,
+        }
+    }
+
+    /// Feeds the next chunk, which may contain any number of (partial) lines
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<Result<Record<T>, Error>> {
+        let mut out = Vec::new();
+        for segment in chunk.split_inclusive(|b| *b == b'\n') {
+            let (line, ends_line) = match segment.split_last() {
+                Some((b'\n', line)) => (line, true),
+                _ => (segment, false),
+            };
+            if !self.skipping_line {
+                self.started |= line.iter().any(|b| !b.is_ascii_whitespace());
+                match self.current.push(line) {
+                    Ok(Some(partial)) => out.push(Ok(Record { index: self.index, update: Update::Partial(partial) })),
+                    Ok(None) => (),
+                    Err(err) => {
+                        out.push(Err(err));
+                        self.skipping_line = true;
+                    },
+                }
+            }
+            if ends_line {
+                out.extend(self.end_record());
+            }
+        }
+        out
+    }
+
+    /// Signals the end of the input, completing the last record if it wasn't terminated by a newline
+    pub fn finish(mut self) -> Option<Result<Record<T>, Error>> {
+        self.end_record()
+    }
+
+    fn end_record(&mut self) -> Option<Result<Record<T>, Error>> {
+        let decoder = std::mem::take(&mut self.current);
+        let index = self.index;
+        let out = match (self.started, self.skipping_line) {
+            (false, false) => return None,
+            (_, true) => None,
+            (true, false) => Some(decoder.finish().map(|complete| Record { index, update: Update::Complete(complete) })),
+        };
+        self.index += 1;
+        self.started = false;
+        self.skipping_line = false;
+        out
+    }
+}
+
+/// Reads a single JSON document from a [std::io::BufRead], yielding a partial
+/// whenever more of it arrives and finally the complete value.
+///
+/// Iteration stops after the document is complete or after the first error.
+pub struct PartialReader<T, R> {
+    reader: R,
+    decoder: Option<PartialDecoder<T>>,
+}
+
+impl<T, R> PartialReader<T, R>
+where
+    T: AsPartial<Partial: DeserializeOwned> + DeserializeOwned,
+    R: std::io::BufRead,
+{
+    pub fn new(reader: R) -> Self {
+        Self::with_decoder(reader, PartialDecoder::new())
+    }
+
+    pub fn with_decoder(reader: R, decoder: PartialDecoder<T>) -> Self {
+        Self { reader, decoder: Some(decoder) }
+    }
+}
+
+impl<T, R> Iterator for PartialReader<T, R>
+where
+    T: AsPartial<Partial: DeserializeOwned> + DeserializeOwned,
+    R: std::io::BufRead,
+{
+    type Item = Result<Update<T>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let decoder = self.decoder.as_mut()?;
+            let chunk = match self.reader.fill_buf() {
+                Ok(chunk) => chunk,
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(err) => {
+                    self.decoder = None;
+                    return Some(Err(err.into()))
+                },
+            };
+            if chunk.is_empty() {
+                return self.decoder.take().map(|decoder| decoder.finish().map(Update::Complete))
+            }
+            let chunk_len = chunk.len();
+            let pushed = decoder.push(chunk);
+            self.reader.consume(chunk_len);
+            match pushed {
+                Err(err) => {
+                    self.decoder = None;
+                    return Some(Err(err))
+                },
+                Ok(_) if decoder.is_complete() => {
+                    return self.decoder.take().map(|decoder| decoder.finish().map(Update::Complete))
+                },
+                Ok(Some(partial)) => return Some(Ok(Update::Partial(partial))),
+                Ok(None) => continue,
+            }
+        }
+    }
+}
+
+/// Reads NDJSON records from a [std::io::BufRead], yielding partials of each
+/// record as it arrives and then the complete record.
+///
+/// Iteration stops at the end of the input or after an I/O error.
+pub struct NdjsonReader<T: AsPartial, R> {
+    reader: R,
+    decoder: Option<NdjsonDecoder<T>>,
+    pending: VecDeque<Result<Record<T>, Error>>,
+}
+
+impl<T, R> NdjsonReader<T, R>
+where
+    T: AsPartial<Partial: DeserializeOwned> + DeserializeOwned,
+    R: std::io::BufRead,
+{
+    pub fn new(reader: R) -> Self {
+        Self { reader, decoder: Some(NdjsonDecoder::new()), pending: VecDeque::new() }
+    }
+}
+
+impl<T, R> Iterator for NdjsonReader<T, R>
+where
+    T: AsPartial<Partial: DeserializeOwned> + DeserializeOwned,
+    R: std::io::BufRead,
+{
+    type Item = Result<Record<T>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.pending.pop_front() {
+                return Some(item)
+            }
+            let decoder = self.decoder.as_mut()?;
+            let chunk = match self.reader.fill_buf() {
+                Ok(chunk) => chunk,
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(err) => {
+                    self.decoder = None;
+                    return Some(Err(err.into()))
+                },
+            };
+            if chunk.is_empty() {
+                return self.decoder.take().and_then(NdjsonDecoder::finish)
+            }
+            let chunk_len = chunk.len();
+            self.pending.extend(decoder.push(chunk));
+            self.reader.consume(chunk_len);
+        }
+    }
+}
+
+#[cfg(feature = "futures")]
+pub use self::async_adapters::{NdjsonStream, PartialStream};
+
+#[cfg(feature = "futures")]
+mod async_adapters {
+    use std::collections::VecDeque;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use futures_core::Stream;
+    use serde::de::DeserializeOwned;
+
+    use super::{NdjsonDecoder, PartialDecoder, Record, Update};
+    use crate::AsPartial;
+    use crate::json::Error;
+
+    /// Receives a single JSON document from a stream of byte chunks, producing a
+    /// partial whenever more of it arrives and finally the complete value.
+    ///
+    /// The stream ends after the document is complete or after the first error.
+    /// Sources that are not [Unpin] can be pinned with [Box::pin].
+    pub struct PartialStream<T, S> {
+        source: S,
+        decoder: Option<PartialDecoder<T>>,
+    }
+
+    impl<T, S> PartialStream<T, S>
+    where
+        T: AsPartial<Partial: DeserializeOwned> + DeserializeOwned,
+        S: Stream<Item: AsRef<[u8]>> + Unpin,
+    {
+        pub fn new(source: S) -> Self {
+            Self::with_decoder(source, PartialDecoder::new())
+        }
+
+        pub fn with_decoder(source: S, decoder: PartialDecoder<T>) -> Self {
+            Self { source, decoder: Some(decoder) }
+        }
+    }
+
+    impl<T, S> Stream for PartialStream<T, S>
+    where
+        T: AsPartial<Partial: DeserializeOwned> + DeserializeOwned,
+        S: Stream<Item: AsRef<[u8]>> + Unpin,
+    {
+        type Item = Result<Update<T>, Error>;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            let this = self.get_mut();
+            loop {
+                let Some(decoder) = this.decoder.as_mut() else {
+                    return Poll::Ready(None)
+                };
+                let chunk = match Pin::new(&mut this.source).poll_next(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(Some(chunk)) => chunk,
+                    Poll::Ready(None) => {
+                        return Poll::Ready(this.decoder.take().map(|decoder| decoder.finish().map(Update::Complete)))
+                    },
+                };
+                match decoder.push(chunk.as_ref()) {
+                    Err(err) => {
+                        this.decoder = None;
+                        return Poll::Ready(Some(Err(err)))
+                    },
+                    Ok(_) if decoder.is_complete() => {
+                        return Poll::Ready(this.decoder.take().map(|decoder| decoder.finish().map(Update::Complete)))
+                    },
+                    Ok(Some(partial)) => return Poll::Ready(Some(Ok(Update::Partial(partial)))),
+                    Ok(None) => continue,
+                }
+            }
+        }
+    }
+
+    /// Receives NDJSON records from a stream of byte chunks, producing partials
+    /// of each record as it arrives and then the complete record.
+    ///
+    /// Sources that are not [Unpin] can be pinned with [Box::pin].
+    pub struct NdjsonStream<T: AsPartial, S> {
+        source: S,
+        decoder: Option<NdjsonDecoder<T>>,
+        pending: VecDeque<Result<Record<T>, Error>>,
+    }
+
+    // finished records are never pinned, only the source is polled through a pin
+    impl<T: AsPartial, S: Unpin> Unpin for NdjsonStream<T, S> {}
+
+    impl<T, S> NdjsonStream<T, S>
+    where
+        T: AsPartial<Partial: DeserializeOwned> + DeserializeOwned,
+        S: Stream<Item: AsRef<[u8]>> + Unpin,
+    {
+        pub fn new(source: S) -> Self {
+            Self { source, decoder: Some(NdjsonDecoder::new()), pending: VecDeque::new() }
+        }
+    }
+
+    impl<T, S> Stream for NdjsonStream<T, S>
+    where
+        T: AsPartial<Partial: DeserializeOwned> + DeserializeOwned,
+        S: Stream<Item: AsRef<[u8]>> + Unpin,
+    {
+        type Item = Result<Record<T>, Error>;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            let this = self.get_mut();
+            loop {
+                if let Some(item) = this.pending.pop_front() {
+                    return Poll::Ready(Some(item))
+                }
+                let Some(decoder) = this.decoder.as_mut() else {
+                    return Poll::Ready(None)
+                };
+                match Pin::new(&mut this.source).poll_next(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(Some(chunk)) => this.pending.extend(decoder.push(chunk.as_ref())),
+                    Poll::Ready(None) => return Poll::Ready(this.decoder.take().and_then(NdjsonDecoder::finish)),
+                }
+            }
+        }
+    }
+}
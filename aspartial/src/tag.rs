@@ -0,0 +1,179 @@
+//! Matching of enum tags that may still be arriving.
+
+use serde_json::Value;
+
+/// How the tag found in a payload matched the variants of a partial enum
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TagMatch {
+    /// The tag names exactly this variant
+    Exact(&'static str),
+    /// The tag is still being received, and it is the beginning of the tags of
+    /// these variants (possibly including one it already names)
+    Prefix{ tag: String, candidates: Vec<&'static str> },
+}
+
+impl TagMatch {
+    /// Matches `tag` against the serialized tags of all variants of an enum.
+    ///
+    /// A tag that `is_open` may still be arriving, so it matches every tag it is the
+    /// beginning of, even when it already names a variant: `"Get"` could still turn
+    /// into `"GetAll"`. A tag that is complete only ever matches exactly.
+    pub fn find(tag: &str, is_open: bool, variant_tags: &[&'static str]) -> Option<Self> {
+        let accepted_tags: Vec<_> = variant_tags.iter().map(|variant_tag| (*variant_tag, *variant_tag)).collect();
+        Self::find_accepted(tag, is_open, &accepted_tags)
+    }
+
+    /// Like [TagMatch::find], for variants that are deserialized from other tags than
+    /// the ones they are serialized with (`rename(deserialize = ..)`, aliases, etc).
+    /// `accepted_tags` pairs every tag that is accepted with the tag of its variant,
+    /// which is what the match refers to.
+    pub fn find_accepted(tag: &str, is_open: bool, accepted_tags: &[(&'static str, &'static str)]) -> Option<Self> {
+        if !is_open {
+            return accepted_tags.iter()
+                .find(|(accepted, _)| *accepted == tag)
+                .map(|(_, variant_tag)| Self::Exact(variant_tag))
+        }
+        let mut candidates = Vec::<&'static str>::new();
+        for (accepted, variant_tag) in accepted_tags {
+            if accepted.starts_with(tag) && !candidates.contains(variant_tag) {
+                candidates.push(variant_tag);
+            }
+        }
+        if candidates.is_empty() {
+            return None
+        }
+        Some(Self::Prefix { tag: tag.to_owned(), candidates })
+    }
+
+    /// Matches the keys of an externally tagged payload, returning the match and
+    /// the entry under the matching key.
+    ///
+    /// `pending_key` is a key that has no value yet, and whether it is still arriving
+    /// (see [crate::json::Completion::pending_key]). It is matched when none of the
+    /// keys with a value are, and has no entry to go with it.
+    pub fn find_key<'v>(
+        value: &'v Value, pending_key: Option<(&str, bool)>, variant_tags: &[&'static str],
+    ) -> Option<(Self, Option<(&'v str, &'v Value)>)> {
+        let accepted_tags: Vec<_> = variant_tags.iter().map(|variant_tag| (*variant_tag, *variant_tag)).collect();
+        Self::find_key_accepted(value, pending_key, &accepted_tags)
+    }
+
+    /// Like [TagMatch::find_key], with the accepted tags of [TagMatch::find_accepted]
+    pub fn find_key_accepted<'v>(
+        value: &'v Value, pending_key: Option<(&str, bool)>, accepted_tags: &[(&'static str, &'static str)],
+    ) -> Option<(Self, Option<(&'v str, &'v Value)>)> {
+        let object = value.as_object()?;
+        for (key, payload) in object {
+            if let Some(tag_match) = Self::find_accepted(key, false, accepted_tags) {
+                return Some((tag_match, Some((key.as_str(), payload))))
+            }
+        }
+        let (key, is_open) = pending_key?;
+        Self::find_accepted(key, is_open, accepted_tags).map(|tag_match| (tag_match, None))
+    }
+
+    /// Whether the variant with this tag could be the one in the payload
+    pub fn allows(&self, variant_tag: &str) -> bool {
+        match self {
+            Self::Exact(tag) => *tag == variant_tag,
+            Self::Prefix { candidates, .. } => candidates.contains(&variant_tag),
+        }
+    }
+
+    /// The tag of the only variant that the payload could be, if that is unambiguous already
+    pub fn variant(&self) -> Option<&'static str> {
+        match self {
+            Self::Exact(tag) => Some(tag),
+            Self::Prefix { candidates, .. } => match candidates.as_slice() {
+                [single] => Some(single),
+                _ => None,
+            },
+        }
+    }
+
+    /// Whether the tag is still arriving, so that it may name another variant yet
+    pub fn is_open(&self) -> bool {
+        matches!(self, Self::Prefix { .. })
+    }
+
+    /// The tag as it was found in the payload
+    pub fn tag(&self) -> &str {
+        match self {
+            Self::Exact(tag) => tag,
+            Self::Prefix { tag, .. } => tag,
+        }
+    }
+}
+
+/// A tag that names none of the variants of a partial enum (and can't be the beginning
+/// of any of them), kept along with its payload so that variants added to the payload's
+/// producer aren't lost
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownTag {
+    pub tag: String,
+    /// The contents that came with the tag, if any. For internally tagged enums,
+    /// this is the whole map, including the tag.
+    pub payload: Option<Value>,
+}
+
+impl UnknownTag {
+    /// The tag and payload in the wire shape they were received in
+    #[doc(hidden)]
+    pub fn to_wire_value(&self, style: TagStyle) -> Value {
+        match &self.payload {
+            Some(payload) => style.to_wire_value(Some(&self.tag), Some(payload.clone())),
+            None => style.to_unit_wire_value(Some(&self.tag)),
+        }
+    }
+}
+
+/// How an enum is tagged in its serialized form, mirroring serde's enum representations
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagStyle {
+    External,
+    Internal { tag_key: &'static str },
+    Adjacent { tag_key: &'static str, content_key: &'static str },
+    Untagged,
+}
+
+impl TagStyle {
+    /// Puts the tag and payload of a partial enum together in the wire shape of
+    /// the original enum. Whatever isn't known yet is left out.
+    #[doc(hidden)]
+    pub fn to_wire_value(self, tag: Option<&str>, payload: Option<Value>) -> Value {
+        match (self, tag) {
+            (Self::External, Some(tag)) => {
+                Value::Object([(tag.to_owned(), payload.unwrap_or(Value::Null))].into_iter().collect())
+            },
+            (Self::Internal { tag_key }, Some(tag)) => match payload {
+                Some(Value::Object(mut object)) => {
+                    object.insert(tag_key.to_owned(), tag.into());
+                    Value::Object(object)
+                },
+                // only maps can hold the tag, so anything else is sent as is
+                Some(payload) => payload,
+                None => Value::Object([(tag_key.to_owned(), tag.into())].into_iter().collect()),
+            },
+            (Self::Adjacent { tag_key, content_key }, tag) => Value::Object(
+                tag.map(|tag| (tag_key.to_owned(), Value::from(tag)))
+                    .into_iter()
+                    .chain(payload.map(|payload| (content_key.to_owned(), payload)))
+                    .collect()
+            ),
+            (Self::Untagged, _) => payload.unwrap_or(Value::Null),
+            // without a tag, the payload is sent as is, like it would have been parsed
+            (Self::External | Self::Internal { .. }, None) => payload.unwrap_or_else(|| Value::Object(Default::default())),
+        }
+    }
+
+    /// Like [TagStyle::to_wire_value], but for unit variants, which have no payload
+    #[doc(hidden)]
+    pub fn to_unit_wire_value(self, tag: Option<&str>) -> Value {
+        match (self, tag) {
+            (Self::External, Some(tag)) => Value::from(tag),
+            (Self::Untagged, _) | (Self::External, None) => Value::Null,
+            (Self::Internal { .. } | Self::Adjacent { .. }, tag) => self.to_wire_value(tag, None),
+        }
+    }
+}
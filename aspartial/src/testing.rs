@@ -0,0 +1,157 @@
+//! Helpers for testing partial types against streamed documents.
+//!
+//! [assert_monotonic_prefixes] feeds a complete document to an
+//! [IncrementalParser] piece by piece and checks that every partial it produces
+//! refines the previous one, i.e. that receiving more text never makes a
+//! partial lose information it already had.
+//!
+//! Note that partial enums serialize the first variant that fits their payload
+//! until the tag arrives, so a tag that arrives late can switch to a variant
+//! that leaves out fields the first one had.
+
+use serde_json::Value;
+
+use crate::AsSerializablePartial;
+use crate::json::{Error, IncrementalParser};
+use crate::path::FieldPath;
+
+/// How to split a document into the successive prefixes that get parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Chunking {
+    /// Every byte prefix, including those that end in the middle of a UTF-8 sequence
+    EveryByte,
+    /// Every prefix ending on a char boundary
+    EveryChar,
+    /// Chunks of this many bytes
+    Fixed(usize),
+    /// Prefixes ending at these byte offsets, plus the whole document
+    Boundaries(Vec<usize>),
+}
+
+impl Chunking {
+    /// The lengths of the prefixes to parse, in increasing order, ending with the whole document
+    fn prefix_lengths(&self, document: &str) -> Vec<usize> {
+        let mut out: Vec<usize> = match self {
+            Self::EveryByte => (1..=document.len()).collect(),
+            Self::EveryChar => document.char_indices()
+                .map(|(idx, c)| idx + c.len_utf8())
+                .collect(),
+            Self::Fixed(chunk_len) => (1..=document.len())
+                .filter(|len| len % (*chunk_len).max(1) == 0)
+                .collect(),
+            Self::Boundaries(offsets) => offsets.iter()
+                .copied()
+                .filter(|offset| *offset > 0 && *offset < document.len())
+                .collect(),
+        };
+        out.sort_unstable();
+        out.dedup();
+        if out.last() != Some(&document.len()) {
+            out.push(document.len());
+        }
+        out
+    }
+}
+
+/// The first prefix of a document at which a partial misbehaved
+#[derive(Debug)]
+pub enum PrefixFailure {
+    /// The prefix could not be turned into a partial
+    Parse { prefix: String, error: Error },
+    /// The partial lost information that the partial of a shorter prefix had
+    Regression { prefix: String, path: FieldPath, previous: Value, current: Value },
+}
+
+impl std::fmt::Display for PrefixFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse { prefix, error } => write!(f, "prefix {prefix:?} could not be parsed: {error}"),
+            Self::Regression { prefix, path, previous, current } => write!(
+                f, "prefix {prefix:?} lost information at {path}: was {previous}, now {current}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PrefixFailure {}
+
+/// Checks whether `current` holds at least all the information of `previous`,
+/// returning the first path where it doesn't.
+///
+/// `null` (an absent field) is refined by anything, a string by any string that
+/// starts with it and an array by any longer array whose items refine it.
+fn find_regression(previous: &Value, current: &Value, path: &mut FieldPath) -> Option<FieldPath> {
+    match (previous, current) {
+        (Value::Null, _) => None,
+        (Value::String(previous), Value::String(current)) if current.starts_with(previous.as_str()) => None,
+        (Value::Array(previous), Value::Array(current)) if previous.len() <= current.len() => {
+            previous.iter().zip(current).enumerate().find_map(|(idx, (previous, current))| {
+                path.push(idx);
+                let regression = find_regression(previous, current, path);
+                path.pop();
+                regression
+            })
+        },
+        (Value::Object(previous), Value::Object(current)) => {
+            previous.iter().find_map(|(key, previous)| {
+                path.push(key.as_str());
+                let regression = find_regression(previous, current.get(key).unwrap_or(&Value::Null), path);
+                path.pop();
+                regression
+            })
+        },
+        (previous, current) if previous == current => None,
+        _ => Some(path.clone()),
+    }
+}
+
+/// Parses every prefix of `document` (as split by `chunking`) into the partial
+/// of `T`, checking that each partial refines the previous one.
+///
+/// Partials are compared through their serialized form.
+pub fn check_monotonic_prefixes<T>(document: &str, chunking: &Chunking) -> Result<(), PrefixFailure>
+where
+    T: AsSerializablePartial,
+{
+    let mut parser = IncrementalParser::new();
+    let mut previous: Option<Value> = None;
+    let mut fed = 0;
+    for prefix_len in chunking.prefix_lengths(document) {
+        let prefix = || String::from_utf8_lossy(&document.as_bytes()[..prefix_len]).into_owned();
+        let parsed = parser.push(&document.as_bytes()[fed..prefix_len])
+            .map_err(Error::from)
+            .and_then(|_| parser.partial::<T>());
+        fed = prefix_len;
+        let partial = match parsed {
+            Ok(Some(partial)) => partial,
+            Ok(None) => continue,
+            Err(error) => return Err(PrefixFailure::Parse { prefix: prefix(), error }),
+        };
+        let current = serde_json::to_value(&partial)
+            .map_err(|error| PrefixFailure::Parse { prefix: prefix(), error: error.into() })?;
+        if let Some(previous) = &previous
+            && let Some(path) = find_regression(previous, &current, &mut FieldPath::root())
+        {
+            let pointer = path.to_json_pointer();
+            return Err(PrefixFailure::Regression {
+                prefix: prefix(),
+                previous: previous.pointer(&pointer).cloned().unwrap_or(Value::Null),
+                current: current.pointer(&pointer).cloned().unwrap_or(Value::Null),
+                path,
+            })
+        }
+        previous = Some(current);
+    }
+    Ok(())
+}
+
+/// Like [check_monotonic_prefixes], but panics with the first violating prefix and field path
+#[track_caller]
+pub fn assert_monotonic_prefixes<T>(document: &str, chunking: Chunking)
+where
+    T: AsSerializablePartial,
+{
+    if let Err(failure) = check_monotonic_prefixes::<T>(document, &chunking) {
+        panic!("partials of {} are not monotonic: {failure}", std::any::type_name::<T>())
+    }
+}
@@ -0,0 +1,116 @@
+//! The partial representation of optional fields.
+
+use crate::Merge;
+
+/// The partial of a field of type `Option<T>`, which tells a field that hasn't
+/// arrived apart from one that was explicitly set to `null`.
+///
+/// Derived partials use it for every field whose type is spelled `Option<...>`.
+/// Fields are deserialized as [Tristate::Missing] if their key is absent and as
+/// [Tristate::Null] if it is `null`, and are left out of the serialized partial
+/// while missing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Tristate<T> {
+    /// Nothing is known about the field
+    #[default]
+    Missing,
+    /// The field was explicitly set to `null`, e.g. to clear it in a patch
+    Null,
+    /// The field has a value
+    Value(T),
+}
+
+impl<T> Tristate<T> {
+    pub fn is_missing(&self) -> bool {
+        matches!(self, Self::Missing)
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    pub fn is_value(&self) -> bool {
+        matches!(self, Self::Value(_))
+    }
+
+    pub fn as_ref(&self) -> Tristate<&T> {
+        match self {
+            Self::Missing => Tristate::Missing,
+            Self::Null => Tristate::Null,
+            Self::Value(value) => Tristate::Value(value),
+        }
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Tristate<U> {
+        match self {
+            Self::Missing => Tristate::Missing,
+            Self::Null => Tristate::Null,
+            Self::Value(value) => Tristate::Value(f(value)),
+        }
+    }
+
+    /// The value, if there is one
+    pub fn value(self) -> Option<T> {
+        match self {
+            Self::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Encodes the state as nested options: `None` if missing, `Some(None)` if null
+    pub fn into_nested(self) -> Option<Option<T>> {
+        match self {
+            Self::Missing => None,
+            Self::Null => Some(None),
+            Self::Value(value) => Some(Some(value)),
+        }
+    }
+
+    /// The inverse of [Tristate::into_nested]
+    pub fn from_nested(nested: Option<Option<T>>) -> Self {
+        match nested {
+            None => Self::Missing,
+            Some(None) => Self::Null,
+            Some(Some(value)) => Self::Value(value),
+        }
+    }
+}
+
+/// `None` becomes [Tristate::Null], since the value is known to be absent
+impl<T> From<Option<T>> for Tristate<T> {
+    fn from(value: Option<T>) -> Self {
+        match value {
+            Some(value) => Self::Value(value),
+            None => Self::Null,
+        }
+    }
+}
+
+/// Missing overlays keep the base, `null` clears it and values are merged into it
+impl<T: Merge> Merge for Tristate<T> {
+    fn merge(&mut self, overlay: Self) {
+        match (self, overlay) {
+            (_, Self::Missing) => (),
+            (Self::Value(base), Self::Value(overlay)) => base.merge(overlay),
+            (base, overlay) => *base = overlay,
+        }
+    }
+}
+
+/// Missing fields serialize as `null`, but derived partials skip them altogether
+impl<T: serde::Serialize> serde::Serialize for Tristate<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Value(value) => serializer.serialize_some(value),
+            Self::Missing | Self::Null => serializer.serialize_none(),
+        }
+    }
+}
+
+/// Only ever produces [Tristate::Null] or [Tristate::Value]. Missing keys must
+/// be handled with `#[serde(default)]`, which derived partials do.
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for Tristate<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Option::<T>::deserialize(deserializer).map(Self::from)
+    }
+}
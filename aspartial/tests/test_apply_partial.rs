@@ -0,0 +1,128 @@
+use ::aspartial::{ApplyPartial, AsPartial};
+
+#[derive(AsPartial, Debug, Clone, PartialEq, serde::Deserialize)]
+#[aspartial(name = PartialAddress)]
+#[aspartial(apply)]
+struct Address {
+    city: String,
+    zip: u32,
+}
+
+#[derive(AsPartial, Debug, Clone, PartialEq, serde::Deserialize)]
+#[aspartial(name = PartialCircle)]
+#[aspartial(apply)]
+struct Circle {
+    radius: f64,
+}
+
+#[derive(AsPartial, Debug, Clone, PartialEq, serde::Deserialize)]
+#[aspartial(name = PartialSquare)]
+#[aspartial(apply)]
+struct Square {
+    side: f64,
+}
+
+#[derive(AsPartial, Debug, Clone, PartialEq, serde::Deserialize)]
+#[aspartial(name = PartialShape)]
+#[aspartial(apply)]
+#[serde(tag = "kind")]
+enum Shape {
+    Circle(Circle),
+    Square(Square),
+}
+
+#[derive(AsPartial, Debug, Clone, PartialEq, serde::Deserialize)]
+#[aspartial(name = PartialResource)]
+#[aspartial(apply)]
+struct Resource {
+    name: String,
+    address: Address,
+    nickname: Option<String>,
+    shape: Shape,
+    tags: Vec<Address>,
+}
+
+fn resource() -> Resource {
+    Resource {
+        name: "desk".to_owned(),
+        address: Address{ city: "Lisboa".to_owned(), zip: 1100 },
+        nickname: None,
+        shape: Shape::Circle(Circle{ radius: 1.0 }),
+        tags: vec![Address{ city: "Porto".to_owned(), zip: 4000 }],
+    }
+}
+
+fn patch(value: serde_json::Value) -> PartialResource {
+    serde_json::from_value(value).unwrap()
+}
+
+#[test]
+fn test_apply_overwrites_only_provided_fields(){
+    let mut target = resource();
+    target.apply_partial(patch(serde_json::json!({
+        "address": {"zip": 1200},
+        "nickname": "the desk",
+    }))).unwrap();
+    let mut expected = resource();
+    expected.address.zip = 1200;
+    expected.nickname = Some("the desk".to_owned());
+    assert_eq!(target, expected);
+}
+
+#[test]
+fn test_apply_enum_same_and_other_variant(){
+    let mut target = resource();
+    target.apply_partial(patch(serde_json::json!({"shape": {"kind": "Circle", "radius": 2.0}}))).unwrap();
+    assert_eq!(target.shape, Shape::Circle(Circle{ radius: 2.0 }));
+
+    target.apply_partial(patch(serde_json::json!({"shape": {"kind": "Square", "side": 3.0}}))).unwrap();
+    assert_eq!(target.shape, Shape::Square(Square{ side: 3.0 }));
+}
+
+#[test]
+fn test_apply_reports_incomplete_new_values(){
+    let mut target = resource();
+    let err = target.apply_partial(patch(serde_json::json!({
+        "name": "table",
+        "shape": {"kind": "Square"},
+        "tags": [{"zip": 4100}, {"city": "Faro"}],
+    }))).unwrap_err();
+    let missing: Vec<String> = err.missing_paths().map(|path| path.to_string()).collect();
+    assert_eq!(missing, vec!["shape.side", "tags[1].zip"]);
+    // the parts that could be applied still are
+    assert_eq!(target.name, "table");
+    assert_eq!(target.shape, Shape::Circle(Circle{ radius: 1.0 }));
+}
+
+#[test]
+fn test_apply_vec_patches_by_index(){
+    let mut target = resource();
+    target.apply_partial(patch(serde_json::json!({
+        "tags": [{"zip": 4100}, {"city": "Faro", "zip": 8000}],
+    }))).unwrap();
+    assert_eq!(target.tags, vec![
+        Address{ city: "Porto".to_owned(), zip: 4100 },
+        Address{ city: "Faro".to_owned(), zip: 8000 },
+    ]);
+
+    target.apply_partial(patch(serde_json::json!({"tags": []}))).unwrap();
+    assert_eq!(target.tags, vec![]);
+}
+
+#[test]
+fn test_apply_primitives(){
+    let mut value = 3u32;
+    value.apply_partial(4).unwrap();
+    assert_eq!(value, 4);
+
+    let mut value = Some("a".to_owned());
+    value.apply_partial(None).unwrap();
+    assert_eq!(value, None);
+}
+
+#[test]
+fn test_apply_implies_complete(){
+    // `aspartial(apply)` also derives `FromPartial`, which switching variants needs
+    let completed = <Circle as ::aspartial::FromPartial>::from_partial(PartialCircle{ radius: Some(1.5) }).unwrap();
+    assert_eq!(completed, Circle{ radius: 1.5 });
+}
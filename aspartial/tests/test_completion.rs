@@ -0,0 +1,78 @@
+use ::aspartial::AsPartial;
+use ::aspartial::json::IncrementalParser;
+use ::aspartial::path::FieldPath;
+
+#[allow(dead_code)]
+#[derive(AsPartial)]
+#[aspartial(name = PartialMessage)]
+#[aspartial(attrs( #[derive(PartialEq, Debug)] ))]
+#[derive(serde::Deserialize)]
+struct Message {
+    title: String,
+    body: String,
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial)]
+#[aspartial(name = PartialEvent)]
+#[derive(serde::Deserialize)]
+#[serde(tag = "kind")]
+enum Event {
+    Post(Message),
+    Reply(Message),
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial)]
+#[aspartial(name = PartialFeed)]
+struct Feed {
+    owner: String,
+    events: Vec<Event>,
+}
+
+#[test]
+fn test_completion_of_nested_fields(){
+    let mut parser = IncrementalParser::new();
+    assert!(!parser.completion().is_complete(&FieldPath::root()));
+
+    parser.push(br#"{"owner": "bob", "events": [{"kind": "Post", "title": "Hi", "body": "Hello th"#).unwrap();
+    let events_0 = FieldPath::root().join("events").join(0);
+    let completion = parser.completion();
+    assert_eq!(completion.streaming_leaf(), Some(&events_0.join("body")));
+    assert!(completion.is_complete(&FieldPath::root().join("owner")));
+    assert!(completion.is_complete(&events_0.join("title")));
+    assert!(!completion.is_complete(&events_0.join("body")));
+    assert!(!completion.is_complete(&events_0));
+    assert!(!completion.is_complete(&FieldPath::root()));
+
+    let partial = parser.partial::<Feed>().unwrap().unwrap();
+    let events = partial.events.unwrap();
+    let post = events[0].post.as_ref().unwrap();
+    assert_eq!(post.body.as_deref(), Some("Hello th"));
+    assert_eq!(events[0].reply, None);
+
+    parser.push(br#"ere"}, "#).unwrap();
+    let completion = parser.completion();
+    assert!(completion.is_complete(&events_0));
+    assert_eq!(completion.in_progress(), Some(&FieldPath::root().join("events")));
+    assert_eq!(completion.streaming_leaf(), None);
+
+    parser.push(br#"{"kind": "Rep"#).unwrap();
+    assert_eq!(parser.completion().streaming_leaf(), Some(&FieldPath::root().join("events").join(1).join("kind")));
+
+    parser.push(br#"ly"}]}"#).unwrap();
+    assert!(parser.completion().is_done());
+    assert!(parser.completion().is_complete(&FieldPath::root()));
+}
+
+#[test]
+fn test_field_path_display(){
+    let path = FieldPath::root().join("items").join(2).join("address").join("city");
+    assert_eq!(path.to_string(), "items[2].address.city");
+    assert_eq!(path.to_json_pointer(), "/items/2/address/city");
+
+    let path = FieldPath::root().join(0).join("a/b~").join("with space");
+    assert_eq!(path.to_string(), r#"[0]["a/b~"]["with space"]"#);
+    assert_eq!(path.to_json_pointer(), "/0/a~1b~0/with space");
+    assert_eq!(FieldPath::root().to_string(), ".");
+}
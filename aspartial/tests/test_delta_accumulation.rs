@@ -0,0 +1,116 @@
+use ::aspartial::AsPartial;
+use ::aspartial::delta::{ToolCallAccumulator, merge_delta};
+use ::serde_json::json;
+
+#[allow(dead_code)]
+#[derive(AsPartial)]
+#[aspartial(name = PartialWeatherArgs)]
+#[aspartial(attrs( #[derive(PartialEq, Debug)] ))]
+struct WeatherArgs {
+    location: String,
+    days: u32,
+}
+
+#[test]
+fn test_tool_call_accumulation(){
+    let chunks = [
+        json!([{"index": 0, "id": "call_a", "type": "function", "function": {"name": "get_wea", "arguments": ""}}]),
+        json!([{"index": 0, "function": {"name": "ther", "arguments": "{\"loca"}}]),
+        json!([{"index": 1, "id": "call_b", "type": "function", "function": {"name": "get_weather", "arguments": "{\"days\": 3"}}]),
+        json!([{"index": 0, "function": {"arguments": "tion\": \"Lis"}}]),
+        json!([{"index": 1, "function": {"arguments": "}"}}, {"index": 0, "function": {"arguments": "bon\", \"days\": 2}"}}]),
+    ];
+
+    let mut accumulator = ToolCallAccumulator::new();
+    for (chunk_idx, chunk) in chunks.into_iter().enumerate() {
+        accumulator.push(chunk).unwrap();
+        if chunk_idx == 3 {
+            let call = accumulator.get(0).unwrap();
+            assert_eq!(
+                call.partial::<WeatherArgs>().unwrap(),
+                Some(PartialWeatherArgs{ location: Some("Lis".to_owned()), days: None })
+            );
+            let call = accumulator.get(1).unwrap();
+            assert_eq!(call.partial::<WeatherArgs>().unwrap(), Some(PartialWeatherArgs{ location: None, days: None }));
+        }
+    }
+
+    let calls: Vec<_> = accumulator.calls().collect();
+    assert_eq!(calls.len(), 2);
+    let (_, call_a) = calls[0];
+    assert_eq!(call_a.id(), Some("call_a"));
+    assert_eq!(call_a.name(), Some("get_weather"));
+    assert_eq!(call_a.arguments(), r#"{"location": "Lisbon", "days": 2}"#);
+    assert!(call_a.is_complete());
+    assert_eq!(call_a.fields(), &json!({"index": 0, "id": "call_a", "type": "function", "function": {"name": "get_weather"}}));
+    assert_eq!(
+        call_a.partial::<WeatherArgs>().unwrap(),
+        Some(PartialWeatherArgs{ location: Some("Lisbon".to_owned()), days: Some(2) })
+    );
+    let (_, call_b) = calls[1];
+    assert_eq!(call_b.partial::<WeatherArgs>().unwrap(), Some(PartialWeatherArgs{ location: None, days: Some(3) }));
+
+    let mut accumulator = ToolCallAccumulator::new().with_arguments_pointer("/partial_json");
+    accumulator.push(json!({"partial_json": "{\"location\": \"Po"})).unwrap();
+    assert_eq!(accumulator.get(0).unwrap().arguments(), "{\"location\": \"Po");
+    assert!(accumulator.push(json!({"partial_json": "\"}}"})).is_err());
+}
+
+#[test]
+fn test_invalid_arguments_keep_the_delta(){
+    let mut accumulator = ToolCallAccumulator::new();
+    accumulator.push(json!({"index": 0, "function": {"arguments": "{\"days\": 2"}})).unwrap();
+    let chunk = json!([
+        {"index": 0, "id": "call_a", "function": {"name": "get_weather", "arguments": "]"}},
+        {"index": 1, "id": "call_b", "function": {"arguments": "{}"}},
+    ]);
+    assert!(accumulator.push(chunk).is_err());
+
+    let call_a = accumulator.get(0).unwrap();
+    assert_eq!(call_a.id(), Some("call_a"));
+    assert_eq!(call_a.name(), Some("get_weather"));
+    assert_eq!(call_a.arguments(), r#"{"days": 2]"#);
+    // the deltas after the failing one are merged too
+    let call_b = accumulator.get(1).unwrap();
+    assert_eq!(call_b.id(), Some("call_b"));
+    assert!(call_b.is_complete());
+}
+
+#[test]
+fn test_merge_delta(){
+    let mut target = json!({"role": "assistant", "content": null});
+    merge_delta(&mut target, json!({"content": "Hel", "choices": [{"index": 0, "text": "a"}]}));
+    merge_delta(&mut target, json!({"content": "lo", "role": null, "choices": [{"index": 1, "text": "b"}, {"index": 0, "text": "c"}]}));
+    merge_delta(&mut target, json!({"logprobs": [1], "finish_reason": "stop"}));
+    merge_delta(&mut target, json!({"logprobs": [2]}));
+    assert_eq!(
+        target,
+        json!({
+            "role": "assistant",
+            "content": "Hello",
+            "choices": [{"index": 0, "text": "ac"}, {"index": 1, "text": "b"}],
+            "logprobs": [1, 2],
+            "finish_reason": "stop",
+        })
+    );
+}
+
+#[test]
+fn test_repeated_fields_are_not_appended(){
+    let mut accumulator = ToolCallAccumulator::new();
+    accumulator.push(json!([{"index": 0, "id": "call_a", "type": "function", "function": {"name": "get_weather", "arguments": "{\"days\""}}])).unwrap();
+    accumulator.push(json!([{"index": 0, "id": "call_a", "type": "function", "function": {"arguments": ": 2}"}}])).unwrap();
+    let call = accumulator.get(0).unwrap();
+    assert_eq!(call.id(), Some("call_a"));
+    assert_eq!(call.fields(), &json!({"index": 0, "id": "call_a", "type": "function", "function": {"name": "get_weather"}}));
+    assert_eq!(call.partial::<WeatherArgs>().unwrap(), Some(PartialWeatherArgs{ location: None, days: Some(2) }));
+
+    let mut target = json!({"id": "chatcmpl-1", "object": "chat.completion.chunk", "content": "Hel"});
+    merge_delta(&mut target, json!({"id": "chatcmpl-1", "object": "chat.completion.chunk", "content": "lo"}));
+    merge_delta(&mut target, json!({"finish_reason": "length"}));
+    merge_delta(&mut target, json!({"finish_reason": "stop"}));
+    assert_eq!(
+        target,
+        json!({"id": "chatcmpl-1", "object": "chat.completion.chunk", "content": "Hello", "finish_reason": "stop"})
+    );
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use ::aspartial::{AsPartial, FromPartial};
+use serde_json::{json, Value};
+
+#[derive(AsPartial, serde::Deserialize, serde::Serialize, PartialEq, Debug)]
+#[aspartial(name=PartialShape)]
+#[aspartial(complete)]
+#[aspartial(attrs( #[derive(Debug)] ))]
+#[serde(tag = "kind")]
+enum Shape {
+    Circle{ radius: u32 },
+    Square{ side: u32 },
+}
+
+#[derive(AsPartial, serde::Deserialize, serde::Serialize, PartialEq, Debug)]
+#[aspartial(name=PartialDrawing)]
+#[aspartial(complete)]
+#[aspartial(attrs( #[derive(Debug)] ))]
+struct Drawing {
+    id: u32,
+    #[serde(flatten)]
+    shape: Shape,
+    #[serde(flatten)]
+    rest: HashMap<String, Value>,
+}
+
+#[derive(AsPartial, serde::Deserialize, serde::Serialize, PartialEq, Debug)]
+#[aspartial(name=PartialCommand)]
+#[aspartial(complete)]
+#[aspartial(attrs( #[derive(Debug)] ))]
+enum Command {
+    Walk(u32),
+    Stop,
+}
+
+#[derive(AsPartial, serde::Deserialize, serde::Serialize, PartialEq, Debug)]
+#[aspartial(name=PartialStep)]
+#[aspartial(complete)]
+#[aspartial(attrs( #[derive(Debug)] ))]
+struct Step {
+    id: u32,
+    #[serde(flatten)]
+    command: Command,
+    #[serde(flatten)]
+    rest: HashMap<String, Value>,
+}
+
+#[derive(AsPartial, serde::Deserialize, serde::Serialize, PartialEq, Debug)]
+#[aspartial(name=PartialEvent)]
+#[aspartial(complete)]
+#[aspartial(attrs( #[derive(Debug)] ))]
+#[serde(tag = "t", content = "c")]
+enum Event {
+    Click(u32),
+}
+
+#[derive(AsPartial, serde::Deserialize, serde::Serialize, PartialEq, Debug)]
+#[aspartial(name=PartialLogLine)]
+#[aspartial(complete)]
+#[aspartial(attrs( #[derive(Debug)] ))]
+struct LogLine {
+    #[serde(flatten)]
+    event: Event,
+    #[serde(flatten)]
+    rest: HashMap<String, Value>,
+}
+
+#[test]
+fn test_flattened_internally_tagged_enum(){
+    let raw = json!({"id": 1, "kind": "Circle", "radius": 5, "x": 7});
+    let parsed: PartialDrawing = serde_json::from_value(raw.clone()).unwrap();
+    assert_eq!(parsed.id, Some(1));
+    assert_eq!(parsed.shape.as_ref().unwrap().circle.as_ref().unwrap().radius, Some(5));
+    // the enum doesn't take its entries, so the catch-all sees them like it does with serde
+    let original: Drawing = serde_json::from_value(raw.clone()).unwrap();
+    assert_eq!(parsed.rest.as_ref(), Some(&original.rest));
+
+    assert_eq!(Drawing::from_partial(parsed).unwrap(), original);
+}
+
+#[test]
+fn test_flattened_externally_tagged_enum(){
+    let raw = json!({"id": 1, "Walk": 3, "x": 7});
+    let parsed: PartialStep = serde_json::from_value(raw.clone()).unwrap();
+    assert_eq!(parsed.command.as_ref().unwrap().walk, Some(3));
+    // the variant's entry is taken from the catch-all, like serde does
+    let original: Step = serde_json::from_value(raw.clone()).unwrap();
+    assert_eq!(parsed.rest, Some(HashMap::from([("x".to_owned(), json!(7))])));
+    assert_eq!(parsed.rest.as_ref(), Some(&original.rest));
+
+    assert_eq!(serde_json::to_value(&parsed).unwrap(), raw);
+    assert_eq!(Step::from_partial(parsed).unwrap(), original);
+}
+
+#[test]
+fn test_flattened_unit_variant(){
+    let raw = json!({"id": 1, "Stop": null});
+    let original: Step = serde_json::from_value(raw.clone()).unwrap();
+    assert_eq!(serde_json::to_value(&original).unwrap(), raw);
+
+    let partial = original.to_partial();
+    assert_eq!(serde_json::to_value(&partial).unwrap(), raw);
+    let parsed: PartialStep = serde_json::from_value(raw).unwrap();
+    assert!(parsed.command.unwrap().stop.is_some());
+    assert_eq!(parsed.rest, Some(HashMap::new()));
+}
+
+#[test]
+fn test_flattened_enum_without_tag(){
+    // a key that merely starts like a tag is left to the catch-all
+    let parsed: PartialStep = serde_json::from_value(json!({"id": 1, "Wa": 3})).unwrap();
+    let command = parsed.command.unwrap();
+    assert_eq!(command.walk, None);
+    assert_eq!(command.tag_match, None);
+    assert_eq!(parsed.rest, Some(HashMap::from([("Wa".to_owned(), json!(3))])));
+}
+
+#[test]
+fn test_flattened_adjacently_tagged_enum(){
+    let raw = json!({"t": "Click", "c": 4, "x": 7});
+    let parsed: PartialLogLine = serde_json::from_value(raw.clone()).unwrap();
+    assert_eq!(parsed.event.as_ref().unwrap().click, Some(4));
+    let original: LogLine = serde_json::from_value(raw.clone()).unwrap();
+    assert_eq!(parsed.rest.as_ref(), Some(&original.rest));
+
+    assert_eq!(serde_json::to_value(&parsed).unwrap(), raw);
+}
+
+#[test]
+fn test_flattened_enum_truncated(){
+    let parsed = aspartial::from_str_truncated::<Drawing>(r#"{"id": 1, "kind": "Circ"#).unwrap();
+    assert_eq!(parsed.id, Some(1));
+    let shape = parsed.shape.unwrap();
+    assert!(matches!(shape.tag_match, Some(aspartial::TagMatch::Prefix{ .. })));
+    assert_eq!(shape.circle.unwrap().radius, None);
+
+    let parsed = aspartial::from_str_truncated::<Step>(r#"{"id": 1, "Walk": 3, "x": "ab"#).unwrap();
+    assert_eq!(parsed.command.unwrap().walk, Some(3));
+    assert_eq!(parsed.rest, Some(HashMap::from([("x".to_owned(), json!("ab"))])));
+}
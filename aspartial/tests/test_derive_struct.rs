@@ -27,3 +27,27 @@ fn test_derive_struct(){
     assert_eq!(parsed.defaults_to_7, 7);
     assert_eq!(parsed.defaults_to_default, <bool as Default>::default());
 }
+
+/// Implements `AsPartial` by hand, and none of the traits that are derived on request
+#[derive(Debug, PartialEq)]
+struct Custom(String);
+
+impl AsPartial for Custom {
+    type Partial = String;
+    fn to_partial(self) -> Self::Partial {
+        self.0
+    }
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial)]
+#[aspartial(name=PartialWithCustom)]
+struct WithCustom {
+    custom: Custom,
+}
+
+#[test]
+fn test_derive_with_hand_written_field(){
+    let partial = WithCustom{ custom: Custom("abc".to_owned()) }.to_partial();
+    assert_eq!(partial.custom, Some("abc".to_owned()));
+}
@@ -0,0 +1,146 @@
+use ::aspartial::{ApplyPartial, AsPartial, Defaulted, Diff, TagMatch, Tristate};
+
+#[derive(AsPartial, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[aspartial(name = PartialAddress)]
+#[aspartial(apply)]
+#[aspartial(diff)]
+#[aspartial(attrs( #[derive(PartialEq, Debug)] ))]
+struct Address {
+    city: String,
+    zip: u32,
+}
+
+#[derive(AsPartial, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[aspartial(name = PartialShape)]
+#[aspartial(apply)]
+#[aspartial(diff)]
+#[aspartial(attrs( #[derive(PartialEq, Debug)] ))]
+#[serde(tag = "kind")]
+enum Shape {
+    Home(Address),
+    Work(Address),
+}
+
+#[derive(AsPartial, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[aspartial(name = PartialPerson)]
+#[aspartial(apply)]
+#[aspartial(diff)]
+#[aspartial(attrs( #[derive(PartialEq, Debug)] ))]
+struct Person {
+    name: String,
+    address: Address,
+    nickname: Option<String>,
+    places: Vec<Shape>,
+    #[serde(default)]
+    age: u32,
+}
+
+fn person() -> Person {
+    Person {
+        name: "Ana".to_owned(),
+        address: Address{ city: "Lisboa".to_owned(), zip: 1100 },
+        nickname: Some("A".to_owned()),
+        places: vec![Shape::Home(Address{ city: "Lisboa".to_owned(), zip: 1100 })],
+        age: 30,
+    }
+}
+
+#[test]
+fn test_diff_equal_values(){
+    assert_eq!(person().diff(person()), None);
+    assert_eq!(3u32.diff(3), None);
+    assert_eq!(3u32.diff(4), Some(4));
+}
+
+#[test]
+fn test_diff_is_minimal(){
+    let mut new = person();
+    new.address.city = "Porto".to_owned();
+    let diff = person().diff(new).unwrap();
+    assert_eq!(diff.name, None);
+    assert_eq!(diff.nickname, Tristate::Missing);
+    assert_eq!(diff.places, None);
+    assert_eq!(diff.address, Some(PartialAddress{ city: Some("Porto".to_owned()), zip: None }));
+    // fields with a serde default can't be absent from the partial, so they hold the unchanged value as a default
+    assert_eq!(diff.age, Defaulted::Default(30));
+}
+
+#[test]
+fn test_diff_enum_variants(){
+    let home = Shape::Home(Address{ city: "Lisboa".to_owned(), zip: 1100 });
+    let moved = Shape::Home(Address{ city: "Lisboa".to_owned(), zip: 1200 });
+    let diff = home.diff(moved).unwrap();
+    assert_eq!(diff.home, Some(PartialAddress{ city: None, zip: Some(1200) }));
+    assert_eq!(diff.tag_match, Some(TagMatch::Exact("Home")));
+
+    let work = Shape::Work(Address{ city: "Porto".to_owned(), zip: 4000 });
+    let diff = home.diff(work.clone()).unwrap();
+    assert_eq!(diff, work.to_partial());
+}
+
+#[test]
+fn test_diff_then_apply_round_trips(){
+    let mut new = person();
+    new.name = "Ana Maria".to_owned();
+    new.nickname = None;
+    new.places.push(Shape::Work(Address{ city: "Porto".to_owned(), zip: 4000 }));
+    new.places[0] = Shape::Home(Address{ city: "Lisboa".to_owned(), zip: 1150 });
+    new.age = 31;
+
+    let mut old = person();
+    let diff = old.diff(new.clone()).unwrap();
+    old.apply_partial(diff).unwrap();
+    assert_eq!(old, new);
+}
+
+#[test]
+fn test_change_summary(){
+    let mut new = person();
+    new.address.city = "Porto".to_owned();
+    new.nickname = None;
+    new.places.clear();
+    let changes: Vec<String> = ::aspartial::diff::changes(&person(), &new).unwrap()
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(changes, vec![
+        r#"address.city: "Lisboa" -> "Porto""#,
+        r#"nickname: "A" -> null"#,
+        r#"places[0]: {"city":"Lisboa","kind":"Home","zip":1100} -> (absent)"#,
+    ]);
+}
+
+/// A leaf type with a hand-written `AsPartial` and no `Diff`, only `PartialEq`
+#[derive(Debug, Clone, PartialEq)]
+struct Color(u8, u8, u8);
+
+impl AsPartial for Color {
+    type Partial = String;
+    fn to_partial(self) -> Self::Partial {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+#[derive(AsPartial, Debug, Clone, PartialEq)]
+#[aspartial(name = PartialTheme)]
+#[aspartial(diff)]
+#[aspartial(attrs( #[derive(PartialEq, Debug)] ))]
+struct Theme {
+    name: String,
+    color: Color,
+    accent: Option<Color>,
+}
+
+#[test]
+fn test_diff_replaces_fields_without_diff(){
+    let old = Theme{ name: "dark".to_owned(), color: Color(0, 0, 0), accent: None };
+    let mut new = old.clone();
+    assert_eq!(old.diff(new.clone()), None);
+
+    new.color = Color(10, 10, 10);
+    new.accent = Some(Color(1, 2, 3));
+    let diff = old.diff(new).unwrap();
+    assert_eq!(diff.name, None);
+    assert_eq!(diff.color, Some("#0a0a0a".to_owned()));
+    assert_eq!(diff.accent, Tristate::Value("#010203".to_owned()));
+}
@@ -0,0 +1,129 @@
+use ::aspartial::{ApplyPartial, AsPartial, Diff, FromPartial, Introspect, Merge};
+use serde_json::json;
+
+#[derive(AsPartial, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[aspartial(name = PartialShape)]
+#[aspartial(apply)]
+#[aspartial(diff)]
+#[aspartial(enum_repr)]
+#[aspartial(attrs( #[derive(Debug)] ))]
+#[serde(tag = "kind")]
+enum Shape {
+    Circle { radius: f64 },
+    Square(Side),
+    Empty,
+}
+
+#[derive(AsPartial, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[aspartial(name = PartialSide)]
+#[aspartial(apply)]
+#[aspartial(diff)]
+#[aspartial(attrs( #[derive(Debug)] ))]
+struct Side {
+    length: f64,
+}
+
+#[derive(AsPartial, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[aspartial(name = PartialCommand)]
+#[aspartial(apply)]
+#[aspartial(diff)]
+#[aspartial(enum_repr)]
+enum Command {
+    Stop,
+    Walk(u32),
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial, Debug, PartialEq, serde::Deserialize)]
+#[aspartial(name = PartialMessage)]
+#[aspartial(apply)]
+#[aspartial(diff)]
+#[aspartial(enum_repr)]
+#[serde(tag = "t", content = "c")]
+enum Message {
+    Text(String),
+    #[serde(other)]
+    Unsupported,
+}
+
+fn parse<P: serde::de::DeserializeOwned>(value: serde_json::Value) -> P {
+    serde_json::from_value(value).unwrap()
+}
+
+#[test]
+fn test_matching_on_enum_shaped_partials(){
+    let partial: PartialShape = parse(json!({"kind": "Circle"}));
+    let PartialShape::Circle(circle) = &partial else {
+        panic!("expected a circle, got {partial:?}")
+    };
+    assert_eq!(circle.radius, None);
+    assert_eq!(Shape::from_partial(partial).unwrap_err().to_string(), "could not complete partial: missing radius");
+
+    let partial: PartialShape = parse(json!({"kind": "Square", "length": 2.0}));
+    assert!(matches!(&partial, PartialShape::Square(side) if side.length == Some(2.0)));
+    assert_eq!(Shape::from_partial(partial).unwrap(), Shape::Square(Side{ length: 2.0 }));
+    assert!(matches!(parse(json!({"kind": "Empty"})), PartialShape::Empty));
+
+    // until the tag names a variant, the partial can't tell which one it is
+    for document in [json!({"radius": 1.0}), json!({"kind": "Ci", "radius": 1.0}), json!({"kind": "Hexagon"})] {
+        let partial: PartialShape = parse(document);
+        assert!(matches!(partial, PartialShape::Unknown));
+        assert!(partial.is_empty());
+        assert!(Shape::from_partial(partial).is_err());
+    }
+}
+
+#[test]
+fn test_enum_shaped_partials_of_externally_tagged_enums(){
+    assert!(matches!(parse(json!("Stop")), PartialCommand::Stop));
+    assert!(matches!(parse(json!({"Walk": 3})), PartialCommand::Walk(3)));
+    assert!(matches!(parse(json!({"Wa": 3})), PartialCommand::Unknown));
+    assert_eq!(Command::from_partial(parse(json!({"Walk": 3}))).unwrap(), Command::Walk(3));
+
+    assert_eq!(serde_json::to_value(Command::Stop.to_partial()).unwrap(), json!("Stop"));
+    assert_eq!(serde_json::to_value(Command::Walk(3).to_partial()).unwrap(), json!({"Walk": 3}));
+}
+
+#[test]
+fn test_serde_other_in_enum_shaped_partials(){
+    assert!(matches!(parse(json!({"t": "Image", "c": {"url": "a.png"}})), PartialMessage::Unsupported));
+    assert!(matches!(parse(json!({"t": "Text", "c": "hi"})), PartialMessage::Text(text) if text == "hi"));
+    assert_eq!(Message::from_partial(parse(json!({"t": "Image"}))).unwrap(), Message::Unsupported);
+}
+
+#[test]
+fn test_round_trips_of_enum_shaped_partials(){
+    let shape = Shape::Circle{ radius: 1.0 };
+    let document = serde_json::to_value(shape.clone().to_partial()).unwrap();
+    assert_eq!(document, serde_json::to_value(&shape).unwrap());
+    assert_eq!(Shape::from_partial(parse(document)).unwrap(), shape);
+
+    let partial: PartialShape = parse(json!({"kind": "Circle"}));
+    assert_eq!(serde_json::to_value(&partial).unwrap(), json!({"kind": "Circle"}));
+    assert_eq!(serde_json::to_value(PartialShape::Unknown).unwrap(), json!({}));
+}
+
+#[test]
+fn test_apply_diff_and_merge_enum_shaped_partials(){
+    let mut shape = Shape::Square(Side{ length: 1.0 });
+    shape.apply_partial(parse(json!({"kind": "Square", "length": 2.0}))).unwrap();
+    assert_eq!(shape, Shape::Square(Side{ length: 2.0 }));
+    shape.apply_partial(PartialShape::Unknown).unwrap();
+    assert_eq!(shape, Shape::Square(Side{ length: 2.0 }));
+    shape.apply_partial(parse(json!({"kind": "Empty"}))).unwrap();
+    assert_eq!(shape, Shape::Empty);
+    assert!(shape.apply_partial(parse(json!({"kind": "Circle"}))).is_err());
+
+    let diff = Shape::Square(Side{ length: 1.0 }).diff(Shape::Square(Side{ length: 2.0 })).unwrap();
+    assert_eq!(serde_json::to_value(&diff).unwrap(), json!({"kind": "Square", "length": 2.0}));
+    assert!(Shape::Empty.diff(Shape::Empty).is_none());
+    assert!(matches!(Shape::Empty.diff(Shape::Circle{ radius: 1.0 }), Some(PartialShape::Circle(_))));
+
+    let mut base: PartialShape = parse(json!({"kind": "Circle"}));
+    base.merge(PartialShape::Unknown);
+    assert!(matches!(&base, PartialShape::Circle(circle) if circle.radius.is_none()));
+    base.merge(parse(json!({"kind": "Circle", "radius": 3.0})));
+    assert!(base.is_complete());
+    base.merge(parse(json!({"kind": "Empty"})));
+    assert!(matches!(base, PartialShape::Empty));
+}
@@ -0,0 +1,139 @@
+use ::aspartial::{AsPartial, Defaulted, FromPartial, Tristate};
+use ::aspartial::complete::CompletionProblem;
+
+#[derive(AsPartial, Debug, PartialEq, serde::Deserialize)]
+#[aspartial(name = PartialAddress)]
+#[aspartial(complete)]
+struct Address {
+    city: String,
+    #[serde(rename = "zipCode")]
+    zip_code: u32,
+}
+
+#[derive(AsPartial, Debug, PartialEq, serde::Deserialize)]
+#[aspartial(name = PartialItem)]
+#[aspartial(complete)]
+struct Item {
+    #[serde(rename = "itemName")]
+    name: String,
+    address: Address,
+}
+
+#[derive(AsPartial, Debug, PartialEq, serde::Deserialize)]
+#[aspartial(name = PartialOrder)]
+#[aspartial(complete)]
+struct Order {
+    items: Vec<Item>,
+    note: Option<String>,
+    #[serde(default = "_seven")]
+    priority: u32,
+    #[serde(default)]
+    urgent: bool,
+}
+
+fn _seven() -> u32 {
+    7
+}
+
+#[derive(AsPartial, Debug, PartialEq, serde::Deserialize)]
+#[aspartial(newtype)]
+#[aspartial(complete)]
+struct OrderId(u64);
+
+#[derive(AsPartial, Debug, PartialEq, serde::Deserialize)]
+#[aspartial(name = PartialPair)]
+#[aspartial(complete)]
+struct Pair(String, OrderId);
+
+#[derive(AsPartial, Debug, PartialEq, serde::Deserialize)]
+#[aspartial(name = PartialTagged)]
+#[aspartial(complete)]
+#[serde(tag = "type", content = "data")]
+enum Tagged {
+    Address(Address),
+    Item(Item),
+}
+
+#[derive(AsPartial, Debug, PartialEq, serde::Deserialize)]
+#[aspartial(name = PartialUntagged)]
+#[aspartial(complete)]
+#[serde(untagged)]
+enum Untagged {
+    Address(Address),
+    Item(Item),
+}
+
+#[test]
+fn test_complete_struct(){
+    let raw = serde_json::json!({
+        "items": [{"itemName": "chair", "address": {"city": "Lisboa", "zipCode": 1100}}],
+    });
+    let partial: PartialOrder = serde_json::from_value(raw).unwrap();
+    let order = partial.try_complete().unwrap();
+    assert_eq!(order, Order{
+        items: vec![Item{ name: "chair".to_owned(), address: Address{ city: "Lisboa".to_owned(), zip_code: 1100 } }],
+        note: None,
+        priority: 7,
+        urgent: false,
+    });
+}
+
+#[test]
+fn test_missing_fields_are_listed_with_wire_paths(){
+    let raw = serde_json::json!({
+        "items": [
+            {"itemName": "chair", "address": {"city": "Lisboa", "zipCode": 1100}},
+            {"itemName": "table", "address": {"zipCode": 1200}},
+            {"address": {}},
+        ],
+    });
+    let partial: PartialOrder = serde_json::from_value(raw).unwrap();
+    let err = Order::from_partial(partial).unwrap_err();
+    let missing: Vec<String> = err.missing_paths().map(|path| path.to_string()).collect();
+    assert_eq!(missing, vec![
+        "items[1].address.city",
+        "items[2].itemName",
+        "items[2].address.city",
+        "items[2].address.zipCode",
+    ]);
+    assert!(err.to_string().contains("missing items[1].address.city"));
+
+    let err = PartialOrder{ items: None, note: Tristate::Missing, priority: Defaulted::Supplied(1), urgent: Defaulted::Supplied(true) }.try_complete().unwrap_err();
+    assert_eq!(err.problems(), &[CompletionProblem::Missing(["items"].into_iter().map(Into::into).collect())]);
+}
+
+#[test]
+fn test_complete_tuple_struct_and_newtype(){
+    let partial: PartialPair = serde_json::from_value(serde_json::json!(["a", 3])).unwrap();
+    assert_eq!(partial.try_complete().unwrap(), Pair("a".to_owned(), OrderId(3)));
+
+    let partial: PartialPair = serde_json::from_value(serde_json::json!(["a", null])).unwrap();
+    let missing: Vec<String> = partial.try_complete().unwrap_err().missing_paths().map(|path| path.to_string()).collect();
+    assert_eq!(missing, vec!["[1]"]);
+}
+
+#[test]
+fn test_complete_tagged_enum(){
+    let raw = serde_json::json!({"type": "Address", "data": {"city": "Porto", "zipCode": 4000}});
+    let partial: PartialTagged = serde_json::from_value(raw).unwrap();
+    assert_eq!(partial.try_complete().unwrap(), Tagged::Address(Address{ city: "Porto".to_owned(), zip_code: 4000 }));
+
+    let raw = serde_json::json!({"type": "Item", "data": {"itemName": "lamp"}});
+    let partial: PartialTagged = serde_json::from_value(raw).unwrap();
+    let missing: Vec<String> = partial.try_complete().unwrap_err().missing_paths().map(|path| path.to_string()).collect();
+    assert_eq!(missing, vec!["data.address"]);
+}
+
+#[test]
+fn test_enum_variant_selection(){
+    let partial: PartialUntagged = serde_json::from_value(serde_json::json!({"city": "Faro"})).unwrap();
+    let err = partial.try_complete().unwrap_err();
+    assert!(matches!(
+        err.problems(),
+        [CompletionProblem::AmbiguousVariant{ candidates, .. }] if candidates == &["Address", "Item"]
+    ));
+
+    let partial: PartialUntagged = serde_json::from_value(serde_json::json!(12)).unwrap();
+    let err = partial.try_complete().unwrap_err();
+    assert!(matches!(err.problems(), [CompletionProblem::NoVariant(path)] if path.is_root()));
+}
@@ -0,0 +1,87 @@
+use ::aspartial::AsPartial;
+use ::aspartial::json::repair_truncated;
+
+#[allow(dead_code)]
+#[derive(AsPartial)]
+#[aspartial(name = PartialAddress)]
+#[aspartial(attrs( #[derive(PartialEq, Debug)] ))]
+struct Address {
+    street: String,
+    number: u32,
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial)]
+#[aspartial(name = PartialPerson)]
+#[aspartial(attrs( #[derive(PartialEq, Debug)] ))]
+struct Person {
+    name: String,
+    tags: Vec<String>,
+    address: Address,
+}
+
+#[test]
+fn test_from_str_truncated(){
+    let parsed = aspartial::from_str_truncated::<Person>(
+        r#"{"name": "Jo", "tags": ["a", "b"], "address": {"street": "Main St", "number": "#
+    ).unwrap();
+    assert_eq!(
+        parsed,
+        PartialPerson{
+            name: Some("Jo".to_owned()),
+            tags: Some(vec!["a".to_owned(), "b".to_owned()]),
+            address: Some(PartialAddress{ street: Some("Main St".to_owned()), number: None }),
+        }
+    );
+
+    let parsed = aspartial::from_str_truncated::<Person>(r#"{"name": "Jo", "tags": ["a", "b"#).unwrap();
+    assert_eq!(parsed.tags, Some(vec!["a".to_owned(), "b".to_owned()]));
+
+    assert!(matches!(aspartial::from_str_truncated::<Person>("  "), Err(aspartial::json::Error::Empty)));
+    assert!(matches!(aspartial::from_str_truncated::<Person>("{]"), Err(aspartial::json::Error::Syntax(_))));
+    assert!(matches!(aspartial::from_str_truncated::<Person>("[1, 2"), Err(aspartial::json::Error::Deserialize(_))));
+}
+
+#[test]
+fn test_repair_truncated(){
+    let cases = [
+        (r#"{"a": [1, 2"#, r#"{"a": [1]}"#),
+        (r#"{"a": [1, 2 "#, r#"{"a": [1, 2 ]}"#),
+        (r#"{"a": [1, 2,"#, r#"{"a": [1, 2]}"#),
+        (r#"{"a": "some te"#, r#"{"a": "some te"}"#),
+        (r#"{"a": "line\"#, r#"{"a": "line"}"#),
+        (r#"{"a": "smile \ud83d"#, r#"{"a": "smile "}"#),
+        (r#"{"a": 1, "b":"#, r#"{"a": 1}"#),
+        (r#"{"a": 1, "b"#, r#"{"a": 1}"#),
+        (r#"{"a": 1,  "#, r#"{"a": 1}"#),
+        (r#"{"a": tr"#, r#"{}"#),
+        (r#"{"a": -"#, r#"{}"#),
+        (r#"[{"x": {"y": [true, nul"#, r#"[{"x": {"y": [true]}}]"#),
+        (r#""just a str"#, r#""just a str""#),
+        (r#"{"complete": true}"#, r#"{"complete": true}"#),
+    ];
+    for (truncated, expected) in cases {
+        let repaired = repair_truncated(truncated).unwrap();
+        assert_eq!(repaired, expected, "repairing {truncated}");
+        let from_repaired: serde_json::Value = serde_json::from_str(&repaired).unwrap();
+        let mut parser = aspartial::json::IncrementalParser::new();
+        parser.push(truncated.as_bytes()).unwrap();
+        assert_eq!(Some(from_repaired), parser.value());
+    }
+
+    assert!(matches!(repair_truncated(""), Err(aspartial::json::Error::Empty)));
+}
+
+#[test]
+fn test_complete_top_level_scalars(){
+    // a top-level number has nothing after it to tell that it ended
+    assert_eq!(repair_truncated("12").unwrap(), "12");
+    assert_eq!(repair_truncated("-1.5e3").unwrap(), "-1.5e3");
+    assert_eq!(repair_truncated("true").unwrap(), "true");
+    assert_eq!(repair_truncated(r#""done""#).unwrap(), r#""done""#);
+    assert!(matches!(repair_truncated("-"), Err(aspartial::json::Error::Empty)));
+
+    assert_eq!(aspartial::from_str_truncated::<u32>("12").unwrap(), 12);
+    assert_eq!(aspartial::from_str_truncated::<f64>("0.25").unwrap(), 0.25);
+    assert_eq!(aspartial::from_str_truncated::<Vec<u32>>("[1, 2]").unwrap(), vec![1, 2]);
+}
@@ -0,0 +1,140 @@
+use ::aspartial::AsPartial;
+use ::aspartial::json::IncrementalParser;
+
+#[allow(dead_code)]
+#[derive(AsPartial)]
+#[aspartial(name = PartialInner)]
+#[aspartial(attrs( #[derive(PartialEq, Debug)] ))]
+struct Inner {
+    x: u32,
+    y: String,
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial)]
+#[aspartial(name = PartialOuter)]
+#[aspartial(attrs( #[derive(PartialEq, Debug)] ))]
+struct Outer {
+    name: String,
+    inners: Vec<Inner>,
+    ratio: f64,
+}
+
+#[test]
+fn test_partials_while_streaming(){
+    let mut parser = IncrementalParser::new();
+    assert_eq!(parser.partial::<Outer>().unwrap(), None);
+
+    parser.push(br#"{"name": "some na"#).unwrap();
+    let partial = parser.partial::<Outer>().unwrap().unwrap();
+    assert_eq!(partial, PartialOuter{ name: Some("some na".to_owned()), inners: None, ratio: None });
+
+    parser.push(br#"me", "inners": [{"x": 1, "y": "a"}, {"x": 2, "y""#).unwrap();
+    let partial = parser.partial::<Outer>().unwrap().unwrap();
+    assert_eq!(
+        partial.inners,
+        Some(vec![
+            PartialInner{ x: Some(1), y: Some("a".to_owned()) },
+            PartialInner{ x: Some(2), y: None },
+        ])
+    );
+    assert_eq!(partial.name, Some("some name".to_owned()));
+
+    parser.push(br#": "b"}], "ratio": 0.5}"#).unwrap();
+    assert!(parser.is_complete());
+    let partial = parser.partial::<Outer>().unwrap().unwrap();
+    assert_eq!(partial.ratio, Some(0.5));
+}
+
+#[test]
+fn test_byte_by_byte_matches_whole_document(){
+    let doc = r#"{"text": "caf\u00e9 \ud83d\ude00 ünïcødé 🦀", "list": [1, -2.5e3, true, null, {"k": []}]}"#;
+    let mut parser = IncrementalParser::new();
+    for b in doc.as_bytes() {
+        parser.push(std::slice::from_ref(b)).unwrap();
+        // every intermediate snapshot must be valid, even mid-character
+        parser.value().unwrap();
+    }
+    let expected: serde_json::Value = serde_json::from_str(doc).unwrap();
+    assert_eq!(parser.finish().unwrap(), expected);
+}
+
+#[test]
+fn test_split_multibyte_character(){
+    let doc = r#"{"y": "🦀"}"#.as_bytes();
+    let crab_start = doc.iter().position(|b| *b == 0xF0).unwrap();
+    let mut parser = IncrementalParser::new();
+    parser.push(&doc[..crab_start + 2]).unwrap();
+    assert_eq!(parser.value().unwrap(), serde_json::json!({"y": ""}));
+    parser.push(&doc[crab_start + 2..]).unwrap();
+    assert_eq!(parser.value().unwrap(), serde_json::json!({"y": "🦀"}));
+}
+
+#[test]
+fn test_dangling_keys_are_dropped(){
+    let mut parser = IncrementalParser::new();
+    parser.push(br#"{"a": 1, "b"#).unwrap();
+    assert_eq!(parser.value().unwrap(), serde_json::json!({"a": 1}));
+    parser.push(br#"": "#).unwrap();
+    assert_eq!(parser.value().unwrap(), serde_json::json!({"a": 1}));
+    parser.push(br#"["#).unwrap();
+    assert_eq!(parser.value().unwrap(), serde_json::json!({"a": 1, "b": []}));
+}
+
+#[test]
+fn test_top_level_number_needs_finish(){
+    let mut parser = IncrementalParser::new();
+    parser.push(b"123").unwrap();
+    assert!(!parser.is_complete());
+    assert_eq!(parser.finish().unwrap(), serde_json::json!(123));
+
+    let mut parser = IncrementalParser::new();
+    parser.push(b"[1, 2").unwrap();
+    assert!(parser.finish().is_err());
+}
+
+#[test]
+fn test_syntax_errors(){
+    let mut parser = IncrementalParser::new();
+    parser.push(br#"{"a": 1"#).unwrap();
+    let err = parser.push(br#" "b": 2}"#).unwrap_err();
+    assert_eq!(err.offset(), 8);
+    // errors are sticky
+    assert!(parser.push(b"}").is_err());
+
+    for invalid in [&b"[1,]"[..], b"{\"a\" 1}", b"tru3", b"01", b"\"\\x\"", b"{} {}"] {
+        assert!(IncrementalParser::new().push(invalid).is_err(), "{:?}", std::str::from_utf8(invalid));
+    }
+}
+
+#[test]
+fn test_generation_tracks_changes_to_the_value(){
+    let mut parser = IncrementalParser::new();
+    parser.push(br#"{"name": "a"#).unwrap();
+    let streaming = parser.generation();
+    // the end of a string settles it, even if the value stays the same
+    parser.push(br#"""#).unwrap();
+    let settled = parser.generation();
+    assert_ne!(settled, streaming);
+
+    // keys without a value yet may still be enum tags
+    parser.push(br#", "rat"#).unwrap();
+    let key_streaming = parser.generation();
+    assert_ne!(key_streaming, settled);
+    parser.push(br#"io""#).unwrap();
+    let key_settled = parser.generation();
+    assert_ne!(key_settled, key_streaming);
+    // withheld numbers don't show up in the value, and neither do delimiters
+    parser.push(br#": 0.5"#).unwrap();
+    assert_eq!(parser.generation(), key_settled);
+    parser.push(b",").unwrap();
+    let with_ratio = parser.generation();
+    assert_ne!(with_ratio, key_settled);
+
+    parser.push(br#" "inners": []"#).unwrap();
+    let with_inners = parser.generation();
+    assert_ne!(with_inners, with_ratio);
+    // closing a container doesn't change its contents
+    parser.push(b"}").unwrap();
+    assert_eq!(parser.generation(), with_inners);
+}
@@ -0,0 +1,133 @@
+use ::aspartial::{AsPartial, Introspect};
+
+#[allow(dead_code)]
+#[derive(AsPartial, serde::Deserialize)]
+#[aspartial(name = PartialAddress)]
+struct Address {
+    city: String,
+    #[serde(rename = "zipCode")]
+    zip: u32,
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial, serde::Deserialize)]
+#[aspartial(name = PartialContact)]
+enum Contact {
+    Email(String),
+    Mail(Address),
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial, serde::Deserialize)]
+#[aspartial(name = PartialPerson)]
+struct Person {
+    name: String,
+    nickname: Option<String>,
+    addresses: Vec<Address>,
+    contact: Contact,
+    #[serde(default)]
+    verified: bool,
+}
+
+fn paths(paths: Vec<::aspartial::path::FieldPath>) -> Vec<String> {
+    paths.iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn test_empty_partial(){
+    let partial: PartialPerson = serde_json::from_str("{}").unwrap();
+    assert!(partial.is_empty());
+    assert!(!partial.is_complete());
+    assert_eq!(partial.completeness(), 0.0);
+    // the defaulted `verified` is neither present nor missing
+    assert_eq!(paths(partial.missing_paths()), vec!["name", "nickname", "addresses", "contact"]);
+}
+
+#[test]
+fn test_nested_paths(){
+    let partial: PartialPerson = serde_json::from_str(r#"{
+        "name": "Ana",
+        "nickname": null,
+        "addresses": [{"city": "Lisboa", "zipCode": 1100}, {"city": "Porto"}],
+        "contact": {"Mail": {"zipCode": 4000}}
+    }"#).unwrap();
+    assert!(!partial.is_empty());
+    assert_eq!(paths(partial.present_paths()), vec![
+        "name",
+        "nickname",
+        "addresses[0].city",
+        "addresses[0].zipCode",
+        "addresses[1].city",
+        "contact.Mail.zipCode",
+    ]);
+    assert_eq!(paths(partial.missing_paths()), vec!["addresses[1].zipCode", "contact.Mail.city"]);
+    assert_eq!(partial.completeness(), 0.75);
+}
+
+#[test]
+fn test_complete_partial(){
+    let partial: PartialPerson = serde_json::from_str(r#"{
+        "name": "Ana", "nickname": "A", "addresses": [], "contact": {"Email": "ana@example.com"}, "verified": true
+    }"#).unwrap();
+    assert!(partial.is_complete());
+    assert_eq!(partial.completeness(), 1.0);
+    assert_eq!(paths(partial.present_paths()), vec!["name", "nickname", "addresses", "contact.Email", "verified"]);
+}
+
+/// Implements `AsPartial` by hand, with a partial that doesn't implement `Introspect`
+#[allow(dead_code)]
+#[derive(serde::Deserialize)]
+struct Coordinates {
+    lat: f64,
+    lon: f64,
+}
+
+#[allow(dead_code)]
+#[derive(serde::Deserialize)]
+struct PartialCoordinates {
+    lat: Option<f64>,
+    lon: Option<f64>,
+}
+
+impl AsPartial for Coordinates {
+    type Partial = PartialCoordinates;
+    fn to_partial(self) -> Self::Partial {
+        PartialCoordinates{ lat: Some(self.lat), lon: Some(self.lon) }
+    }
+}
+
+impl AsPartial for PartialCoordinates {
+    type Partial = Self;
+    fn to_partial(self) -> Self::Partial {
+        self
+    }
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial, serde::Deserialize)]
+#[aspartial(name = PartialPin)]
+struct Pin {
+    label: String,
+    at: Coordinates,
+    previously: Option<Coordinates>,
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial, serde::Deserialize)]
+#[aspartial(name = PartialPlace)]
+#[serde(untagged)]
+enum Place {
+    Pin(Pin),
+    At(Coordinates),
+}
+
+#[test]
+fn test_hand_written_partials_are_leaves(){
+    let partial: PartialPin = serde_json::from_str(r#"{"at": {"lat": 1.0}}"#).unwrap();
+    assert_eq!(paths(partial.present_paths()), vec!["at"]);
+    assert_eq!(paths(partial.missing_paths()), vec!["label", "previously"]);
+
+    let partial: PartialPlace = serde_json::from_str(r#"{"lat": 1.0}"#).unwrap();
+    let best = partial.resolve().unwrap().best;
+    assert_eq!((best.tag, best.score.present), ("At", 1));
+}
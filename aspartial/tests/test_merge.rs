@@ -0,0 +1,160 @@
+use ::aspartial::{AsPartial, Merge, TagMatch};
+
+#[allow(dead_code)]
+#[derive(AsPartial, serde::Deserialize)]
+#[aspartial(name = PartialAddress)]
+#[aspartial(attrs( #[derive(PartialEq, Debug)] ))]
+struct Address {
+    city: String,
+    zip: u32,
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial, serde::Deserialize)]
+#[aspartial(name = PartialPerson)]
+#[aspartial(attrs( #[derive(PartialEq, Debug)] ))]
+struct Person {
+    name: String,
+    address: Address,
+    nicknames: Vec<String>,
+    extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial, serde::Deserialize)]
+#[aspartial(name = PartialShape)]
+#[serde(tag = "kind")]
+enum Shape {
+    Address(Address),
+    Person(Person),
+}
+
+fn parse<T: serde::de::DeserializeOwned>(value: serde_json::Value) -> T {
+    serde_json::from_value(value).unwrap()
+}
+
+#[test]
+fn test_merge_nested_struct(){
+    let mut base: PartialPerson = parse(serde_json::json!({
+        "name": "Ana",
+        "address": {"city": "Lisboa"},
+        "nicknames": ["a", "b", "c"],
+        "extra": {"x": 1, "nested": {"keep": true, "replace": 1}},
+    }));
+    let overlay: PartialPerson = parse(serde_json::json!({
+        "address": {"zip": 1100},
+        "nicknames": ["z"],
+        "extra": {"nested": {"replace": 2}, "y": 2},
+    }));
+    base.merge(overlay);
+    assert_eq!(base, parse(serde_json::json!({
+        "name": "Ana",
+        "address": {"city": "Lisboa", "zip": 1100},
+        "nicknames": ["z", "b", "c"],
+        "extra": {"x": 1, "y": 2, "nested": {"keep": true, "replace": 2}},
+    })));
+}
+
+#[test]
+fn test_merge_overlay_wins(){
+    let base: PartialAddress = parse(serde_json::json!({"city": "Lisboa", "zip": 1100}));
+    let overlay: PartialAddress = parse(serde_json::json!({"city": "Porto"}));
+    assert_eq!(base.merged(overlay), PartialAddress{ city: Some("Porto".to_owned()), zip: Some(1100) });
+}
+
+#[test]
+fn test_merge_builtin_partials(){
+    let mut base = vec![Some(1), None];
+    base.merge(vec![None, Some(2), Some(3)]);
+    assert_eq!(base, vec![Some(1), Some(2), Some(3)]);
+
+    let mut base: Option<Vec<u32>> = None;
+    base.merge(Some(vec![4]));
+    base.merge(None);
+    assert_eq!(base, Some(vec![4]));
+}
+
+#[test]
+fn test_merge_partial_enum(){
+    let mut base: PartialShape = parse(serde_json::json!({"kind": "Address", "city": "Lisboa"}));
+    base.merge(parse(serde_json::json!({"kind": "Address", "zip": 1100})));
+    assert_eq!(base.address, Some(PartialAddress{ city: Some("Lisboa".to_owned()), zip: Some(1100) }));
+    assert_eq!(base.tag_match, Some(TagMatch::Exact("Address")));
+
+    // a tag in the overlay drops the variants it rules out
+    base.merge(parse(serde_json::json!({"kind": "Person", "name": "Rui"})));
+    assert_eq!(base.address, None);
+    assert_eq!(base.person.as_ref().and_then(|person| person.name.as_deref()), Some("Rui"));
+    assert_eq!(base.tag_match, Some(TagMatch::Exact("Person")));
+}
+
+/// Implements `AsPartial` by hand, with a partial that implements neither `Merge` nor `Serialize`
+#[allow(dead_code)]
+#[derive(serde::Deserialize)]
+struct Coordinates {
+    lat: f64,
+    lon: f64,
+}
+
+#[derive(Debug, PartialEq, serde::Deserialize)]
+struct PartialCoordinates {
+    lat: Option<f64>,
+    lon: Option<f64>,
+}
+
+impl AsPartial for Coordinates {
+    type Partial = PartialCoordinates;
+    fn to_partial(self) -> Self::Partial {
+        PartialCoordinates{ lat: Some(self.lat), lon: Some(self.lon) }
+    }
+}
+
+impl AsPartial for PartialCoordinates {
+    type Partial = Self;
+    fn to_partial(self) -> Self::Partial {
+        self
+    }
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial, serde::Deserialize)]
+#[aspartial(name = PartialPin)]
+#[aspartial(attrs( #[derive(PartialEq, Debug)] ))]
+struct Pin {
+    label: String,
+    at: Coordinates,
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial, serde::Deserialize)]
+#[aspartial(name = PartialPlace)]
+enum Place {
+    Pin(Pin),
+    At(Coordinates),
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial, serde::Deserialize)]
+#[aspartial(name = PartialPlaceRepr)]
+#[aspartial(enum_repr)]
+#[aspartial(attrs( #[derive(PartialEq, Debug)] ))]
+enum PlaceRepr {
+    At(Coordinates),
+}
+
+#[test]
+fn test_merge_replaces_hand_written_partials(){
+    let mut base: PartialPin = parse(serde_json::json!({"label": "home", "at": {"lat": 1.0}}));
+    base.merge(parse(serde_json::json!({"label": "home"})));
+    assert_eq!(base.at, Some(PartialCoordinates{ lat: Some(1.0), lon: None }));
+    base.merge(parse(serde_json::json!({"at": {"lon": 2.0}})));
+    assert_eq!(base, PartialPin{ label: Some("home".to_owned()), at: Some(PartialCoordinates{ lat: None, lon: Some(2.0) }) });
+
+    let mut base: PartialPlace = parse(serde_json::json!({"At": {"lat": 1.0}}));
+    base.merge(parse(serde_json::json!({"At": {"lon": 2.0}})));
+    assert_eq!(base.at, Some(PartialCoordinates{ lat: None, lon: Some(2.0) }));
+
+    let mut base: PartialPlaceRepr = parse(serde_json::json!({"At": {"lat": 1.0}}));
+    base.merge(parse(serde_json::json!({"At": {"lon": 2.0}})));
+    assert_eq!(base, PartialPlaceRepr::At(PartialCoordinates{ lat: None, lon: Some(2.0) }));
+}
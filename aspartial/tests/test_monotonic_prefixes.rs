@@ -0,0 +1,110 @@
+use ::aspartial::AsPartial;
+use ::aspartial::testing::{assert_monotonic_prefixes, check_monotonic_prefixes, Chunking, PrefixFailure};
+
+#[allow(dead_code)]
+#[derive(AsPartial)]
+#[aspartial(name = PartialAddress)]
+#[derive(serde::Deserialize)]
+struct Address {
+    city: String,
+    zip: u32,
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial)]
+#[aspartial(name = PartialPerson)]
+#[derive(serde::Deserialize)]
+struct Person {
+    name: String,
+    address: Address,
+    tags: Vec<String>,
+    #[serde(default)]
+    verified: bool,
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial)]
+#[aspartial(name = PartialEvent)]
+#[derive(serde::Deserialize)]
+#[serde(tag = "kind")]
+enum Event {
+    Joined(Person),
+    Moved(Address),
+}
+
+const PERSON: &str = r#"{"name": "Zoë", "address": {"city": "Lisboa", "zip": 1100}, "tags": ["a", "bc"], "verified": true}"#;
+
+#[test]
+fn test_derived_struct_is_monotonic(){
+    assert_monotonic_prefixes::<Person>(PERSON, Chunking::EveryByte);
+    assert_monotonic_prefixes::<Person>(PERSON, Chunking::EveryChar);
+    assert_monotonic_prefixes::<Person>(PERSON, Chunking::Fixed(7));
+    assert_monotonic_prefixes::<Person>(PERSON, Chunking::Boundaries(vec![20, 5, 60]));
+}
+
+#[test]
+fn test_tagged_enum_is_monotonic(){
+    // partial enums serialize in the shape of the original, so trying every variant before the tag arrives is invisible
+    let document = r#"{"kind": "Moved", "city": "Porto", "zip": 4000}"#;
+    assert_monotonic_prefixes::<Event>(document, Chunking::EveryByte);
+}
+
+#[test]
+fn test_tagged_enum_with_late_tag_regresses(){
+    // before the tag arrives every variant is tried, and the ones that don't match get dropped later
+    let document = r#"{"name": "Zoë", "city": "Porto", "zip": 4000, "kind": "Moved"}"#;
+    let failure = check_monotonic_prefixes::<Event>(document, &Chunking::EveryByte).unwrap_err();
+    let PrefixFailure::Regression { prefix, path, previous, current } = &failure else {
+        panic!("unexpected failure: {failure}")
+    };
+    assert_eq!(prefix, r#"{"name": "Zoë", "city": "Porto", "zip": 4000, "kind": "M"#);
+    assert_eq!(path.to_string(), "name");
+    assert_eq!(previous, &serde_json::json!("Zoë"));
+    assert_eq!(current, &serde_json::Value::Null);
+
+    // once the tag starts arriving, only the variants it names are left
+    let tag_started = document.find("Moved").unwrap() + 1;
+    assert_monotonic_prefixes::<Event>(document, Chunking::Boundaries((tag_started..document.len()).collect()));
+}
+
+/// A partial that forgets its contents once they get too long
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(from = "String")]
+struct Forgetful(Option<String>);
+
+impl From<String> for Forgetful {
+    fn from(value: String) -> Self {
+        Self(Some(value).filter(|value| value.len() <= 3))
+    }
+}
+
+impl AsPartial for Forgetful {
+    type Partial = Self;
+    fn to_partial(self) -> Self::Partial {
+        self
+    }
+}
+
+#[test]
+fn test_regression_is_reported(){
+    let failure = check_monotonic_prefixes::<Vec<Forgetful>>(r#"["ok", "long"]"#, &Chunking::EveryByte).unwrap_err();
+    let PrefixFailure::Regression { prefix, path, previous, current } = &failure else {
+        panic!("unexpected failure: {failure}")
+    };
+    assert_eq!(prefix, r#"["ok", "long"#);
+    assert_eq!(path.to_string(), "[1]");
+    assert_eq!(previous, &serde_json::json!("lon"));
+    assert_eq!(current, &serde_json::Value::Null);
+}
+
+#[test]
+fn test_parse_failure_is_reported(){
+    let failure = check_monotonic_prefixes::<Person>(r#"{"name": 12}"#, &Chunking::EveryByte).unwrap_err();
+    assert!(matches!(failure, PrefixFailure::Parse { prefix, .. } if prefix == r#"{"name": 12}"#));
+}
+
+#[test]
+#[should_panic(expected = "lost information at [1]")]
+fn test_assert_panics_on_regression(){
+    assert_monotonic_prefixes::<Vec<Forgetful>>(r#"["ok", "long"]"#, Chunking::Fixed(3));
+}
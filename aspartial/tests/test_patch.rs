@@ -0,0 +1,111 @@
+use ::aspartial::AsPartial;
+use ::aspartial::patch::{
+    apply_json_patch, apply_merge_patch, from_merge_patch, json_patch, merge_patch, to_merge_patch, PatchError,
+    PatchOperation,
+};
+use serde_json::json;
+
+#[allow(dead_code)]
+#[derive(AsPartial, Clone, serde::Serialize, serde::Deserialize)]
+#[aspartial(name = PartialAddress)]
+#[aspartial(attrs( #[derive(PartialEq, Debug)] ))]
+struct Address {
+    city: String,
+    zip: u32,
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial, Clone, serde::Serialize, serde::Deserialize)]
+#[aspartial(name = PartialPerson)]
+#[aspartial(attrs( #[derive(PartialEq, Debug)] ))]
+struct Person {
+    name: String,
+    address: Address,
+    tags: Vec<String>,
+}
+
+#[test]
+fn test_partial_to_and_from_merge_patch(){
+    let partial = PartialPerson{
+        name: None,
+        address: Some(PartialAddress{ city: Some("Porto".to_owned()), zip: None }),
+        tags: None,
+    };
+    let patch = to_merge_patch(&partial).unwrap();
+    assert_eq!(patch, json!({"address": {"city": "Porto"}}));
+    assert_eq!(from_merge_patch::<Person>(patch).unwrap(), partial);
+}
+
+#[test]
+fn test_merge_patch_rfc_7386_examples(){
+    let mut target = json!({"a": "b", "c": {"d": "e", "f": "g"}});
+    merge_patch(&mut target, json!({"a": "z", "c": {"f": null}}));
+    assert_eq!(target, json!({"a": "z", "c": {"d": "e"}}));
+
+    let mut target = json!({"a": [{"b": "c"}]});
+    merge_patch(&mut target, json!({"a": [1]}));
+    assert_eq!(target, json!({"a": [1]}));
+
+    let mut target = json!(["a", "b"]);
+    merge_patch(&mut target, json!({"a": "b", "c": null}));
+    assert_eq!(target, json!({"a": "b"}));
+}
+
+#[test]
+fn test_typed_merge_patch_rejects_invalid_patches(){
+    let mut document = json!({"name": "Ana", "address": {"city": "Lisboa", "zip": 1100}, "tags": []});
+    apply_merge_patch::<Person>(&mut document, json!({"address": {"zip": 1200}})).unwrap();
+    assert_eq!(document["address"], json!({"city": "Lisboa", "zip": 1200}));
+
+    let before = document.clone();
+    assert!(apply_merge_patch::<Person>(&mut document, json!({"address": {"zip": "nope"}})).is_err());
+    assert_eq!(document, before);
+}
+
+#[test]
+fn test_json_patch_from_difference(){
+    let old = Person{
+        name: "Ana".to_owned(),
+        address: Address{ city: "Lisboa".to_owned(), zip: 1100 },
+        tags: vec!["a".to_owned(), "b".to_owned(), "c".to_owned()],
+    };
+    let mut new = old.clone();
+    new.address.city = "Porto".to_owned();
+    new.tags = vec!["a".to_owned()];
+    let operations = json_patch(&old, &new).unwrap();
+    assert_eq!(operations, vec![
+        PatchOperation::Replace{ path: "/address/city".to_owned(), value: json!("Porto") },
+        PatchOperation::Remove{ path: "/tags/2".to_owned() },
+        PatchOperation::Remove{ path: "/tags/1".to_owned() },
+    ]);
+    assert_eq!(
+        serde_json::to_value(&operations[0]).unwrap(),
+        json!({"op": "replace", "path": "/address/city", "value": "Porto"}),
+    );
+
+    let mut document = serde_json::to_value(&old).unwrap();
+    apply_json_patch(&mut document, &operations).unwrap();
+    assert_eq!(document, serde_json::to_value(&new).unwrap());
+}
+
+#[test]
+fn test_apply_json_patch_operations(){
+    let operations: Vec<PatchOperation> = serde_json::from_value(json!([
+        {"op": "test", "path": "/a/b", "value": 1},
+        {"op": "add", "path": "/list/-", "value": 3},
+        {"op": "add", "path": "/list/0", "value": 0},
+        {"op": "copy", "from": "/a", "path": "/copied"},
+        {"op": "move", "from": "/a/b", "path": "/moved"},
+        {"op": "remove", "path": "/gone"},
+    ])).unwrap();
+    let mut document = json!({"a": {"b": 1}, "list": [1, 2], "gone": true});
+    apply_json_patch(&mut document, &operations).unwrap();
+    assert_eq!(document, json!({"a": {}, "list": [0, 1, 2, 3], "copied": {"b": 1}, "moved": 1}));
+
+    let failing = [
+        PatchOperation::Remove{ path: "/list/0".to_owned() },
+        PatchOperation::Test{ path: "/moved".to_owned(), value: json!(2) },
+    ];
+    assert_eq!(apply_json_patch(&mut document, &failing), Err(PatchError::TestFailed("/moved".to_owned())));
+    assert_eq!(document["list"], json!([0, 1, 2, 3]));
+}
@@ -0,0 +1,58 @@
+use ::aspartial::AsPartial;
+use serde_json::json;
+
+#[allow(dead_code)]
+#[derive(AsPartial, Debug, PartialEq, serde::Deserialize)]
+#[aspartial(name = PartialContact)]
+#[serde(untagged)]
+enum Contact {
+    Email { address: String },
+    Person { name: String, address: String, phone: String },
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial, Debug, PartialEq, serde::Deserialize)]
+#[aspartial(name = PartialShape)]
+#[serde(tag = "kind")]
+enum Shape {
+    Circle { radius: f64 },
+    Ring { radius: f64, width: f64 },
+}
+
+#[test]
+fn test_resolving_untagged_enums(){
+    let partial: PartialContact = serde_json::from_value(json!({"name": "ana", "address": "a@b.c"})).unwrap();
+    let resolution = partial.resolve().unwrap();
+    assert_eq!(resolution.best.tag, "Person");
+    assert_eq!(resolution.best.score.present, 2);
+    assert_eq!(resolution.best.score.missing, 1);
+    assert_eq!(resolution.runner_ups.iter().map(|candidate| candidate.tag).collect::<Vec<_>>(), vec!["Email"]);
+    assert!(resolution.is_clear());
+    assert!(matches!(partial.best_variant(), Some(PartialContactRef::Person(person)) if person.name.as_deref() == Some("ana")));
+
+    // with as many present fields, the variant with fewer missing ones wins
+    let partial: PartialContact = serde_json::from_value(json!({"address": "a@b.c"})).unwrap();
+    let resolution = partial.resolve().unwrap();
+    assert_eq!(resolution.best.tag, "Email");
+    assert_eq!(resolution.best.score.missing, 0);
+    assert!(resolution.is_clear());
+
+    let partial: PartialContact = serde_json::from_value(json!(3)).unwrap();
+    assert!(partial.resolve().is_none());
+}
+
+#[test]
+fn test_exact_tags_win(){
+    let partial: PartialShape = serde_json::from_value(json!({"kind": "Circle", "radius": 1.0})).unwrap();
+    let candidates = partial.candidates();
+    assert_eq!(candidates.len(), 1);
+    assert!(candidates[0].score.exact_tag);
+    assert!(matches!(candidates[0].variant, PartialShapeRef::Circle(circle) if circle.radius == Some(1.0)));
+
+    // a tag that is still arriving only allows the variants it is a prefix of
+    let partial = aspartial::from_str_truncated::<Shape>(r#"{"radius": 1.0, "width": 0.5, "kind": "R"#).unwrap();
+    let resolution = partial.resolve().unwrap();
+    assert_eq!(resolution.best.tag, "Ring");
+    assert!(!resolution.best.score.exact_tag);
+    assert!(resolution.runner_ups.is_empty());
+}
@@ -0,0 +1,57 @@
+use ::aspartial::{AsPartial, AsSerializablePartial};
+use serde_json::json;
+
+#[allow(dead_code)]
+#[derive(AsPartial, serde::Deserialize)]
+#[aspartial(name = PartialAddress)]
+#[serde(rename_all = "camelCase")]
+struct Address {
+    street_name: String,
+    #[serde(rename = "zip")]
+    postal_code: u32,
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial, serde::Deserialize)]
+#[aspartial(name = PartialLabeled)]
+struct Labeled<T> {
+    label: String,
+    value: T,
+    note: Option<String>,
+}
+
+fn round_trip<T: AsSerializablePartial>(value: serde_json::Value) -> serde_json::Value {
+    let partial: T::Partial = serde_json::from_value(value).unwrap();
+    serde_json::to_value(&partial).unwrap()
+}
+
+#[test]
+fn test_serialized_partial_keeps_wire_names(){
+    let partial: PartialAddress = serde_json::from_value(json!({"streetName": "Rua Augusta"})).unwrap();
+    assert_eq!(partial.street_name, Some("Rua Augusta".to_owned()));
+    assert_eq!(serde_json::to_value(&partial).unwrap(), json!({"streetName": "Rua Augusta"}));
+
+    let document = json!({"streetName": "Rua Augusta", "zip": 1100});
+    assert_eq!(round_trip::<Address>(document.clone()), document);
+}
+
+#[test]
+fn test_generic_partial_is_serializable(){
+    let document = json!({"label": "home", "value": {"zip": 1100}, "note": null});
+    assert_eq!(round_trip::<Labeled<Address>>(document.clone()), document);
+    assert_eq!(round_trip::<Labeled<u32>>(json!({"value": 3})), json!({"value": 3}));
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial, serde::Deserialize)]
+#[aspartial(name = PartialTagged)]
+#[aspartial(attrs( #[derive(Debug, serde::Serialize)] ))]
+struct Tagged {
+    tag: String,
+}
+
+#[test]
+fn test_serialize_derived_through_attrs(){
+    let document = json!({"tag": "urgent"});
+    assert_eq!(round_trip::<Tagged>(document.clone()), document);
+}
@@ -0,0 +1,88 @@
+use ::aspartial::AsPartial;
+use serde_json::json;
+
+#[allow(dead_code)]
+#[derive(AsPartial, serde::Serialize, serde::Deserialize)]
+#[aspartial(name = PartialAddress)]
+struct Address {
+    city: String,
+    zip: u32,
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial, serde::Serialize, serde::Deserialize)]
+#[aspartial(name = PartialExternal)]
+#[serde(rename_all = "snake_case")]
+enum External {
+    HomeAddress(Address),
+    Email(String),
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial, serde::Serialize, serde::Deserialize)]
+#[aspartial(name = PartialInternal)]
+#[serde(tag = "kind")]
+enum Internal {
+    Home(Address),
+    #[serde(rename = "office")]
+    Work(Address),
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial, serde::Serialize, serde::Deserialize)]
+#[aspartial(name = PartialAdjacent)]
+#[serde(tag = "t", content = "c")]
+enum Adjacent {
+    Home(Address),
+    Email(String),
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial, serde::Serialize, serde::Deserialize)]
+#[aspartial(name = PartialUntagged)]
+#[serde(untagged)]
+enum Untagged {
+    Home(Address),
+    Email(String),
+}
+
+fn round_trip<T: ::aspartial::AsSerializablePartial>(value: serde_json::Value) -> serde_json::Value {
+    let partial: T::Partial = serde_json::from_value(value).unwrap();
+    serde_json::to_value(&partial).unwrap()
+}
+
+#[test]
+fn test_externally_tagged(){
+    let document = json!({"home_address": {"city": "Lisboa"}});
+    assert_eq!(round_trip::<External>(document.clone()), document);
+    assert_eq!(round_trip::<External>(json!({"email": "ana@example.com"})), json!({"email": "ana@example.com"}));
+}
+
+#[test]
+fn test_internally_tagged(){
+    let document = json!({"kind": "office", "city": "Porto", "zip": 4000});
+    assert_eq!(round_trip::<Internal>(document.clone()), document);
+    // a tag that is still arriving is kept as is
+    assert_eq!(round_trip::<Internal>(json!({"kind": "off", "zip": 4000})), json!({"kind": "off", "zip": 4000}));
+}
+
+#[test]
+fn test_adjacently_tagged(){
+    let document = json!({"t": "Email", "c": "ana@example.com"});
+    assert_eq!(round_trip::<Adjacent>(document.clone()), document);
+    let document = json!({"t": "Home", "c": {"city": "Lisboa"}});
+    assert_eq!(round_trip::<Adjacent>(document.clone()), document);
+}
+
+#[test]
+fn test_untagged(){
+    assert_eq!(round_trip::<Untagged>(json!({"zip": 1100})), json!({"zip": 1100}));
+    assert_eq!(round_trip::<Untagged>(json!("ana@example.com")), json!("ana@example.com"));
+}
+
+#[test]
+fn test_full_value_serializes_like_the_original(){
+    let original = Internal::Work(Address{ city: "Porto".to_owned(), zip: 4000 });
+    let expected = serde_json::to_value(&original).unwrap();
+    assert_eq!(serde_json::to_value(original.to_partial()).unwrap(), expected);
+}
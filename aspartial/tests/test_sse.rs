@@ -0,0 +1,98 @@
+use ::aspartial::AsPartial;
+use ::aspartial::sse::{Event, SseDecoder, SseReader, SseStream};
+use ::futures::StreamExt;
+
+#[allow(dead_code)]
+#[derive(AsPartial)]
+#[aspartial(name = PartialDelta)]
+#[aspartial(attrs( #[derive(PartialEq, Debug)] ))]
+struct Delta {
+    content: String,
+    index: u32,
+}
+
+const FIXTURE: &[u8] = b"\
+: this is a comment\r\n\
+retry: 3000\r\n\
+\r\n\
+event: delta\r\n\
+id: 1\r\n\
+data: {\"content\": \"Hel\", \"index\": 0}\r\n\
+\r\n\
+data: {\"content\":\n\
+data:  \"lo\",\n\
+data: \"index\": 1}\n\
+\n\
+event: ping\n\
+\n\
+data: {\"content\": \"!\", \"ind\r\
+\r\
+data: [DONE]\n\
+\n\
+data: {\"content\": \"after done\"}\n\
+\n";
+
+#[test]
+fn test_sse_framing(){
+    let expected = vec![
+        Event{ event: Some("delta".to_owned()), data: r#"{"content": "Hel", "index": 0}"#.to_owned(), id: Some("1".to_owned()), retry: None },
+        Event{ event: None, data: "{\"content\":\n \"lo\",\n\"index\": 1}".to_owned(), id: Some("1".to_owned()), retry: None },
+        Event{ event: None, data: r#"{"content": "!", "ind"#.to_owned(), id: Some("1".to_owned()), retry: None },
+        Event{ event: None, data: "[DONE]".to_owned(), id: Some("1".to_owned()), retry: None },
+        Event{ event: None, data: r#"{"content": "after done"}"#.to_owned(), id: Some("1".to_owned()), retry: None },
+    ];
+
+    let mut decoder = SseDecoder::new();
+    assert_eq!(decoder.push(FIXTURE), expected);
+
+    // splitting the input anywhere, even between '\r' and '\n', produces the same events
+    let mut decoder = SseDecoder::new();
+    let events: Vec<Event> = FIXTURE.chunks(1).flat_map(|chunk| decoder.push(chunk)).collect();
+    assert_eq!(events, expected);
+
+    let mut decoder = SseDecoder::new();
+    assert_eq!(decoder.push(b"data: unterminated"), vec![]);
+    assert_eq!(decoder.push(b"\n\n").len(), 1);
+
+    // events without data are skipped, e.g. keep-alives
+    let mut decoder = SseDecoder::new();
+    assert_eq!(decoder.push(b"event: ping\ndata:\n\nevent: ping\n\n"), vec![]);
+    assert_eq!(decoder.push(b"data: x\n\n")[0].event, None);
+}
+
+#[test]
+fn test_sse_skips_empty_data(){
+    let fixture = b"data:\n\ndata: {\"content\": \"Hi\"}\n\ndata: \n\ndata: [DONE]\n\n";
+    let messages: Vec<_> = SseReader::<Delta, _>::new(&fixture[..]).collect();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].as_ref().unwrap().partial, PartialDelta{ content: Some("Hi".to_owned()), index: None });
+}
+
+#[test]
+fn test_sse_reader(){
+    let reader = std::io::BufReader::with_capacity(10, FIXTURE);
+    let partials: Vec<PartialDelta> = SseReader::<Delta, _>::new(reader)
+        .map(|message| message.unwrap().partial)
+        .collect();
+    assert_eq!(
+        partials,
+        vec![
+            PartialDelta{ content: Some("Hel".to_owned()), index: Some(0) },
+            PartialDelta{ content: Some("lo".to_owned()), index: Some(1) },
+            PartialDelta{ content: Some("!".to_owned()), index: None },
+        ]
+    );
+}
+
+#[test]
+fn test_sse_stream(){
+    let fixture = b"event: delta\ndata: {\"content\": \"Hi\"}\n\ndata: not json\n\ndata: [DONE]\n\n";
+    let messages: Vec<_> = futures::executor::block_on(
+        SseStream::<Delta, _>::new(futures::stream::iter(fixture.chunks(4))).collect()
+    );
+    assert_eq!(messages.len(), 2);
+    let first = messages[0].as_ref().unwrap();
+    assert_eq!(first.event.as_deref(), Some("delta"));
+    assert_eq!(first.partial, PartialDelta{ content: Some("Hi".to_owned()), index: None });
+    assert!(messages[1].is_err());
+}
@@ -0,0 +1,134 @@
+use ::aspartial::AsPartial;
+use ::aspartial::TagMatch;
+use ::aspartial::stream::{NdjsonReader, NdjsonStream, PartialDecoder, PartialReader, PartialStream, Record, Update};
+use ::futures::StreamExt;
+
+#[derive(AsPartial, serde::Deserialize, PartialEq, Debug)]
+#[aspartial(name = PartialMessage)]
+#[aspartial(attrs( #[derive(PartialEq, Debug)] ))]
+struct Message {
+    role: String,
+    content: String,
+}
+
+const DOC: &str = r#"{"role": "assistant", "content": "Hello there"}"#;
+
+fn expected_message() -> Message {
+    Message{ role: "assistant".to_owned(), content: "Hello there".to_owned() }
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial, serde::Deserialize)]
+#[aspartial(name = PartialCall)]
+#[serde(rename_all = "snake_case")]
+enum Call {
+    GetWeather{ city: String },
+    GetTime{ zone: String },
+}
+
+#[test]
+fn test_partial_decoder_follows_externally_tagged_keys(){
+    let mut decoder = PartialDecoder::<Call>::new();
+    let partial = decoder.push(br#"{"get_"#).unwrap().unwrap();
+    assert_eq!(partial.tag_match.as_ref().and_then(TagMatch::variant), None);
+    assert!(partial.tag_match.unwrap().is_open());
+
+    // the value is still `{}`, but the key narrows the variants down
+    let partial = decoder.push(b"ti").unwrap().unwrap();
+    assert_eq!(partial.tag_match.as_ref().and_then(TagMatch::variant), Some("get_time"));
+    assert!(partial.tag_match.unwrap().is_open());
+
+    let partial = decoder.push(br#"me""#).unwrap().unwrap();
+    assert_eq!(partial.tag_match, Some(TagMatch::Exact("get_time")));
+    assert!(decoder.push(b": ").unwrap().is_none());
+
+    let partial = decoder.push(br#"{"zone": "UT"#).unwrap().unwrap();
+    assert_eq!(partial.get_time.unwrap().zone.as_deref(), Some("UT"));
+}
+
+#[test]
+fn test_partial_reader(){
+    let reader = std::io::BufReader::with_capacity(8, DOC.as_bytes());
+    let updates: Vec<_> = PartialReader::<Message, _>::new(reader).map(Result::unwrap).collect();
+
+    let Some((Update::Complete(complete), partials)) = updates.split_last() else {
+        panic!("Expected a complete message at the end");
+    };
+    assert_eq!(complete, &expected_message());
+    let partials: Vec<&PartialMessage> = partials.iter()
+        .map(|update| match update {
+            Update::Partial(partial) => partial,
+            Update::Complete(_) => panic!("Only the last update should be complete"),
+        })
+        .collect();
+    assert_eq!(partials.first().unwrap(), &&PartialMessage{ role: None, content: None });
+    let last_partial = partials.last().unwrap();
+    assert_eq!(last_partial.role.as_deref(), Some("assistant"));
+    assert!("Hello there".starts_with(last_partial.content.as_deref().unwrap()));
+    // consecutive partials are never identical
+    assert!(partials.windows(2).all(|pair| pair[0] != pair[1]));
+}
+
+#[test]
+fn test_partial_reader_errors(){
+    let updates: Vec<_> = PartialReader::<Message, _>::new(&br#"{"role": "a"#[..]).collect();
+    assert!(matches!(updates.last(), Some(Err(aspartial::json::Error::Syntax(_)))));
+
+    let updates: Vec<_> = PartialReader::<Message, _>::new(&br#"{"role": "a"}"#[..]).collect();
+    assert!(matches!(updates.last(), Some(Err(aspartial::json::Error::Deserialize(_)))));
+}
+
+#[test]
+fn test_partial_stream(){
+    let chunks: Vec<&[u8]> = DOC.as_bytes().chunks(5).collect();
+    let updates: Vec<_> = futures::executor::block_on(
+        PartialStream::<Message, _>::new(futures::stream::iter(chunks)).collect()
+    );
+    assert!(updates.len() > 2);
+    match updates.last() {
+        Some(Ok(Update::Complete(complete))) => assert_eq!(complete, &expected_message()),
+        _ => panic!("Expected a complete message at the end"),
+    }
+}
+
+const NDJSON: &str = concat!(
+    r#"{"role": "user", "content": "Hi"}"#, "\n",
+    "\n",
+    r#"{"role": "oops" "content"}"#, "\n",
+    r#"{"role": "assistant", "content": "Hello there"}"#,
+);
+
+fn check_ndjson_records(records: Vec<Result<Record<Message>, aspartial::json::Error>>){
+    let mut errors = 0;
+    let mut completed = Vec::new();
+    for record in records {
+        match record {
+            Ok(Record{ index, update: Update::Complete(complete) }) => completed.push((index, complete)),
+            Ok(Record{ update: Update::Partial(_), .. }) => (),
+            Err(_) => errors += 1,
+        }
+    }
+    assert_eq!(errors, 1);
+    assert_eq!(
+        completed,
+        vec![
+            (0, Message{ role: "user".to_owned(), content: "Hi".to_owned() }),
+            (2, expected_message()),
+        ]
+    );
+}
+
+#[test]
+fn test_ndjson_reader(){
+    let reader = std::io::BufReader::with_capacity(16, NDJSON.as_bytes());
+    check_ndjson_records(NdjsonReader::<Message, _>::new(reader).collect());
+}
+
+#[test]
+fn test_ndjson_stream(){
+    let chunks: Vec<&[u8]> = NDJSON.as_bytes().chunks(3).collect();
+    let records = futures::executor::block_on(
+        NdjsonStream::<Message, _>::new(futures::stream::iter(chunks)).collect()
+    );
+    check_ndjson_records(records);
+}
@@ -0,0 +1,97 @@
+use ::aspartial::{ApplyPartial, AsPartial, Diff, FromPartial, Introspect, Merge, Tristate};
+use serde_json::json;
+
+#[derive(AsPartial, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[aspartial(name = PartialShape)]
+#[aspartial(apply)]
+#[aspartial(diff)]
+#[aspartial(attrs( #[derive(Debug)] ))]
+#[serde(tag = "kind")]
+#[serde(rename_all = "snake_case")]
+#[serde(rename_all_fields = "camelCase")]
+enum Shape {
+    Circle { radius: f64 },
+    Rectangle { top_width: f64, side_height: f64, label: Option<String> },
+    Point(f64),
+}
+
+#[derive(AsPartial, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[aspartial(name = PartialEvent)]
+#[aspartial(apply)]
+#[aspartial(diff)]
+enum Event<T> {
+    #[serde(rename_all = "UPPERCASE")]
+    Started { at: u64 },
+    Finished { at: u64, result: T },
+}
+
+fn paths(paths: Vec<::aspartial::path::FieldPath>) -> Vec<String> {
+    paths.iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn test_parsing_struct_variants(){
+    let partial: PartialShape = serde_json::from_value(json!({"kind": "rectangle", "topWidth": 2.0, "label": null})).unwrap();
+    let rectangle = partial.rectangle.as_ref().unwrap();
+    assert_eq!(rectangle.top_width, Some(2.0));
+    assert_eq!(rectangle.side_height, None);
+    assert_eq!(rectangle.label, Tristate::Null);
+    assert!(partial.circle.is_none());
+
+    let err = Shape::from_partial(partial).unwrap_err();
+    assert_eq!(err.to_string(), "could not complete partial: missing sideHeight");
+}
+
+#[test]
+fn test_struct_variant_round_trip(){
+    let shape = Shape::Rectangle { top_width: 2.0, side_height: 3.0, label: Some("door".to_owned()) };
+    let partial = shape.clone().to_partial();
+    let document = serde_json::to_value(&partial).unwrap();
+    assert_eq!(document, serde_json::to_value(&shape).unwrap());
+    let parsed: PartialShape = serde_json::from_value(document).unwrap();
+    assert_eq!(Shape::from_partial(parsed).unwrap(), shape);
+
+    let point: PartialShape = serde_json::from_value(json!({"kind": "point"})).unwrap();
+    assert!(Shape::from_partial(point).is_err());
+}
+
+#[test]
+fn test_apply_and_diff_struct_variants(){
+    let old = Shape::Rectangle { top_width: 2.0, side_height: 3.0, label: Some("door".to_owned()) };
+    let new = Shape::Rectangle { top_width: 2.0, side_height: 4.0, label: None };
+    let diff = old.diff(new.clone()).unwrap();
+    assert_eq!(serde_json::to_value(&diff).unwrap(), json!({"kind": "rectangle", "sideHeight": 4.0, "label": null}));
+
+    let mut patched = old.clone();
+    patched.apply_partial(diff).unwrap();
+    assert_eq!(patched, new);
+    assert!(new.clone().diff(new).is_none());
+
+    let mut patched = old;
+    let circle: PartialShape = serde_json::from_value(json!({"kind": "circle", "radius": 1.0})).unwrap();
+    patched.apply_partial(circle).unwrap();
+    assert_eq!(patched, Shape::Circle { radius: 1.0 });
+}
+
+#[test]
+fn test_merge_and_introspect_struct_variants(){
+    let mut base: PartialShape = serde_json::from_value(json!({"kind": "rectangle", "topWidth": 2.0})).unwrap();
+    let overlay: PartialShape = serde_json::from_value(json!({"kind": "rectangle", "sideHeight": 3.0})).unwrap();
+    base.merge(overlay);
+    assert_eq!(paths(base.present_paths()), vec!["topWidth", "sideHeight"]);
+    assert_eq!(paths(base.missing_paths()), vec!["label"]);
+}
+
+#[test]
+fn test_generic_struct_variants(){
+    let partial: PartialEvent<String> = serde_json::from_value(json!({"Started": {"AT": 3}})).unwrap();
+    assert_eq!(partial.started.as_ref().unwrap().at, Some(3));
+    assert_eq!(Event::<String>::from_partial(partial).unwrap(), Event::Started { at: 3 });
+
+    let event = Event::Finished { at: 5, result: "ok".to_owned() };
+    let document = serde_json::to_value(event.clone().to_partial()).unwrap();
+    assert_eq!(document, json!({"Finished": {"at": 5, "result": "ok"}}));
+
+    let partial: PartialEvent<String> = serde_json::from_value(json!({"Finished": {"at": 5}})).unwrap();
+    assert_eq!(paths(partial.missing_paths()), vec!["Finished.result"]);
+}
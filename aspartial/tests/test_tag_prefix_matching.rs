@@ -0,0 +1,202 @@
+use ::aspartial::{AsPartial, TagMatch};
+use ::aspartial::json::IncrementalParser;
+
+#[allow(dead_code)]
+#[derive(AsPartial)]
+#[aspartial(name = PartialSomeStruct)]
+#[aspartial(attrs( #[derive(PartialEq, Eq, Debug)] ))]
+#[derive(serde::Deserialize)]
+struct SomeStruct {
+    a: u32,
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial)]
+#[aspartial(name = PartialInternal)]
+#[derive(::serde::Deserialize)]
+#[serde(tag = "variant_tag")]
+enum Internal {
+    Variant1(SomeStruct),
+    Variant2(SomeStruct),
+    Other(SomeStruct),
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial)]
+#[aspartial(name = PartialAdjacent)]
+#[derive(::serde::Deserialize)]
+#[serde(tag = "t", content = "c")]
+enum Adjacent {
+    Circle(SomeStruct),
+    Square(SomeStruct),
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial)]
+#[aspartial(name = PartialExternal)]
+#[derive(::serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum External {
+    GetWeather(SomeStruct),
+    GetTime(SomeStruct),
+    Search(SomeStruct),
+}
+
+#[test]
+fn test_internally_tagged_prefix_narrows_candidates(){
+    let parsed = aspartial::from_str_truncated::<Internal>(r#"{"a": 1, "variant_tag": "Var"#).unwrap();
+    assert_eq!(
+        parsed.tag_match,
+        Some(TagMatch::Prefix{ tag: "Var".to_owned(), candidates: vec!["Variant1", "Variant2"] }),
+    );
+    assert!(parsed.tag_match.as_ref().unwrap().is_open());
+    assert_eq!(parsed.tag_match.as_ref().unwrap().variant(), None);
+    assert_eq!(parsed.variant1, Some(PartialSomeStruct{ a: Some(1) }));
+    assert_eq!(parsed.variant2, Some(PartialSomeStruct{ a: Some(1) }));
+    assert_eq!(parsed.other, None);
+}
+
+#[test]
+fn test_internally_tagged_unambiguous_prefix_commits(){
+    let parsed = aspartial::from_str_truncated::<Internal>(r#"{"a": 1, "variant_tag": "Ot"#).unwrap();
+    let tag_match = parsed.tag_match.as_ref().unwrap();
+    assert!(tag_match.is_open());
+    assert_eq!(tag_match.variant(), Some("Other"));
+    assert_eq!(parsed.variant1, None);
+    assert_eq!(parsed.variant2, None);
+    assert_eq!(parsed.other, Some(PartialSomeStruct{ a: Some(1) }));
+}
+
+#[test]
+fn test_exact_tag_is_not_open(){
+    let parsed: PartialInternal = serde_json::from_value(serde_json::json!({"variant_tag": "Variant2"})).unwrap();
+    assert_eq!(parsed.tag_match, Some(TagMatch::Exact("Variant2")));
+    assert!(!parsed.tag_match.unwrap().is_open());
+    assert_eq!(parsed.variant1, None);
+    assert_eq!(parsed.variant2, Some(PartialSomeStruct{ a: None }));
+}
+
+#[test]
+fn test_complete_prefix_is_unknown(){
+    // the tag has ended, so it can't turn into one of the tags it is the beginning of
+    let parsed = aspartial::from_str_truncated::<Internal>(r#"{"variant_tag": "Var", "a": 1"#).unwrap();
+    assert_eq!(parsed.tag_match, None);
+    assert_eq!(parsed.unknown_tag.map(|unknown| unknown.tag), Some("Var".to_owned()));
+    assert_eq!(parsed.variant1, None);
+    assert_eq!(parsed.variant2, None);
+}
+
+#[test]
+fn test_unknown_tag_matches_nothing(){
+    let parsed: PartialInternal = serde_json::from_value(serde_json::json!({"variant_tag": "Nope", "a": 1})).unwrap();
+    assert_eq!(parsed.tag_match, None);
+    assert_eq!(parsed.variant1, None);
+    assert_eq!(parsed.variant2, None);
+    assert_eq!(parsed.other, None);
+}
+
+#[test]
+fn test_adjacently_tagged_prefix(){
+    let parsed = aspartial::from_str_truncated::<Adjacent>(r#"{"c": {"a": 2}, "t": "Sq"#).unwrap();
+    assert_eq!(parsed.tag_match.as_ref().and_then(TagMatch::variant), Some("Square"));
+    assert_eq!(parsed.circle, None);
+    assert_eq!(parsed.square, Some(PartialSomeStruct{ a: Some(2) }));
+}
+
+#[test]
+fn test_externally_tagged_key_prefix(){
+    let parsed = aspartial::from_str_truncated::<External>(r#"{"get_"#).unwrap();
+    assert_eq!(
+        parsed.tag_match,
+        Some(TagMatch::Prefix{ tag: "get_".to_owned(), candidates: vec!["get_weather", "get_time"] }),
+    );
+    // the payload hasn't started arriving yet
+    assert_eq!(parsed.get_weather, None);
+    assert_eq!(parsed.get_time, None);
+    assert_eq!(parsed.search, None);
+
+    let parsed = aspartial::from_str_truncated::<External>(r#"{"get_time": {"a": 3"#).unwrap();
+    assert_eq!(parsed.tag_match, Some(TagMatch::Exact("get_time")));
+    assert_eq!(parsed.get_weather, None);
+    assert_eq!(parsed.get_time, Some(PartialSomeStruct{ a: None }));
+
+    // a key that has ended only matches exactly
+    let parsed: PartialExternal = serde_json::from_value(serde_json::json!({"get_t": {}})).unwrap();
+    assert_eq!(parsed.tag_match, None);
+    assert_eq!(parsed.unknown_tag.map(|unknown| unknown.tag), Some("get_t".to_owned()));
+}
+
+#[test]
+fn test_open_tag_while_streaming(){
+    let mut parser = IncrementalParser::new();
+    parser.push(br#"{"variant_tag": "Varia"#).unwrap();
+    let partial = parser.partial::<Internal>().unwrap().unwrap();
+    assert_eq!(partial.tag_match.as_ref().unwrap().variant(), None);
+
+    parser.push(br#"nt2", "a": 5}"#).unwrap();
+    let partial = parser.partial::<Internal>().unwrap().unwrap();
+    assert_eq!(partial.tag_match, Some(TagMatch::Exact("Variant2")));
+    assert_eq!(partial.variant2, Some(PartialSomeStruct{ a: Some(5) }));
+}
+
+#[test]
+fn test_tag_naming_a_variant_stays_open_while_streaming(){
+    let mut parser = IncrementalParser::new();
+    parser.push(br#"{"a": 1, "variant_tag": "Other"#).unwrap();
+    let partial = parser.partial::<Internal>().unwrap().unwrap();
+    // "Other" could still turn into e.g. "OtherThing"
+    assert_eq!(partial.tag_match, Some(TagMatch::Prefix{ tag: "Other".to_owned(), candidates: vec!["Other"] }));
+
+    parser.push(br#"""#).unwrap();
+    let partial = parser.partial::<Internal>().unwrap().unwrap();
+    assert_eq!(partial.tag_match, Some(TagMatch::Exact("Other")));
+}
+
+#[test]
+fn test_variant_tags_and_dangling_keys(){
+    assert_eq!(PartialExternal::VARIANT_TAGS, &["get_weather", "get_time", "search"]);
+    // a key still being streamed isn't part of the value yet, but it reaches the partial
+    let mut parser = IncrementalParser::new();
+    parser.push(br#"{"se"#).unwrap();
+    assert_eq!(parser.value(), Some(serde_json::json!({})));
+    let partial = parser.partial::<External>().unwrap().unwrap();
+    assert_eq!(partial.tag_match.as_ref().and_then(TagMatch::variant), Some("search"));
+    assert!(partial.tag_match.as_ref().unwrap().is_open());
+
+    // as does a key that has ended, but has no value yet
+    parser.push(br#"arch": "#).unwrap();
+    let partial = parser.partial::<External>().unwrap().unwrap();
+    assert_eq!(partial.tag_match, Some(TagMatch::Exact("search")));
+    assert_eq!(partial.search, None);
+    assert_eq!(partial.unknown_tag, None);
+
+    let mut parser = IncrementalParser::new();
+    parser.push(br#"{"nope": "#).unwrap();
+    let partial = parser.partial::<External>().unwrap().unwrap();
+    assert_eq!(partial.tag_match, None);
+    assert_eq!(partial.unknown_tag.map(|unknown| unknown.tag), Some("nope".to_owned()));
+}
+
+#[test]
+fn test_nested_tags_are_looked_up_at_their_path(){
+    #[allow(dead_code)]
+    #[derive(AsPartial, serde::Deserialize)]
+    #[aspartial(name = PartialHolder)]
+    struct Holder {
+        items: Vec<Internal>,
+    }
+
+    // the first tag has ended, the second is still arriving
+    let parsed = aspartial::from_str_truncated::<Holder>(
+        r#"{"items": [{"variant_tag": "Ot", "a": 1}, {"variant_tag": "Ot"#
+    ).unwrap();
+    let items = parsed.items.unwrap();
+    assert_eq!(items[0].tag_match, None);
+    assert_eq!(items[1].tag_match.as_ref().and_then(TagMatch::variant), Some("Other"));
+}
+
+#[test]
+fn test_to_partial_records_exact_tag(){
+    let partial = Adjacent::Circle(SomeStruct{ a: 1 }).to_partial();
+    assert_eq!(partial.tag_match, Some(TagMatch::Exact("Circle")));
+}
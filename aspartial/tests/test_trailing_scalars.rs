@@ -0,0 +1,53 @@
+use ::aspartial::{AsPartial, Tristate};
+use ::aspartial::json::{IncrementalParser, TrailingScalars};
+use ::aspartial::path::FieldPath;
+
+#[allow(dead_code)]
+#[derive(AsPartial)]
+#[aspartial(name = PartialCounter)]
+#[aspartial(attrs( #[derive(PartialEq, Debug)] ))]
+struct Counter {
+    count: u32,
+    flag: bool,
+    label: Option<String>,
+}
+
+#[test]
+fn test_trailing_scalars_are_withheld(){
+    let parsed = aspartial::from_str_truncated::<Counter>(r#"{"count": 12"#).unwrap();
+    assert_eq!(parsed.count, None);
+
+    let parsed = aspartial::from_str_truncated::<Counter>(r#"{"count": 12, "flag": tr"#).unwrap();
+    assert_eq!(parsed, PartialCounter{ count: Some(12), flag: None, label: Tristate::Missing });
+
+    let parsed = aspartial::from_str_truncated::<Counter>(r#"{"count": 123 , "flag": true, "label": nul"#).unwrap();
+    assert_eq!(parsed, PartialCounter{ count: Some(123), flag: Some(true), label: Tristate::Missing });
+
+    let mut parser = IncrementalParser::new();
+    parser.push(br#"{"count": 1"#).unwrap();
+    assert_eq!(parser.pending_scalar(), Some("1"));
+    assert_eq!(parser.completion().streaming_leaf(), Some(&FieldPath::root().join("count")));
+    assert_eq!(parser.value().unwrap(), serde_json::json!({}));
+    parser.push(b"23").unwrap();
+    assert_eq!(parser.pending_scalar(), Some("123"));
+    parser.push(br#", "flag": fa"#).unwrap();
+    assert_eq!(parser.pending_scalar(), Some("fa"));
+    assert_eq!(parser.value().unwrap(), serde_json::json!({"count": 123}));
+    parser.push(b"lse").unwrap();
+    assert_eq!(parser.pending_scalar(), None);
+    assert_eq!(parser.value().unwrap(), serde_json::json!({"count": 123, "flag": false}));
+}
+
+#[test]
+fn test_include_trailing_numbers(){
+    let mut parser = IncrementalParser::new().with_trailing_scalars(TrailingScalars::Include);
+    parser.push(br#"{"count": 12"#).unwrap();
+    assert_eq!(parser.partial::<Counter>().unwrap().unwrap().count, Some(12));
+    parser.push(br#"3, "flag": t"#).unwrap();
+    assert_eq!(parser.partial::<Counter>().unwrap().unwrap(), PartialCounter{ count: Some(123), flag: None, label: Tristate::Missing });
+
+    // a number that isn't valid yet is left out either way
+    let mut parser = IncrementalParser::new().with_trailing_scalars(TrailingScalars::Include);
+    parser.push(br#"[1.5, 2."#).unwrap();
+    assert_eq!(parser.value().unwrap(), serde_json::json!([1.5]));
+}
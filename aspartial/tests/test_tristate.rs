@@ -0,0 +1,103 @@
+use ::aspartial::{ApplyPartial, AsPartial, Defaulted, Diff, Merge, Tristate};
+use ::aspartial::patch::{from_merge_patch, to_merge_patch};
+use serde_json::json;
+
+#[derive(AsPartial, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[aspartial(name = PartialProfile)]
+#[aspartial(apply)]
+#[aspartial(diff)]
+#[aspartial(attrs( #[derive(PartialEq, Debug)] ))]
+struct Profile {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nickname: Option<String>,
+    website: std::option::Option<String>,
+    #[serde(default = "_three")]
+    retries: u32,
+    #[serde(default)]
+    public: bool,
+}
+
+fn _three() -> u32 {
+    3
+}
+
+fn profile() -> Profile {
+    Profile {
+        name: "Ana".to_owned(),
+        nickname: Some("A".to_owned()),
+        website: Some("ana.pt".to_owned()),
+        retries: 5,
+        public: true,
+    }
+}
+
+#[test]
+fn test_missing_null_and_value_are_distinct(){
+    let partial: PartialProfile = serde_json::from_value(json!({"nickname": null, "website": "ana.pt"})).unwrap();
+    assert_eq!(partial.name, None);
+    assert_eq!(partial.nickname, Tristate::Null);
+    assert_eq!(partial.website, Tristate::Value("ana.pt".to_owned()));
+
+    let partial: PartialProfile = serde_json::from_value(json!({})).unwrap();
+    assert_eq!(partial.nickname, Tristate::Missing);
+    assert_eq!(partial.nickname.into_nested(), None);
+}
+
+#[test]
+fn test_defaults_remember_whether_they_were_supplied(){
+    let partial: PartialProfile = serde_json::from_value(json!({"public": false})).unwrap();
+    assert_eq!(partial.retries, Defaulted::Default(3));
+    assert_eq!(partial.public, Defaulted::Supplied(false));
+    assert_eq!(partial.retries, 3);
+    assert!(!*partial.public);
+}
+
+#[test]
+fn test_serialized_partials_skip_absent_fields(){
+    let partial: PartialProfile = serde_json::from_value(json!({"name": "Ana", "nickname": null})).unwrap();
+    let patch = to_merge_patch(&partial).unwrap();
+    assert_eq!(patch, json!({"name": "Ana", "nickname": null}));
+    assert_eq!(from_merge_patch::<Profile>(patch).unwrap(), partial);
+}
+
+#[test]
+fn test_apply_clears_on_null_and_ignores_defaults(){
+    let mut value = profile();
+    let patch: PartialProfile = serde_json::from_value(json!({"nickname": null})).unwrap();
+    value.apply_partial(patch).unwrap();
+    assert_eq!(value.nickname, None);
+    assert_eq!(value.website, Some("ana.pt".to_owned()));
+    assert_eq!(value.retries, 5);
+    assert!(value.public);
+
+    let patch: PartialProfile = serde_json::from_value(json!({"website": "ana.com", "retries": 1})).unwrap();
+    value.apply_partial(patch).unwrap();
+    assert_eq!(value.website, Some("ana.com".to_owned()));
+    assert_eq!(value.retries, 1);
+}
+
+#[test]
+fn test_merge_honours_null(){
+    let mut base: PartialProfile = serde_json::from_value(json!({"nickname": "A", "website": "ana.pt", "retries": 1})).unwrap();
+    let overlay: PartialProfile = serde_json::from_value(json!({"nickname": null})).unwrap();
+    base.merge(overlay);
+    assert_eq!(base.nickname, Tristate::Null);
+    assert_eq!(base.website, Tristate::Value("ana.pt".to_owned()));
+    assert_eq!(base.retries, Defaulted::Supplied(1));
+}
+
+#[test]
+fn test_diff_clears_with_null(){
+    let mut new = profile();
+    new.nickname = None;
+    let diff = profile().diff(new.clone()).unwrap();
+    assert_eq!(diff.nickname, Tristate::Null);
+    assert_eq!(diff.website, Tristate::Missing);
+    assert_eq!(diff.retries, Defaulted::Default(5));
+    assert_eq!(to_merge_patch(&diff).unwrap(), json!({"nickname": null}));
+
+    let mut old = profile();
+    old.apply_partial(diff).unwrap();
+    assert_eq!(old, new);
+}
@@ -0,0 +1,127 @@
+use ::aspartial::{ApplyPartial, AsPartial, Diff, FromPartial, Introspect, TagMatch};
+use serde_json::json;
+
+#[derive(AsPartial, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[aspartial(name = PartialStatus)]
+#[aspartial(apply)]
+#[aspartial(diff)]
+enum Status {
+    Pending,
+    Paused,
+    Done,
+}
+
+#[derive(AsPartial, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[aspartial(name = PartialShape)]
+#[aspartial(apply)]
+#[aspartial(diff)]
+enum Shape {
+    Empty,
+    Point(f64, f64),
+    Circle(f64),
+}
+
+#[derive(AsPartial, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[aspartial(name = PartialJob)]
+#[aspartial(apply)]
+#[aspartial(diff)]
+#[serde(tag = "state")]
+enum Job {
+    Queued,
+    Running { progress: u8 },
+}
+
+#[derive(AsPartial, Debug, Clone, PartialEq, serde_repr::Serialize_repr, serde_repr::Deserialize_repr)]
+#[aspartial(name = PartialLevel)]
+#[aspartial(apply)]
+#[aspartial(diff)]
+#[aspartial(serde_repr)]
+#[repr(u8)]
+enum Level {
+    Low = 1,
+    High = 5,
+}
+
+#[allow(dead_code)]
+#[derive(AsPartial, Debug, PartialEq, serde::Deserialize)]
+#[aspartial(name = PartialPair)]
+#[aspartial(apply)]
+#[aspartial(diff)]
+struct Pair(u32, u32);
+
+fn round_trip<T: ::aspartial::AsSerializablePartial>(value: serde_json::Value) -> serde_json::Value {
+    let partial: T::Partial = serde_json::from_value(value).unwrap();
+    serde_json::to_value(&partial).unwrap()
+}
+
+#[test]
+fn test_c_like_enum(){
+    let partial: PartialStatus = serde_json::from_value(json!("Paused")).unwrap();
+    assert_eq!(partial.paused, Some(()));
+    assert_eq!(partial.pending, None);
+    assert_eq!(Status::from_partial(partial).unwrap(), Status::Paused);
+
+    // a tag that is still arriving leaves every candidate open
+    let partial = aspartial::from_str_truncated::<Status>(r#""P"#).unwrap();
+    assert_eq!(partial.tag_match, Some(TagMatch::Prefix{ tag: "P".to_owned(), candidates: vec!["Pending", "Paused"] }));
+    assert!(Status::from_partial(partial).is_err());
+
+    assert_eq!(serde_json::to_value(Status::Done.to_partial()).unwrap(), json!("Done"));
+    assert_eq!(round_trip::<Status>(json!("P")), json!("P"));
+    assert!(Status::Done.diff(Status::Done).is_none());
+    assert_eq!(Status::Done.diff(Status::Pending).unwrap().pending, Some(()));
+}
+
+#[test]
+fn test_unit_variants_alongside_data(){
+    assert_eq!(round_trip::<Shape>(json!("Empty")), json!("Empty"));
+    assert_eq!(round_trip::<Shape>(json!({"Empty": null})), json!("Empty"));
+    assert_eq!(round_trip::<Shape>(json!({"Circle": 2.0})), json!({"Circle": 2.0}));
+
+    let mut shape = Shape::Circle(1.0);
+    shape.apply_partial(serde_json::from_value(json!("Empty")).unwrap()).unwrap();
+    assert_eq!(shape, Shape::Empty);
+
+    let partial: PartialJob = serde_json::from_value(json!({"state": "Queued"})).unwrap();
+    assert_eq!(partial.queued, Some(()));
+    assert_eq!(Job::from_partial(partial).unwrap(), Job::Queued);
+    assert_eq!(round_trip::<Job>(json!({"state": "Queued"})), json!({"state": "Queued"}));
+    assert_eq!(round_trip::<Job>(json!({"state": "Running", "progress": 3})), json!({"state": "Running", "progress": 3}));
+}
+
+#[test]
+fn test_tuple_variants_with_missing_trailing_elements(){
+    let partial: PartialShape = serde_json::from_value(json!({"Point": [1.5]})).unwrap();
+    let point = partial.point.as_ref().unwrap();
+    assert_eq!(point.0, Some(1.5));
+    assert_eq!(point.1, None);
+    assert_eq!(partial.missing_paths().iter().map(ToString::to_string).collect::<Vec<_>>(), vec!["Point[1]"]);
+    assert_eq!(serde_json::to_value(&partial).unwrap(), json!({"Point": [1.5]}));
+    assert_eq!(Shape::from_partial(partial).unwrap_err().to_string(), "could not complete partial: missing Point[1]");
+
+    let shape = Shape::Point(1.5, 2.5);
+    assert_eq!(serde_json::to_value(shape.clone().to_partial()).unwrap(), serde_json::to_value(&shape).unwrap());
+    let diff = shape.diff(Shape::Point(1.5, 3.0)).unwrap();
+    assert_eq!(diff.point.as_ref().unwrap().0, None);
+
+    let mut shape = Shape::Point(1.5, 2.5);
+    shape.apply_partial(serde_json::from_value(json!({"Point": [0.5]})).unwrap()).unwrap();
+    assert_eq!(shape, Shape::Point(0.5, 2.5));
+
+    let pair: PartialPair = serde_json::from_value(json!([1])).unwrap();
+    assert_eq!((pair.0, pair.1), (Some(1), None));
+}
+
+#[test]
+fn test_serde_repr_enum(){
+    let partial: PartialLevel = serde_json::from_value(json!(5)).unwrap();
+    assert_eq!(partial.high, Some(()));
+    assert_eq!(Level::from_partial(partial).unwrap(), Level::High);
+
+    let partial: PartialLevel = serde_json::from_value(json!(3)).unwrap();
+    assert!(partial.is_empty());
+    assert!(Level::from_partial(partial).is_err());
+
+    assert_eq!(serde_json::to_value(Level::Low.to_partial()).unwrap(), serde_json::to_value(Level::Low).unwrap());
+    assert_eq!(round_trip::<Level>(json!(5)), json!(5));
+}
@@ -0,0 +1,100 @@
+use ::aspartial::{FromPartial, Merge, TagMatch, UnknownTag};
+use ::aspartial::complete::CompletionProblem;
+use serde_json::json;
+
+#[allow(dead_code)]
+#[derive(::aspartial::AsPartial, Debug, PartialEq, serde::Deserialize)]
+#[aspartial(name = PartialEvent)]
+#[aspartial(complete)]
+#[serde(tag = "type")]
+enum Event {
+    Login { user: String },
+    Logout { user: String },
+}
+
+#[allow(dead_code)]
+#[derive(::aspartial::AsPartial, Debug, PartialEq, serde::Deserialize)]
+#[aspartial(name = PartialMessage)]
+#[aspartial(complete)]
+#[serde(tag = "t", content = "c")]
+enum Message {
+    Text(String),
+    #[serde(other)]
+    Unsupported,
+}
+
+#[allow(dead_code)]
+#[derive(::aspartial::AsPartial, Debug, PartialEq, serde::Deserialize)]
+#[aspartial(name = PartialCommand)]
+#[aspartial(complete)]
+enum Command {
+    Stop,
+    Walk(u32),
+}
+
+#[test]
+fn test_unknown_internal_tag_is_kept(){
+    let document = json!({"type": "Refresh", "token": "abc"});
+    let partial: PartialEvent = serde_json::from_value(document.clone()).unwrap();
+    assert_eq!(partial.unknown_tag, Some(UnknownTag{ tag: "Refresh".to_owned(), payload: Some(document.clone()) }));
+    assert_eq!(partial.tag_match, None);
+    assert_eq!(serde_json::to_value(&partial).unwrap(), document);
+
+    let err = Event::from_partial(partial).unwrap_err();
+    assert!(matches!(err.problems(), [CompletionProblem::UnknownVariant{ tag, .. }] if tag == "Refresh"));
+
+    // a tag that has ended is unknown, even if it is the beginning of a known one
+    let document = json!({"type": "Log", "user": "ana"});
+    let partial: PartialEvent = serde_json::from_value(document.clone()).unwrap();
+    assert_eq!(partial.unknown_tag, Some(UnknownTag{ tag: "Log".to_owned(), payload: Some(document) }));
+    assert_eq!(partial.tag_match, None);
+
+    // but one that may still be arriving isn't unknown yet
+    let partial = aspartial::from_str_truncated::<Event>(r#"{"user": "ana", "type": "Log"#).unwrap();
+    assert_eq!(partial.unknown_tag, None);
+    assert!(partial.tag_match.is_some_and(|tag_match| tag_match.is_open()));
+}
+
+#[test]
+fn test_serde_other(){
+    let document = json!({"t": "Image", "c": {"url": "a.png"}});
+    let partial: PartialMessage = serde_json::from_value(document.clone()).unwrap();
+    assert_eq!(partial.unsupported, Some(()));
+    assert_eq!(partial.tag_match, Some(TagMatch::Exact("Unsupported")));
+    assert_eq!(partial.unknown_tag.as_ref().unwrap().payload, Some(json!({"url": "a.png"})));
+    assert_eq!(Message::from_partial(partial).unwrap(), Message::Unsupported);
+    let document = json!({"t": "Image"});
+    assert_eq!(serde_json::from_value::<Message>(document.clone()).unwrap(), Message::Unsupported);
+    let partial: PartialMessage = serde_json::from_value(document).unwrap();
+    assert_eq!(partial.unknown_tag, Some(UnknownTag{ tag: "Image".to_owned(), payload: None }));
+    assert_eq!(Message::from_partial(partial).unwrap(), Message::Unsupported);
+
+    let partial: PartialMessage = serde_json::from_value(json!({"t": "Text", "c": "hi"})).unwrap();
+    assert_eq!(partial.unknown_tag, None);
+    assert_eq!(Message::from_partial(partial).unwrap(), Message::Text("hi".to_owned()));
+}
+
+#[test]
+fn test_unknown_external_tags(){
+    let partial: PartialCommand = serde_json::from_value(json!("Jump")).unwrap();
+    assert_eq!(partial.unknown_tag, Some(UnknownTag{ tag: "Jump".to_owned(), payload: None }));
+    assert_eq!(serde_json::to_value(&partial).unwrap(), json!("Jump"));
+
+    let partial: PartialCommand = serde_json::from_value(json!({"Turn": 90})).unwrap();
+    assert_eq!(partial.unknown_tag, Some(UnknownTag{ tag: "Turn".to_owned(), payload: Some(json!(90)) }));
+    assert_eq!(serde_json::to_value(&partial).unwrap(), json!({"Turn": 90}));
+}
+
+#[test]
+fn test_merging_unknown_tags(){
+    let mut base: PartialEvent = serde_json::from_value(json!({"type": "Login", "user": "ana"})).unwrap();
+    let overlay: PartialEvent = serde_json::from_value(json!({"type": "Refresh"})).unwrap();
+    base.merge(overlay);
+    assert!(base.login.is_none());
+    assert_eq!(base.unknown_tag.as_ref().unwrap().tag, "Refresh");
+
+    let overlay: PartialEvent = serde_json::from_value(json!({"type": "Logout", "user": "ana"})).unwrap();
+    base.merge(overlay);
+    assert_eq!(base.unknown_tag, None);
+    assert!(base.logout.is_some());
+}
@@ -0,0 +1,110 @@
+use ::aspartial::{AsPartial, FromPartial, TagMatch};
+use serde_json::json;
+
+#[derive(AsPartial, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[aspartial(name = PartialEvent)]
+#[aspartial(complete)]
+#[serde(tag = "type", rename_all(serialize = "snake_case", deserialize = "SCREAMING_SNAKE_CASE"))]
+enum Event {
+    #[serde(alias = "signin", alias = "sign_in")]
+    LogIn { user: String },
+    #[serde(rename(deserialize = "bye"))]
+    LogOut { user: String },
+}
+
+#[derive(AsPartial, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[aspartial(name = PartialAmount)]
+#[aspartial(complete)]
+#[serde(tag = "kind")]
+enum Amount {
+    Exact { value: f64 },
+    #[serde(untagged)]
+    Text(String),
+}
+
+#[derive(AsPartial, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[aspartial(name = PartialCommand)]
+#[aspartial(complete)]
+enum Command {
+    Stop,
+    Walk(u32),
+    #[serde(untagged)]
+    Say(String),
+}
+
+#[derive(AsPartial, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[aspartial(name = PartialReply)]
+#[aspartial(complete)]
+#[aspartial(enum_repr)]
+#[serde(tag = "t", content = "c")]
+enum Reply {
+    #[serde(alias = "txt")]
+    Text(String),
+    #[serde(untagged)]
+    Code(u32),
+}
+
+/// The partial agrees with serde on which value a complete document holds
+fn assert_completes_like_serde<T>(document: serde_json::Value)
+where
+    T: FromPartial + serde::de::DeserializeOwned + std::fmt::Debug + PartialEq,
+    T::Partial: serde::de::DeserializeOwned,
+{
+    let expected: T = serde_json::from_value(document.clone()).unwrap();
+    let partial: T::Partial = serde_json::from_value(document).unwrap();
+    assert_eq!(T::from_partial(partial).unwrap(), expected);
+}
+
+#[test]
+fn test_deserialize_names_and_aliases(){
+    assert_completes_like_serde::<Event>(json!({"type": "LOG_IN", "user": "ana"}));
+    assert_completes_like_serde::<Event>(json!({"type": "signin", "user": "ana"}));
+    assert_completes_like_serde::<Event>(json!({"type": "sign_in", "user": "ana"}));
+    assert_completes_like_serde::<Event>(json!({"type": "bye", "user": "ana"}));
+
+    // the partial refers to variants by the tag they are serialized with
+    let partial: PartialEvent = serde_json::from_value(json!({"type": "bye", "user": "ana"})).unwrap();
+    assert_eq!(partial.tag_match, Some(TagMatch::Exact("log_out")));
+    assert_eq!(PartialEvent::VARIANT_TAGS, &["log_in", "log_out"]);
+    let event = Event::LogOut{ user: "ana".to_owned() };
+    assert_eq!(serde_json::to_value(event.clone().to_partial()).unwrap(), serde_json::to_value(&event).unwrap());
+
+    // the serialized tags are not accepted, just like serde doesn't accept them
+    let partial: PartialEvent = serde_json::from_value(json!({"type": "log_out", "user": "ana"})).unwrap();
+    assert!(partial.log_out.is_none());
+    assert!(serde_json::from_value::<Event>(json!({"type": "log_out", "user": "ana"})).is_err());
+
+    // tags still arriving are matched against the deserialize names and aliases
+    let partial = aspartial::from_str_truncated::<Event>(r#"{"type": "LOG_"#).unwrap();
+    assert_eq!(partial.tag_match, Some(TagMatch::Prefix{ tag: "LOG_".to_owned(), candidates: vec!["log_in"] }));
+    let partial = aspartial::from_str_truncated::<Event>(r#"{"type": "b"#).unwrap();
+    assert_eq!(partial.tag_match, Some(TagMatch::Prefix{ tag: "b".to_owned(), candidates: vec!["log_out"] }));
+}
+
+#[test]
+fn test_untagged_variants_in_tagged_enums(){
+    assert_completes_like_serde::<Amount>(json!({"kind": "Exact", "value": 2.5}));
+    assert_completes_like_serde::<Amount>(json!("about three"));
+    assert_completes_like_serde::<Command>(json!("Stop"));
+    assert_completes_like_serde::<Command>(json!({"Walk": 3}));
+    assert_completes_like_serde::<Command>(json!("hello"));
+
+    let partial: PartialAmount = serde_json::from_value(json!({"kind": "Exact"})).unwrap();
+    assert!(partial.exact.is_some());
+    assert!(partial.text.is_none());
+
+    let amount = Amount::Text("about three".to_owned());
+    assert_eq!(serde_json::to_value(amount.clone().to_partial()).unwrap(), serde_json::to_value(&amount).unwrap());
+    let command = Command::Say("hello".to_owned());
+    assert_eq!(serde_json::to_value(command.clone().to_partial()).unwrap(), json!("hello"));
+}
+
+#[test]
+fn test_enum_shaped_partials_with_aliases_and_untagged_variants(){
+    assert_completes_like_serde::<Reply>(json!({"t": "txt", "c": "hi"}));
+    assert_completes_like_serde::<Reply>(json!(200));
+    assert!(matches!(serde_json::from_value(json!({"t": "Text", "c": "hi"})).unwrap(), PartialReply::Text(text) if text == "hi"));
+    assert!(matches!(serde_json::from_value(json!(200)).unwrap(), PartialReply::Code(200)));
+    assert!(matches!(serde_json::from_value(json!({"t": "Te"})).unwrap(), PartialReply::Unknown));
+    assert_eq!(serde_json::to_value(Reply::Code(200).to_partial()).unwrap(), json!(200));
+}
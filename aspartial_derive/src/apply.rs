@@ -0,0 +1,209 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote_spanned, spanned::Spanned};
+
+use crate::from_partial::{field_segment, tag_allows, variant_segment, where_clause_for_from_partial};
+use crate::serde_attributes::{SerdeEnumTagParams, SerdeOuterRenameParams};
+use crate::syn_extensions::{IEnumExt, IFieldExt, IVariantExt, PartialFieldKind};
+use crate::util::{destructure, field_member};
+
+/// Adds `FromPartial` and `ApplyPartial` bounds for the type of every field to a where
+/// clause from `where_clause_for_partial`
+fn where_clause_for_apply<'field>(
+    wc: syn::WhereClause,
+    fields: impl IntoIterator<Item=&'field syn::Field>,
+) -> syn::WhereClause {
+    let fields: Vec<_> = fields.into_iter().collect();
+    let mut wc = where_clause_for_from_partial(wc, fields.iter().copied());
+    for field in fields {
+        let span = field.ty.span();
+        let field_ty = &field.ty;
+        wc.predicates.push(parse_quote_spanned!{span=>
+            #field_ty : ::aspartial::ApplyPartial
+        });
+    }
+    wc
+}
+
+/// Statements applying the partial struct in `partial` to the fields at `places`,
+/// recording any problems in `errors`
+fn apply_fields(
+    fields: &syn::Fields,
+    outer_rename: Option<&SerdeOuterRenameParams>,
+    places: impl IntoIterator<Item=TokenStream>,
+    partial: TokenStream,
+) -> Vec<TokenStream> {
+    let field_count = fields.len();
+    fields.iter().zip(places).enumerate().map(|(field_idx, (field, place))| {
+        let member = field_member(field, field_idx);
+        let segment = field_segment(field, field_idx, field_count, outer_rename);
+        match field.partial_kind() {
+            PartialFieldKind::Optional => quote!(errors.apply_field(#segment, &mut #place, #partial.#member);),
+            // an explicit null clears the field
+            PartialFieldKind::Tristate(_) => quote!(
+                errors.apply_field(#segment, &mut #place, #partial.#member.into_nested());
+            ),
+            // defaults didn't come from the patch, so they don't overwrite anything
+            PartialFieldKind::Defaulted => quote!(
+                errors.apply_field(#segment, &mut #place, #partial.#member.supplied());
+            ),
+        }
+    }).collect()
+}
+
+pub fn make_apply_struct(input: &syn::ItemStruct, where_clause: syn::WhereClause) -> TokenStream {
+    let where_clause = where_clause_for_apply(where_clause, input.fields.iter());
+    let outer_rename = input.attrs.iter().find_map(SerdeOuterRenameParams::try_from_attr);
+    let places = input.fields.iter().enumerate().map(|(field_idx, field)| {
+        let member = field_member(field, field_idx);
+        quote!(self.#member)
+    });
+    let applications = apply_fields(&input.fields, outer_rename.as_ref(), places, quote!(partial));
+
+    let struct_name = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    quote!{
+        impl #impl_generics ::aspartial::ApplyPartial for #struct_name #ty_generics
+            #where_clause
+        {
+            #[allow(unused_mut, unused_variables)]
+            fn apply_partial(&mut self, partial: Self::Partial) -> Result<(), ::aspartial::CompletionError> {
+                let mut errors = ::aspartial::CompletionError::default();
+                #(#applications)*
+                if errors.is_empty() { Ok(()) } else { Err(errors) }
+            }
+        }
+    }
+}
+
+pub fn make_apply_newtype(input: &syn::ItemStruct, field: &syn::Field, where_clause: syn::WhereClause) -> TokenStream {
+    let where_clause = where_clause_for_apply(where_clause, [field]);
+    let member = field_member(field, 0);
+    let struct_name = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    quote!{
+        impl #impl_generics ::aspartial::ApplyPartial for #struct_name #ty_generics
+            #where_clause
+        {
+            fn apply_partial(&mut self, partial: Self::Partial) -> Result<(), ::aspartial::CompletionError> {
+                ::aspartial::ApplyPartial::apply_partial(&mut self.#member, partial)
+            }
+        }
+    }
+}
+
+/// The current variant is patched if the partial populates it (and the tag
+/// allows it), otherwise the variant that the partial populates replaces it
+pub fn make_apply_enum(
+    input: &syn::ItemEnum,
+    enum_tag_style: &SerdeEnumTagParams,
+    where_clause: syn::WhereClause,
+) -> TokenStream {
+    let where_clause = where_clause_for_apply(where_clause, input.variants.iter().flat_map(|v| v.fields()));
+
+    let mut partial_field_names = Vec::<syn::Ident>::new();
+    let mut patch_arms = Vec::<TokenStream>::new();
+    for (tag, variant) in input.tagged_variants() {
+        let variant_ident = &variant.ident;
+        let partial_field_name = variant.partial_field_name();
+        let segment = variant_segment(enum_tag_style, variant, &tag);
+        let (pattern, application) = match variant.as_struct(input) {
+            Some(variant_struct) => {
+                let outer_rename = variant_struct.attrs.iter().find_map(SerdeOuterRenameParams::try_from_attr);
+                let (pattern, bindings) = destructure(&variant.fields, "field");
+                let places = bindings.iter().map(|binding| quote!(*#binding));
+                let applications = apply_fields(&variant.fields, outer_rename.as_ref(), places, quote!(variant_partial));
+                (pattern, quote!{
+                    if let Some(variant_partial) = partial.#partial_field_name {
+                        errors.nested(#segment, |errors| { #(#applications)* });
+                    }
+                })
+            },
+            // a unit variant has nothing to patch
+            None if matches!(variant.fields, syn::Fields::Unit) => (quote!(), quote!()),
+            None => (quote!((value)), quote!(errors.apply_field(#segment, value, partial.#partial_field_name);)),
+        };
+        let allowed = tag_allows(quote!(partial.tag_match.as_ref()), variant, &tag);
+        patch_arms.push(quote!{
+            Self::#variant_ident #pattern if partial.#partial_field_name.is_some() && #allowed =>
+            {
+                #application
+            },
+        });
+        partial_field_names.push(partial_field_name);
+    }
+
+    let enum_ident = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    quote!{
+        impl #impl_generics ::aspartial::ApplyPartial for #enum_ident #ty_generics
+            #where_clause
+        {
+            fn apply_partial(&mut self, partial: Self::Partial) -> Result<(), ::aspartial::CompletionError> {
+                if partial.tag_match.is_none() && partial.unknown_tag.is_none() #(&& partial.#partial_field_names.is_none())* {
+                    return Ok(())
+                }
+                let mut errors = ::aspartial::CompletionError::default();
+                match self {
+                    #(#patch_arms)*
+                    _ => *self = <Self as ::aspartial::FromPartial>::from_partial(partial)?,
+                }
+                if errors.is_empty() { Ok(()) } else { Err(errors) }
+            }
+        }
+    }
+}
+
+/// Like `make_apply_enum`, for enum-shaped partials (see `aspartial(enum_repr)`).
+/// An `Unknown` partial leaves the value as it is.
+pub fn make_apply_enum_repr(
+    input: &syn::ItemEnum,
+    partial: &syn::Ident,
+    enum_tag_style: &SerdeEnumTagParams,
+    where_clause: syn::WhereClause,
+) -> TokenStream {
+    let where_clause = where_clause_for_apply(where_clause, input.variants.iter().flat_map(|v| v.fields()));
+
+    let patch_arms = input.tagged_variants().map(|(tag, variant)| {
+        let variant_ident = &variant.ident;
+        let segment = variant_segment(enum_tag_style, variant, &tag);
+        match variant.as_struct(input) {
+            Some(variant_struct) => {
+                let outer_rename = variant_struct.attrs.iter().find_map(SerdeOuterRenameParams::try_from_attr);
+                let (pattern, bindings) = destructure(&variant.fields, "field");
+                let places = bindings.iter().map(|binding| quote!(*#binding));
+                let applications = apply_fields(&variant.fields, outer_rename.as_ref(), places, quote!(variant_partial));
+                quote!{
+                    (Self::#variant_ident #pattern, #partial::#variant_ident(variant_partial)) => {
+                        errors.nested(#segment, |errors| { #(#applications)* });
+                    },
+                }
+            },
+            None if matches!(variant.fields, syn::Fields::Unit) => quote!((Self::#variant_ident, #partial::#variant_ident) => (),),
+            None => quote!{
+                (Self::#variant_ident(value), #partial::#variant_ident(variant_partial)) => {
+                    errors.apply_value(#segment, value, variant_partial);
+                },
+            },
+        }
+    });
+
+    let enum_ident = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    quote!{
+        impl #impl_generics ::aspartial::ApplyPartial for #enum_ident #ty_generics
+            #where_clause
+        {
+            fn apply_partial(&mut self, partial: Self::Partial) -> Result<(), ::aspartial::CompletionError> {
+                let mut errors = ::aspartial::CompletionError::default();
+                match (&mut *self, partial) {
+                    #(#patch_arms)*
+                    (_, #partial::Unknown) => (),
+                    #[allow(unreachable_patterns)]
+                    (_, partial) => *self = <Self as ::aspartial::FromPartial>::from_partial(partial)?,
+                }
+                if errors.is_empty() { Ok(()) } else { Err(errors) }
+            }
+        }
+    }
+}
@@ -3,8 +3,20 @@ use quote::{format_ident, quote, ToTokens};
 use syn::{parse_quote, parse_quote_spanned, spanned::Spanned};
 use proc_macro::TokenStream;
 
-use crate::derive_config::{ConfigsForAsPartial, ModeConfig};
-use crate::syn_extensions::{IAttrExt, IEnumExt, IFieldExt, IVariantExt};
+use crate::apply::{make_apply_enum, make_apply_enum_repr, make_apply_newtype, make_apply_struct};
+use crate::derive_config::{ConfigsForAsPartial, EnumReprConfig, ModeConfig, OptInTraits};
+use crate::diff::{make_diff_enum, make_diff_newtype, make_diff_struct};
+use crate::flatten::{make_flatten_default, make_flatten_enum};
+use crate::from_partial::{
+    make_from_partial_enum, make_from_partial_enum_repr, make_from_partial_newtype, make_from_partial_struct
+};
+use crate::introspect::{make_introspect_enum, make_introspect_enum_repr, make_introspect_struct};
+use crate::merge::{make_merge_enum, make_merge_enum_repr, make_merge_struct};
+use crate::resolve::make_resolve_enum;
+use crate::from_partial::tag_allows;
+use crate::serialize::{make_serialize_enum, make_serialize_enum_repr, make_serialize_repr_enum};
+use crate::util::{destructure, field_member};
+use crate::syn_extensions::{option_inner_type, IAttrExt, IEnumExt, IFieldExt, IVariantExt, PartialFieldKind};
 use crate::serde_attributes::SerdeEnumTagParams;
 
 fn where_clause_for_partial<'field>(
@@ -25,6 +37,18 @@ fn where_clause_for_partial<'field>(
             #field_ty : ::aspartial::AsPartial<Partial: ::serde::de::DeserializeOwned>
         });
         wc.predicates.push_punct(comma);
+        if let PartialFieldKind::Tristate(inner) = field.partial_kind() {
+            wc.predicates.push_value(parse_quote_spanned!{span=>
+                #inner : ::aspartial::AsPartial<Partial: ::serde::de::DeserializeOwned>
+            });
+            wc.predicates.push_punct(comma);
+        }
+        if field.is_serde_flatten() && matches!(field.partial_kind(), PartialFieldKind::Optional) {
+            wc.predicates.push_value(parse_quote_spanned!{span=>
+                <#field_ty as ::aspartial::AsPartial>::Partial : ::aspartial::flatten::Flatten
+            });
+            wc.predicates.push_punct(comma);
+        }
         if field.attrs.iter().any(|attr| attr.is_serde_regular_default()) {
             let default_pred: syn::WherePredicate = parse_quote!(#field_ty: std::default::Default);
             wc.predicates.push_value(default_pred);
@@ -34,8 +58,444 @@ fn where_clause_for_partial<'field>(
     wc
 }
 
+/// Partials can always be deserialized (see `where_clause_for_partial`), but only
+/// serialized if the partials of all of their fields can. The bounds are higher-ranked
+/// so that those on hand-written partials without `Serialize` don't fail to compile,
+/// and only keep the partial from being serialized.
+fn serde_bounds(partial_fields: &syn::Fields) -> syn::Attribute {
+    let serialize_bounds = partial_fields.iter()
+        .map(|field| {
+            // flattened fields are serialized through `::aspartial::flatten`, which needs the partial itself
+            let field_ty = match option_inner_type(&field.ty) {
+                Some(inner) if field.is_serde_flatten() => inner,
+                _ => &field.ty,
+            };
+            quote!(for<'__partial> #field_ty: ::serde::Serialize).to_string()
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let serialize_bounds = syn::LitStr::new(&serialize_bounds, Span::call_site());
+    parse_quote!( #[serde(bound(serialize = #serialize_bounds, deserialize = ""))] )
+}
+
+/// The partial version of a struct, or of the fields of a struct-like variant.
+/// Functions producing the partials of serde defaults are added to `default_functions`.
+fn make_partial_struct_item(
+    input: &syn::ItemStruct,
+    partial_ident: syn::Ident,
+    attrs: Vec<syn::Attribute>,
+    where_clause: &syn::WhereClause,
+    default_functions: &mut Vec<syn::ItemFn>,
+) -> syn::ItemStruct {
+    let mut partial_struct = input.clone();
+    partial_struct.ident = partial_ident;
+    partial_struct.attrs = attrs;
+    // `Serialize` may already be derived through `aspartial(attrs(...))`
+    let derive_serialize = match partial_struct.attrs.iter().any(|attr| attr.derives("Serialize")) {
+        true => quote!(),
+        false => quote!(::serde::Serialize,),
+    };
+    partial_struct.attrs.push( parse_quote!( #[derive(#derive_serialize ::serde::Deserialize)] ));
+    // keep the field names of the original
+    partial_struct.attrs.extend(
+        input.attrs.iter()
+            .filter(|attr| attr.is_serde_attr())
+            .filter_map(|attr| attr.retain_serde_args(|meta| {
+                ["rename", "rename_all", "deny_unknown_fields"].iter().any(|arg| meta.path().is_ident(arg))
+            }))
+    );
+    partial_struct.generics.where_clause = Some(where_clause.clone());
+
+    for (field_idx, field) in partial_struct.fields.iter_mut().enumerate() {
+        let original_field = field.clone();
+        let original_ty = &original_field.ty;
+        field.vis = parse_quote!(pub);
+        field.ty = field.partial_type();
+        let mut fixed_attrs = Vec::<syn::Attribute>::new();
+        for attr in &field.attrs {
+            if !attr.is_serde_attr(){
+                continue;
+            }
+            let default_value = if attr.is_serde_regular_default() {
+                quote!(<#original_ty as ::std::default::Default>::default())
+            } else if let Some(default_path) = attr.as_serde_default_func_path() {
+                quote!(#default_path())
+            } else {
+                fixed_attrs.extend(attr.without_serde_skip_serializing_if());
+                continue;
+            };
+            let default_func_name = {
+                let field_ident = field.ident.as_ref().map(|ident| ident.to_string()).unwrap_or(field_idx.to_string());
+                format_ident!("__default_for__{}__{}", partial_struct.ident, field_ident)
+            };
+            default_functions.push({
+                let field_ty = &field.ty;
+                parse_quote!{
+                    #[allow(non_snake_case)]
+                    fn #default_func_name() -> #field_ty {
+                        ::aspartial::Defaulted::Default(::aspartial::AsPartial::to_partial(#default_value))
+                    }
+                }
+            });
+            fixed_attrs.push({
+                let serde_default_arg = syn::LitStr::new(&default_func_name.to_string(), field.span());
+                parse_quote!(
+                    #[serde(default=#serde_default_arg)]
+                )
+            });
+        }
+        // absent fields are left out when serializing the partial
+        fixed_attrs.push(match original_field.partial_kind() {
+            // tuples are arrays, whose trailing elements may not have arrived yet
+            PartialFieldKind::Optional if original_field.ident.is_none() => parse_quote!(
+                #[serde(default, skip_serializing_if = "Option::is_none")]
+            ),
+            // read and written like serde would the original, see `::aspartial::flatten`
+            PartialFieldKind::Optional if original_field.is_serde_flatten() => parse_quote!(
+                #[serde(skip_serializing_if = "Option::is_none", with = "::aspartial::flatten")]
+            ),
+            PartialFieldKind::Optional => parse_quote!(
+                #[serde(skip_serializing_if = "Option::is_none")]
+            ),
+            PartialFieldKind::Tristate(_) => parse_quote!(
+                #[serde(default, skip_serializing_if = "::aspartial::Tristate::is_missing")]
+            ),
+            PartialFieldKind::Defaulted => parse_quote!(
+                #[serde(skip_serializing_if = "::aspartial::Defaulted::is_default")]
+            ),
+        });
+        field.attrs = fixed_attrs;
+    }
+    let serde_bounds = serde_bounds(&partial_struct.fields);
+    partial_struct.attrs.push(serde_bounds);
+    partial_struct
+}
+
+/// Initializers for the fields of a partial struct, from the values of the original fields
+fn partial_field_inits(
+    fields: &syn::Fields, values: impl IntoIterator<Item=proc_macro2::TokenStream>,
+) -> Vec<proc_macro2::TokenStream> {
+    fields.iter()
+        .zip(values)
+        .enumerate()
+        .map(|(field_idx, (field, value))| {
+            let member = field_member(field, field_idx);
+            let partial_value = quote!(::aspartial::AsPartial::to_partial(#value));
+            match field.partial_kind() {
+                PartialFieldKind::Optional => quote!(#member: Some(#partial_value)),
+                PartialFieldKind::Tristate(_) => quote!(#member: ::aspartial::Tristate::from(#partial_value)),
+                PartialFieldKind::Defaulted => quote!(#member: ::aspartial::Defaulted::Supplied(#partial_value)),
+            }
+        })
+        .collect()
+}
+
+/// The partial struct of a struct-like variant, used as the type of the variant's
+/// field in the partial enum. `None` for other kinds of variants.
+fn make_variant_partial_struct(
+    input: &syn::ItemEnum, variant: &syn::Variant, enum_partial: &syn::Ident, attrs: &[syn::Attribute],
+) -> Option<proc_macro2::TokenStream> {
+    let variant_struct = variant.as_struct(input)?;
+    let where_clause = where_clause_for_partial(variant_struct.generics.where_clause.clone(), variant_struct.fields.iter());
+    let mut default_functions = Vec::<syn::ItemFn>::new();
+    let partial_struct = make_partial_struct_item(
+        &variant_struct, variant.partial_struct_ident(enum_partial), attrs.to_vec(), &where_clause, &mut default_functions
+    );
+    let partial_struct_name = &partial_struct.ident;
+    let impl__Merge = make_merge_struct(&partial_struct, where_clause.clone());
+    let impl__Introspect = make_introspect_struct(&variant_struct, partial_struct_name, where_clause.clone());
+    let (impl_generics, ty_generics, _) = variant_struct.generics.split_for_impl();
+    Some(quote!{
+        impl #impl_generics ::aspartial::AsPartial for #partial_struct_name #ty_generics
+            #where_clause
+        {
+            type Partial = Self;
+            fn to_partial(self) -> Self::Partial {
+                self
+            }
+        }
+
+        #partial_struct
+
+        #(#default_functions)*
+
+        #impl__Merge
+
+        #impl__Introspect
+    })
+}
+
+/// The `ACCEPTED_TAGS` of a partial enum, for `TagMatch::find_accepted`
+fn accepted_tags_const(input: &syn::ItemEnum) -> proc_macro2::TokenStream {
+    let (accepted, variant_tags): (Vec<syn::LitStr>, Vec<syn::LitStr>) = input.accepted_tags().into_iter().unzip();
+    quote!{
+        /// Every tag that the variants are deserialized from (their names and aliases), each
+        /// with the serialized tag of its variant. `#[serde(untagged)]` variants have none.
+        pub const ACCEPTED_TAGS: &'static [(&'static str, &'static str)] = &[#((#accepted, #variant_tags)),*];
+    }
+}
+
+/// The tag of the `#[serde(other)]` variant, if there is one
+fn serde_other_tag(input: &syn::ItemEnum, enum_tag_style: &SerdeEnumTagParams) -> syn::Result<Option<syn::LitStr>> {
+    let mut other_variants = input.tagged_variants().filter(|(_, variant)| variant.is_serde_other());
+    other_variants.next().map(|(tag, variant)| {
+        if let Some((_, extra)) = other_variants.next() {
+            return Err(syn::Error::new(extra.span(), "only one variant can be #[serde(other)]"))
+        }
+        if !matches!(variant.fields, syn::Fields::Unit) {
+            return Err(syn::Error::new(variant.span(), "#[serde(other)] must be on a unit variant"))
+        }
+        if !matches!(enum_tag_style, SerdeEnumTagParams::InternallyTagged{..} | SerdeEnumTagParams::AdjacentlyTagged{..}) {
+            return Err(syn::Error::new(variant.span(), "#[serde(other)] is only allowed in internally or adjacently tagged enums"))
+        }
+        Ok(tag)
+    }).transpose()
+}
+
+/// The partial payload of a variant, from the `bindings` of its destructured fields
+fn variant_payload_to_partial(
+    variant: &syn::Variant, enum_partial: &syn::Ident, bindings: &[syn::Ident],
+) -> proc_macro2::TokenStream {
+    match &variant.fields {
+        syn::Fields::Unit => quote!(()),
+        syn::Fields::Unnamed(syn::FieldsUnnamed{unnamed, ..}) if unnamed.len() == 1 => {
+            quote!(::aspartial::AsPartial::to_partial(#(#bindings)*))
+        },
+        _ => {
+            let variant_partial = variant.partial_struct_ident(enum_partial);
+            let field_inits = partial_field_inits(&variant.fields, bindings.iter().map(ToTokens::to_token_stream));
+            quote!(#variant_partial{ #(#field_inits),* })
+        },
+    }
+}
+
+/// The partial of a tagged enum as an enum with the same variants, holding the partials of
+/// their contents, plus an `Unknown` variant for as long as the tag doesn't name a variant
+fn make_partial_enum_repr(
+    input: &syn::ItemEnum,
+    conf: &EnumReprConfig,
+    partial_type_ident: &syn::Ident,
+    attrs: &[syn::Attribute],
+    enum_tag_style: &SerdeEnumTagParams,
+    traits: OptInTraits,
+) -> syn::Result<TokenStream> {
+    if matches!(enum_tag_style, SerdeEnumTagParams::Untagged) {
+        return Err(syn::Error::new(conf.enum_repr_keyword.span(), "aspartial(enum_repr): only tagged enums are supported"))
+    }
+    if let Some(clashing) = input.variants.iter().find(|variant| variant.ident == "Unknown") {
+        return Err(syn::Error::new(clashing.span(), "variant name clashes with the 'Unknown' variant of the partial enum"))
+    }
+    let other_tag = serde_other_tag(input, enum_tag_style)?;
+    let variant_tags: Vec<syn::LitStr> = input.tagged_variants().map(|(tag, _)| tag).collect();
+
+    let fields = input.variants.iter().flat_map(|v| v.fields());
+    let where_clause = where_clause_for_partial(input.generics.where_clause.clone(), fields);
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let enum_ident = &input.ident;
+    // the partials of struct-like variants are only as visible as the enum
+    let vis = &input.vis;
+
+    let partial_variants = input.variants.iter().map(|variant| {
+        let variant_ident = &variant.ident;
+        match variant.fields {
+            syn::Fields::Unit => quote!(#variant_ident),
+            _ => {
+                let payload_ty = variant.partial_payload_type(input, partial_type_ident);
+                quote!(#variant_ident(#payload_ty))
+            },
+        }
+    });
+    let variant_partial_structs: Vec<_> = input.variants.iter()
+        .filter_map(|variant| make_variant_partial_struct(input, variant, partial_type_ident, attrs))
+        .collect();
+
+    // the tag, matched against the variants, and the payload that came with it, along
+    // with where the payload sits inside of the value
+    let find_tag = match enum_tag_style {
+        SerdeEnumTagParams::InternallyTagged { tag_key } => quote!{
+            value.get(#tag_key)
+                .and_then(::serde_json::Value::as_str)
+                .map(|tag| {
+                    let is_open = ::aspartial::deserialize::is_streaming(Some(#tag_key));
+                    (::aspartial::TagMatch::find_accepted(tag, is_open, Self::ACCEPTED_TAGS), Some((None, &value)))
+                })
+        },
+        SerdeEnumTagParams::AdjacentlyTagged { tag_key, content_key } => quote!{
+            value.get(#tag_key)
+                .and_then(::serde_json::Value::as_str)
+                .map(|tag| {
+                    let is_open = ::aspartial::deserialize::is_streaming(Some(#tag_key));
+                    let payload = match value.get(#content_key) {
+                        Some(content) => (Some(#content_key), content),
+                        None => (None, &value),
+                    };
+                    (::aspartial::TagMatch::find_accepted(tag, is_open, Self::ACCEPTED_TAGS), Some(payload))
+                })
+        },
+        // unit variants are sent as just their tag
+        _ => quote!(match &value {
+            ::serde_json::Value::String(tag) => {
+                let is_open = ::aspartial::deserialize::is_streaming(None);
+                Some((::aspartial::TagMatch::find_accepted(tag, is_open, Self::ACCEPTED_TAGS), None))
+            },
+            _ => {
+                // a key without a value yet may be the tag, still arriving
+                let pending_key = ::aspartial::deserialize::pending_key();
+                let pending_key = pending_key.as_ref().map(|(key, is_open)| (key.as_str(), *is_open));
+                ::aspartial::TagMatch::find_key_accepted(&value, pending_key, Self::ACCEPTED_TAGS)
+                    .map(|(tag_match, entry)| (Some(tag_match), entry.map(|(key, payload)| (Some(key), payload))))
+            },
+        }),
+    };
+    // `#[serde(untagged)]` variants are tried in order on the whole payload
+    let untagged_attempts = input.variants.iter()
+        .filter(|variant| variant.is_serde_untagged())
+        .map(|variant| {
+            let variant_ident = &variant.ident;
+            match variant.fields {
+                syn::Fields::Unit => quote!{
+                    if ::aspartial::deserialize::from_nested_value::<()>(&value, None).is_ok() {
+                        return Ok(Self::#variant_ident)
+                    }
+                },
+                _ => quote!{
+                    if let Ok(variant_partial) = ::aspartial::deserialize::from_nested_value(&value, None) {
+                        return Ok(Self::#variant_ident(variant_partial))
+                    }
+                },
+            }
+        });
+    let untagged_or_unknown = quote!{{
+        #(#untagged_attempts)*
+        return Ok(Self::Unknown)
+    }};
+    let unknown_tag = match &other_tag {
+        Some(other_tag) => quote!(#other_tag),
+        None => untagged_or_unknown.clone(),
+    };
+    let partials_from_payload = input.tagged_variants()
+        .filter(|(_, variant)| !variant.is_serde_untagged())
+        .map(|(tag, variant)| {
+            let variant_ident = &variant.ident;
+            match variant.fields {
+                syn::Fields::Unit => quote!(#tag => return Ok(Self::#variant_ident),),
+                _ => quote!{
+                    #tag => if let Some(Ok(variant_partial)) = payload.map(|(segment, payload)| ::aspartial::deserialize::from_nested_value(payload, segment)) {
+                        return Ok(Self::#variant_ident(variant_partial))
+                    },
+                },
+            }
+        });
+    let accepted_tags_const = accepted_tags_const(input);
+
+    let to_partial_arms = input.variants.iter().map(|variant| {
+        let variant_ident = &variant.ident;
+        let (pattern, bindings) = destructure(&variant.fields, "field");
+        let payload = variant_payload_to_partial(variant, partial_type_ident, &bindings);
+        match variant.fields {
+            syn::Fields::Unit => quote!(Self::#variant_ident => #partial_type_ident::#variant_ident,),
+            _ => quote!(Self::#variant_ident #pattern => #partial_type_ident::#variant_ident(#payload),),
+        }
+    });
+
+    let impl__FromPartial = traits.complete.then(|| {
+        make_from_partial_enum_repr(input, partial_type_ident, enum_tag_style, where_clause.clone())
+    });
+    let impl__ApplyPartial = traits.apply.then(|| {
+        make_apply_enum_repr(input, partial_type_ident, enum_tag_style, where_clause.clone())
+    });
+    let impl__Diff = traits.diff.then(|| make_diff_enum(input, partial_type_ident, |variant, _| {
+        let variant_ident = &variant.ident;
+        quote!(#partial_type_ident::#variant_ident(variant_partial))
+    }, where_clause.clone()));
+    let impl__Merge = make_merge_enum_repr(input, partial_type_ident, where_clause.clone());
+    let impl__Introspect = make_introspect_enum_repr(input, partial_type_ident, enum_tag_style, where_clause.clone());
+    let impl__Serialize = make_serialize_enum_repr(input, partial_type_ident, enum_tag_style, where_clause.clone());
+    let impl__Flatten = make_flatten_enum(input, partial_type_ident, enum_tag_style, where_clause.clone());
+
+    let expanded = quote!{
+        impl #impl_generics ::aspartial::AsPartial for #enum_ident #ty_generics
+            #where_clause
+        {
+            type Partial = #partial_type_ident #ty_generics;
+            fn to_partial(self) -> Self::Partial {
+                match self {
+                    #(#to_partial_arms)*
+                }
+            }
+        }
+
+        impl #impl_generics ::aspartial::AsPartial for #partial_type_ident #ty_generics
+            #where_clause
+        {
+            type Partial = Self;
+            fn to_partial(self) -> Self::Partial {
+                self
+            }
+        }
+
+        #[derive(::serde::Deserialize)]
+        #[serde(bound = "")]
+        #[serde(try_from="::serde_json::Value")]
+        #(#attrs)*
+        #vis enum #partial_type_ident #impl_generics
+            #where_clause
+        {
+            #(#partial_variants,)*
+            /// The tag hasn't arrived yet, is still arriving, or names none of the variants
+            Unknown,
+        }
+
+        impl #impl_generics #partial_type_ident #ty_generics #where_clause {
+            /// The serialized tags of all variants, in declaration order
+            pub const VARIANT_TAGS: &'static [&'static str] = &[#(#variant_tags),*];
+            #accepted_tags_const
+        }
+
+        #(#variant_partial_structs)*
+
+        impl #impl_generics TryFrom<::serde_json::Value> for #partial_type_ident #ty_generics #where_clause {
+            type Error = ::serde_json::Error;
+            fn try_from(value: ::serde_json::Value) -> Result<Self, Self::Error> {
+                let found: Option<(Option<::aspartial::TagMatch>, Option<(Option<&str>, &::serde_json::Value)>)> = #find_tag;
+                let Some((tag_match, payload)) = found else #untagged_or_unknown;
+                // a tag that may still be arriving doesn't name a variant yet
+                let tag = match tag_match {
+                    Some(::aspartial::TagMatch::Exact(tag)) => tag,
+                    Some(::aspartial::TagMatch::Prefix{ .. }) => #untagged_or_unknown,
+                    None => #unknown_tag,
+                };
+                // a payload that doesn't fit the variant of the tag may still fit an untagged one
+                #[allow(unused_variables)]
+                let payload = payload;
+                match tag {
+                    #(#partials_from_payload)*
+                    _ => (),
+                }
+                #untagged_or_unknown
+            }
+        }
+
+        #impl__Serialize
+
+        #impl__FromPartial
+
+        #impl__ApplyPartial
+
+        #impl__Diff
+
+        #impl__Merge
+
+        #impl__Introspect
+
+        #impl__Flatten
+    };
+    Ok(proc_macro::TokenStream::from(expanded))
+}
+
 pub fn make_partial_enum(input: &syn::ItemEnum) -> syn::Result<TokenStream>{
     let confs = ConfigsForAsPartial::from_attrs(&input.attrs)?;
+    let traits = confs.opt_in_traits();
 
     // if let Some(from_json_val) = &confs.derive_from_json_value {
     //     if !confs.attrs.iter().any(|attr| attr.is__serde__try_from__json_value()) {
@@ -46,7 +506,24 @@ pub fn make_partial_enum(input: &syn::ItemEnum) -> syn::Result<TokenStream>{
     //     }
     // }
 
-    let enum_tag_style = SerdeEnumTagParams::from_attributes(&input.attrs);
+    let serde_repr = match &confs.serde_repr {
+        None => None,
+        Some(conf) => {
+            if let Some(variant) = input.variants.iter().find(|variant| !matches!(variant.fields, syn::Fields::Unit)) {
+                return Err(syn::Error::new(variant.span(), "aspartial(serde_repr): only unit variants are supported"))
+            }
+            let Some(repr) = input.int_repr() else {
+                return Err(syn::Error::new(conf.serde_repr_keyword.span(), "aspartial(serde_repr): needs an integer #[repr(...)]"))
+            };
+            Some(repr)
+        },
+    };
+    // discriminants are matched like untagged values
+    let enum_tag_style = match serde_repr {
+        Some(_) => SerdeEnumTagParams::Untagged,
+        None => SerdeEnumTagParams::from_attributes(&input.attrs),
+    };
+    let is_tagged = !matches!(enum_tag_style, SerdeEnumTagParams::Untagged);
 
     let partial_type_ident = match confs.mode{
         ModeConfig::Name(conf) => conf.ident,
@@ -54,86 +531,256 @@ pub fn make_partial_enum(input: &syn::ItemEnum) -> syn::Result<TokenStream>{
             syn::Error::new(conf.partial_is_inner_keyword.span(), "'newtype' is only valid for structs")
         )
     };
+    if let Some(conf) = &confs.enum_repr {
+        return make_partial_enum_repr(input, conf, &partial_type_ident, &confs.attrs, &enum_tag_style, traits)
+    }
     let (partial_struct_field_idents, variant_tags): (Vec<syn::Ident>, Vec<syn::LitStr>) = input.tagged_variants()
         .map(|(tag, v)| (v.partial_field_name(), tag))
         .unzip();
+    let tag_match_ident = syn::Ident::new("tag_match", Span::call_site());
+    let unknown_tag_ident = syn::Ident::new("unknown_tag", Span::call_site());
+    for reserved in [&tag_match_ident, &unknown_tag_ident] {
+        if let Some(clashing) = partial_struct_field_idents.iter().find(|ident| *ident == reserved) {
+            return Err(syn::Error::new(
+                clashing.span(), format!("variant name clashes with the '{reserved}' field of the partial enum")
+            ))
+        }
+    }
+    let other_tag = serde_other_tag(input, &enum_tag_style)?;
+    // unknown tags deserialize into the `#[serde(other)]` variant, if there is one
+    let or_other = other_tag.map(|tag| quote!(.or(Some(::aspartial::TagMatch::Exact(#tag)))));
     let empty_partial = quote!(#partial_type_ident{
-        #(#partial_struct_field_idents: None),*
+        #(#partial_struct_field_idents: None,)*
+        #tag_match_ident: None,
+        #unknown_tag_ident: None,
     });
-    let partial_from_value = quote!(Self{
-        #(#partial_struct_field_idents: ::serde_json::from_value(value.clone()).ok()),*
-    });
-    let partial_from_tag = quote!{
-        match tag.as_str(){
-            #(#variant_tags => Self{
-                #partial_struct_field_idents: ::serde_json::from_value(value.clone()).ok(),
-                .. #empty_partial
-            },)*
-            _ => #empty_partial
-        }
+    // untagged variants are parsed from the `whole` payload, even when the tagged ones
+    // are only parsed from its contents, found at `payload_segment` inside of it
+    let untagged_payload = |whole: &proc_macro2::TokenStream| quote!(::aspartial::deserialize::from_nested_value(&#whole, None).ok());
+    let partial_from_value = |whole: proc_macro2::TokenStream| {
+        let payloads = input.variants.iter().map(|variant| match variant.is_serde_untagged() {
+            true => untagged_payload(&whole),
+            false => quote!(::aspartial::deserialize::from_nested_value(&value, payload_segment).ok()),
+        });
+        quote!(Self{
+            #(#partial_struct_field_idents: #payloads,)*
+            #tag_match_ident: None,
+            #unknown_tag_ident: None,
+        })
     };
-    let partial_from_outer_tagged = quote! {
-        { 'from_outer_tagged: {
-            let empty = #empty_partial;
-            #(if let Some(payload) = value.get(#variant_tags) {
-                break 'from_outer_tagged Self{
-                    #partial_struct_field_idents: ::serde_json::from_value(payload.clone()).ok(),
-                    .. empty
+    // what populates each variant given a `tag_match: Option<TagMatch>`: untagged variants
+    // are tried unless the tag names another variant, and tagged unit variants have no
+    // payload, so the tag alone populates them. `payload` deserializes the others
+    let payloads_for_tag = |payload: Option<proc_macro2::TokenStream>, whole: proc_macro2::TokenStream| -> Vec<proc_macro2::TokenStream> {
+        input.tagged_variants()
+            .map(|(tag, variant)| {
+                if variant.is_serde_untagged() {
+                    let untagged_payload = untagged_payload(&whole);
+                    return quote!(match &tag_match {
+                        Some(tag_match) if !tag_match.is_open() => None,
+                        _ => #untagged_payload,
+                    })
                 }
-            })*
-            break 'from_outer_tagged #partial_from_value
+                let tagged_payload = match (&variant.fields, &payload) {
+                    (syn::Fields::Unit, _) => quote!(Some(())),
+                    (_, Some(payload)) => payload.clone(),
+                    (_, None) => quote!(None),
+                };
+                quote!(match &tag_match {
+                    Some(tag_match) if tag_match.allows(#tag) => #tagged_payload,
+                    _ => None,
+                })
+            })
+            .collect()
+    };
+    // `unknown_payload` is kept if the tag names no variant
+    let partial_from_tag = |tag_key: &syn::LitStr, unknown_payload: proc_macro2::TokenStream, whole: proc_macro2::TokenStream| {
+        let payload = quote!(::aspartial::deserialize::from_nested_value(&value, payload_segment).ok());
+        let tag_payloads = payloads_for_tag(Some(payload), whole);
+        quote!{{
+            let is_open = ::aspartial::deserialize::is_streaming(Some(#tag_key));
+            let tag_match = ::aspartial::TagMatch::find_accepted(tag, is_open, Self::ACCEPTED_TAGS);
+            let unknown_tag = match tag_match {
+                Some(_) => None,
+                None => Some(::aspartial::UnknownTag{ tag: tag.clone(), payload: #unknown_payload }),
+            };
+            let tag_match = tag_match #or_other;
+            Self{
+                #(#partial_struct_field_idents: #tag_payloads,)*
+                #tag_match_ident: tag_match,
+                #unknown_tag_ident: unknown_tag,
+            }
         }}
     };
+    let unit_payloads = payloads_for_tag(None, quote!(value));
+    let key_payloads = payloads_for_tag(
+        Some(quote!(entry.and_then(|(key, payload)| ::aspartial::deserialize::from_nested_value(payload, Some(key)).ok()))),
+        quote!(value),
+    );
+    let partial_from_whole_value = partial_from_value(quote!(value));
+    let partial_from_outer_tagged = quote! {{
+        #[allow(unused_variables)]
+        let payload_segment: Option<&str> = None;
+        // externally tagged unit variants are sent as just their tag
+        if let ::serde_json::Value::String(tag) = &value {
+            let is_open = ::aspartial::deserialize::is_streaming(None);
+            let tag_match = ::aspartial::TagMatch::find_accepted(tag, is_open, Self::ACCEPTED_TAGS);
+            return Ok(Self{
+                #(#partial_struct_field_idents: #unit_payloads,)*
+                #unknown_tag_ident: match tag_match {
+                    Some(_) => None,
+                    None => Some(::aspartial::UnknownTag{ tag: tag.clone(), payload: None }),
+                },
+                #tag_match_ident: tag_match,
+            })
+        }
+        // a key without a value yet may be the tag, still arriving
+        let pending_key = ::aspartial::deserialize::pending_key();
+        let pending_key = pending_key.as_ref().map(|(key, is_open)| (key.as_str(), *is_open));
+        match ::aspartial::TagMatch::find_key_accepted(&value, pending_key, Self::ACCEPTED_TAGS) {
+            Some((tag_match, entry)) => {
+                let tag_match = Some(tag_match);
+                Self{
+                    #(#partial_struct_field_idents: #key_payloads,)*
+                    #tag_match_ident: tag_match,
+                    #unknown_tag_ident: None,
+                }
+            },
+            None => {
+                let mut partial = #partial_from_whole_value;
+                // a single key is the shape of a variant, just not of a known one
+                if let Some(object) = value.as_object() {
+                    match (object.iter().collect::<Vec<_>>().as_slice(), pending_key) {
+                        ([(tag, payload)], _) => partial.#unknown_tag_ident = Some(::aspartial::UnknownTag{
+                            tag: (*tag).clone(),
+                            payload: Some((*payload).clone()),
+                        }),
+                        ([], Some((tag, _))) => partial.#unknown_tag_ident = Some(::aspartial::UnknownTag{
+                            tag: tag.to_owned(),
+                            payload: None,
+                        }),
+                        _ => (),
+                    }
+                }
+                partial
+            },
+        }
+    }};
 
-    let partial_type_attrs = confs.attrs;
     let partial_struct_fields: Vec<syn::Field> = input.variants.iter()
-        .map(|v| v.as_partial_field())
-        .collect::<syn::Result<_>>()?;
+        .map(|v| v.as_partial_field(input, &partial_type_ident))
+        .collect();
+    let variant_partial_structs: Vec<_> = input.variants.iter()
+        .filter_map(|variant| make_variant_partial_struct(input, variant, &partial_type_ident, &confs.attrs))
+        .collect();
+    let partial_type_attrs = confs.attrs;
     let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
     let fields = input.variants.iter()
-        .map(|v| v.fields())
-        .flatten();
+        .flat_map(|v| v.fields());
     let where_clause = where_clause_for_partial(input.generics.where_clause.clone(), fields);
     let enum_ident = &input.ident;
+    let impl__FromPartial = traits.complete.then(|| {
+        make_from_partial_enum(input, &partial_type_ident, &enum_tag_style, where_clause.clone())
+    });
+    let impl__ApplyPartial = traits.apply.then(|| make_apply_enum(input, &enum_tag_style, where_clause.clone()));
+    let impl__Diff = traits.diff.then(|| make_diff_enum(input, &partial_type_ident, |variant, tag| {
+        let partial_field_name = variant.partial_field_name();
+        let tag_match = if is_tagged && !variant.is_serde_untagged() {
+            quote!(Some(::aspartial::TagMatch::Exact(#tag)))
+        } else {
+            quote!(None)
+        };
+        quote!{{
+            let mut partial = #empty_partial;
+            partial.#partial_field_name = Some(variant_partial);
+            partial.#tag_match_ident = #tag_match;
+            partial
+        }}
+    }, where_clause.clone()));
+    let allowed: Vec<_> = input.tagged_variants()
+        .map(|(tag, variant)| tag_allows(quote!(tag_match), variant, &tag))
+        .collect();
+    let impl__Merge = make_merge_enum(
+        &partial_type_ident, &input.generics, where_clause.clone(), &partial_struct_fields, &allowed
+    );
+    let impl__Introspect = make_introspect_enum(input, &partial_type_ident, &enum_tag_style, where_clause.clone());
+    let resolution = make_resolve_enum(input, &partial_type_ident, where_clause.clone())?;
+    let impl__Serialize = match &serde_repr {
+        Some(repr) => make_serialize_repr_enum(input, &partial_type_ident, repr),
+        None => make_serialize_enum(input, &partial_type_ident, &enum_tag_style, where_clause.clone()),
+    };
+    let impl__Flatten = match &serde_repr {
+        Some(_) => make_flatten_default(&partial_type_ident, &input.generics, where_clause.clone()),
+        None => make_flatten_enum(input, &partial_type_ident, &enum_tag_style, where_clause.clone()),
+    };
 
-    let impl__TryFrom__json_value = match enum_tag_style{
-        SerdeEnumTagParams::Untagged => quote!{
+    let accepted_tags_const = accepted_tags_const(input);
+    let turbofish = ty_generics.as_turbofish();
+    let variant_idents = input.variants.iter().map(|variant| &variant.ident);
+    let impl__TryFrom__json_value = match (&serde_repr, enum_tag_style) {
+        (Some(repr), _) => quote!{
             impl #impl_generics TryFrom<::serde_json::Value> for #partial_type_ident #ty_generics #where_clause {
                 type Error = ::serde_json::Error;
                 fn try_from(value: ::serde_json::Value) -> Result<Self, Self::Error> {
-                    Ok(#partial_from_value)
+                    let discriminant = ::serde_json::from_value::<#repr>(value).ok();
+                    Ok(Self{
+                        #(#partial_struct_field_idents: (discriminant == Some(#enum_ident #turbofish::#variant_idents as #repr)).then_some(()),)*
+                        #tag_match_ident: None,
+                        #unknown_tag_ident: None,
+                    })
                 }
             }
         },
-        SerdeEnumTagParams::InternallyTagged { tag_key } => quote!{
+        (None, SerdeEnumTagParams::Untagged) => quote!{
             impl #impl_generics TryFrom<::serde_json::Value> for #partial_type_ident #ty_generics #where_clause {
                 type Error = ::serde_json::Error;
                 fn try_from(value: ::serde_json::Value) -> Result<Self, Self::Error> {
-                    let tag = match value.get(#tag_key) {
-                        Some(::serde_json::Value::String(tag)) => tag,
-                        _ => return Ok(#partial_from_value),
-                    };
-                    Ok(#partial_from_tag)
+                    #[allow(unused_variables)]
+                    let payload_segment: Option<&str> = None;
+                    Ok(#partial_from_whole_value)
                 }
             }
         },
-        SerdeEnumTagParams::AdjacentlyTagged { tag_key, content_key } => quote!{
-            impl #impl_generics TryFrom<::serde_json::Value> for #partial_type_ident #ty_generics #where_clause {
-                type Error = ::serde_json::Error;
-                fn try_from(value: ::serde_json::Value) -> Result<Self, Self::Error> {
-                    let orig_val = &value;
-                    let value = value.get(#content_key).unwrap_or(&value);
-                    let tag = match orig_val.get(#tag_key) {
-                        Some(::serde_json::Value::String(tag)) => tag,
-                        _ => {
-                            return Ok(#partial_from_value)
-                        },
-                    };
-                    Ok(#partial_from_tag)
+        (None, SerdeEnumTagParams::InternallyTagged { tag_key }) => {
+            let partial_from_tag = partial_from_tag(&tag_key, quote!(Some(value.clone())), quote!(value));
+            quote!{
+                impl #impl_generics TryFrom<::serde_json::Value> for #partial_type_ident #ty_generics #where_clause {
+                    type Error = ::serde_json::Error;
+                    fn try_from(value: ::serde_json::Value) -> Result<Self, Self::Error> {
+                        #[allow(unused_variables)]
+                        let payload_segment: Option<&str> = None;
+                        let tag = match value.get(#tag_key) {
+                            Some(::serde_json::Value::String(tag)) => tag,
+                            _ => return Ok(#partial_from_whole_value),
+                        };
+                        Ok(#partial_from_tag)
+                    }
                 }
             }
         },
-        SerdeEnumTagParams::ExternallyTagged => quote! {
+        (None, SerdeEnumTagParams::AdjacentlyTagged { tag_key, content_key }) => {
+            let partial_from_tag = partial_from_tag(&tag_key, quote!(orig_val.get(#content_key).cloned()), quote!(orig_val));
+            let partial_from_value = partial_from_value(quote!(orig_val));
+            quote!{
+                impl #impl_generics TryFrom<::serde_json::Value> for #partial_type_ident #ty_generics #where_clause {
+                    type Error = ::serde_json::Error;
+                    fn try_from(value: ::serde_json::Value) -> Result<Self, Self::Error> {
+                        let orig_val = &value;
+                        #[allow(unused_variables)]
+                        let payload_segment = value.get(#content_key).map(|_| #content_key);
+                        let value = value.get(#content_key).unwrap_or(&value);
+                        let tag = match orig_val.get(#tag_key) {
+                            Some(::serde_json::Value::String(tag)) => tag,
+                            _ => {
+                                return Ok(#partial_from_value)
+                            },
+                        };
+                        Ok(#partial_from_tag)
+                    }
+                }
+            }
+        },
+        (None, SerdeEnumTagParams::ExternallyTagged) => quote! {
             impl #impl_generics TryFrom<::serde_json::Value> for #partial_type_ident #ty_generics #where_clause {
                 type Error = ::serde_json::Error;
                 fn try_from(value: ::serde_json::Value) -> Result<Self, Self::Error> {
@@ -148,23 +795,26 @@ pub fn make_partial_enum(input: &syn::ItemEnum) -> syn::Result<TokenStream>{
         #[serde(try_from="::serde_json::Value")]
     );
 
-    for variant in input.variants.iter() {
-        if variant.fields.len() > 1 {
-            return Err(syn::Error::new(variant.fields.span(), "Only single, unnamed fields supported in variants for now"))
-        }
-    }
     let fn__to_partial: syn::ItemFn = {
         let match_arms: Vec<_> = input.variants.iter()
             .enumerate()
             .map(|(variant_idx, variant)| {
                 let variant_ident = &variant.ident;
-                let destructure_ident = format_ident!("variant_{variant_idx}");
                 let partial_field_name = variant.partial_field_name();
+                let (pattern, bindings) = destructure(&variant.fields, "field");
+                let payload = variant_payload_to_partial(variant, &partial_type_ident, &bindings);
+                let tag_match = if is_tagged && !variant.is_serde_untagged() {
+                    let variant_tag = &variant_tags[variant_idx];
+                    quote!(Some(::aspartial::TagMatch::Exact(#variant_tag)))
+                } else {
+                    quote!(None)
+                };
 
                 quote!{
-                    Self::#variant_ident(#destructure_ident) => {
+                    Self::#variant_ident #pattern => {
                         #partial_type_ident {
-                            #partial_field_name: Some(#destructure_ident.to_partial()),
+                            #partial_field_name: Some(#payload),
+                            #tag_match_ident: #tag_match,
                             ..empty
                         }
                     }
@@ -203,16 +853,54 @@ pub fn make_partial_enum(input: &syn::ItemEnum) -> syn::Result<TokenStream>{
         pub struct #partial_type_ident #impl_generics
             #where_clause
         {
-            #(#partial_struct_fields),*
+            #(#partial_struct_fields,)*
+            /// How the tag in the payload matched the variants, if the enum is tagged
+            /// and the tag has been seen
+            #[serde(skip)]
+            pub #tag_match_ident: Option<::aspartial::TagMatch>,
+            /// The tag in the payload, if the enum is tagged and the tag names none of the variants
+            #[serde(skip)]
+            pub #unknown_tag_ident: Option<::aspartial::UnknownTag>,
+        }
+
+        impl #impl_generics #partial_type_ident #ty_generics #where_clause {
+            /// The serialized tags of all variants, in declaration order
+            pub const VARIANT_TAGS: &'static [&'static str] = &[#(#variant_tags),*];
+            #accepted_tags_const
         }
 
+        #(#variant_partial_structs)*
+
         #impl__TryFrom__json_value
+
+        #impl__Serialize
+
+        #impl__FromPartial
+
+        #impl__ApplyPartial
+
+        #impl__Diff
+
+        #impl__Merge
+
+        #impl__Introspect
+
+        #impl__Flatten
+
+        #resolution
     };
     Ok(proc_macro::TokenStream::from(expanded))
 }
 
 pub fn make_partial_struct(input: &syn::ItemStruct) -> syn::Result<TokenStream>{
     let confs = ConfigsForAsPartial::from_attrs(&input.attrs)?;
+    let traits = confs.opt_in_traits();
+    if let Some(conf) = &confs.serde_repr {
+        return Err(syn::Error::new(conf.serde_repr_keyword.span(), "'serde_repr' is only valid for enums"))
+    }
+    if let Some(conf) = &confs.enum_repr {
+        return Err(syn::Error::new(conf.enum_repr_keyword.span(), "'enum_repr' is only valid for enums"))
+    }
 
     let struct_name = &input.ident;
     let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
@@ -235,6 +923,9 @@ pub fn make_partial_struct(input: &syn::ItemStruct) -> syn::Result<TokenStream>{
                 return Err(syn::Error::new(field.span(), "Deriving as newtype would lose serde default"))
             }
             let field_ty = &field.ty;
+            let impl__FromPartial = traits.complete.then(|| make_from_partial_newtype(input, field, where_clause.clone()));
+            let impl__ApplyPartial = traits.apply.then(|| make_apply_newtype(input, field, where_clause.clone()));
+            let impl__Diff = traits.diff.then(|| make_diff_newtype(input, field, where_clause.clone()));
 
             return Ok(quote!(
                 impl #impl_generics ::aspartial::AsPartial for #struct_name #ty_generics
@@ -245,75 +936,39 @@ pub fn make_partial_struct(input: &syn::ItemStruct) -> syn::Result<TokenStream>{
                         self.0.to_partial()
                     }
                 }
+
+                #impl__FromPartial
+
+                #impl__ApplyPartial
+
+                #impl__Diff
             ).into())
         }
     };
 
     let mut default_functions = Vec::<syn::ItemFn>::new();
-    let partial_struct = {
-        let mut partial_struct = input.clone();
-        partial_struct.ident = partial_struct_ident;
-        partial_struct.attrs = confs.attrs;
-        partial_struct.attrs.push( parse_quote!( #[derive(::serde::Deserialize)] ));
-        partial_struct.attrs.push( parse_quote!( #[serde(bound = "")] ));
-        partial_struct.generics.where_clause = Some(where_clause.clone());
-
-        for (field_idx, field) in partial_struct.fields.iter_mut().enumerate() {
-            field.vis = parse_quote!(pub);
-            field.ty = field.partial_type();
-            let mut fixed_attrs = Vec::<syn::Attribute>::new();
-            for attr in &field.attrs {
-                if !attr.is_serde_attr(){
-                    continue;
-                }
-                let Some(default_path) = attr.as_serde_default_func_path() else {
-                    fixed_attrs.push(attr.clone());
-                    continue;
-                };
-                let default_func_name = {
-                    let field_ident = field.ident.as_ref().map(|ident| ident.to_string()).unwrap_or(field_idx.to_string());
-                    format_ident!("__default_for__{}__{}", partial_struct.ident, field_ident)
-                };
-                default_functions.push({
-                    let field_ty = &field.ty;
-                    parse_quote!{
-                        #[allow(non_snake_case)]
-                        fn #default_func_name() -> #field_ty {
-                            ::aspartial::AsPartial::to_partial(#default_path())
-                        }
-                    }
-                });
-                fixed_attrs.push({
-                    let serde_default_arg = syn::LitStr::new(&default_func_name.to_string(), field.span());
-                    parse_quote!(
-                        #[serde(default=#serde_default_arg)]
-                    )
-                });
-            }
-            field.attrs = fixed_attrs;
-        }
-        partial_struct
-    };
+    let partial_struct = make_partial_struct_item(
+        input, partial_struct_ident, confs.attrs, &where_clause, &mut default_functions
+    );
 
     let partial_struct_name = &partial_struct.ident;
+    let impl__FromPartial = traits.complete.then(|| {
+        make_from_partial_struct(input, partial_struct_name, where_clause.clone())
+    });
+    let impl__ApplyPartial = traits.apply.then(|| make_apply_struct(input, where_clause.clone()));
+    let impl__Diff = traits.diff.then(|| make_diff_struct(input, partial_struct_name, where_clause.clone()));
+    let impl__Merge = make_merge_struct(&partial_struct, where_clause.clone());
+    let impl__Introspect = make_introspect_struct(input, partial_struct_name, where_clause.clone());
+    let impl__Flatten = make_flatten_default(partial_struct_name, &input.generics, where_clause.clone());
 
     let fn__to_partial: syn::ItemFn = {
-        let field_inits = input.fields.iter()
+        let field_values = input.fields.iter()
             .enumerate()
-            .map(|(field_idx, field)|{
-                let field_ident: proc_macro2::TokenStream = match field.ident.clone(){
-                    Some(ident) => ident.to_token_stream(),
-                    None => {
-                        syn::LitInt::new(&field_idx.to_string(), field.span()).to_token_stream()
-                    },
-                };
-                if field.partial_is_optional() {
-                    quote!{#field_ident : Some(self.#field_ident.to_partial())}
-                } else {
-                    quote!{#field_ident : self.#field_ident.to_partial()}
-                }
-            })
-            .collect::<Vec<_>>();
+            .map(|(field_idx, field)| {
+                let member = field_member(field, field_idx);
+                quote!(self.#member)
+            });
+        let field_inits = partial_field_inits(&input.fields, field_values);
         parse_quote!(
             fn to_partial(self) -> Self::Partial {
                 #partial_struct_name {
@@ -343,6 +998,18 @@ pub fn make_partial_struct(input: &syn::ItemStruct) -> syn::Result<TokenStream>{
         #partial_struct
 
         #(#default_functions)*
+
+        #impl__FromPartial
+
+        #impl__ApplyPartial
+
+        #impl__Diff
+
+        #impl__Merge
+
+        #impl__Introspect
+
+        #impl__Flatten
     };
     Ok(proc_macro::TokenStream::from(expanded))
 }
@@ -355,6 +1022,6 @@ pub fn do_derive_as_partial(input: TokenStream) -> syn::Result<TokenStream>{
         _ => return Err(syn::Error::new(Span::call_site(), "Must apply to enum or struct"))
     }?;
 
-    Ok(proc_macro::TokenStream::from(output))
+    Ok(output)
 }
 
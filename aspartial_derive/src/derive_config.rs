@@ -19,6 +19,26 @@ pub struct AttrsConfig {
     pub attrs: Vec<syn::Attribute>
 }
 
+pub struct SerdeReprConfig {
+    pub serde_repr_keyword: syn::Ident,
+}
+
+pub struct EnumReprConfig {
+    pub enum_repr_keyword: syn::Ident,
+}
+
+pub struct CompleteConfig {
+    pub complete_keyword: syn::Ident,
+}
+
+pub struct ApplyConfig {
+    pub apply_keyword: syn::Ident,
+}
+
+pub struct DiffConfig {
+    pub diff_keyword: syn::Ident,
+}
+
 pub enum ModeConfig {
     /// Determines the name of the generated partial type
     Name(NameConfig),
@@ -47,6 +67,16 @@ pub enum Config{
     PartialIsInner(PartialIsInnerConfig),
     /// Add the attributes to the generated type
     Attrs(AttrsConfig),
+    /// The enum is (de)serialized as its integer discriminant, like `serde_repr` does
+    SerdeRepr(SerdeReprConfig),
+    /// The partial of a tagged enum is an enum too, rather than a struct with all variants
+    EnumRepr(EnumReprConfig),
+    /// Also derive `FromPartial`
+    Complete(CompleteConfig),
+    /// Also derive `ApplyPartial` (and `FromPartial`, which it needs)
+    Apply(ApplyConfig),
+    /// Also derive `Diff`
+    Diff(DiffConfig),
 }
 
 impl From<ModeConfig> for Config {
@@ -72,6 +102,31 @@ impl From<AttrsConfig> for Config {
         Self::Attrs(value)
     }
 }
+impl From<SerdeReprConfig> for Config {
+    fn from(value: SerdeReprConfig) -> Self {
+        Self::SerdeRepr(value)
+    }
+}
+impl From<EnumReprConfig> for Config {
+    fn from(value: EnumReprConfig) -> Self {
+        Self::EnumRepr(value)
+    }
+}
+impl From<CompleteConfig> for Config {
+    fn from(value: CompleteConfig) -> Self {
+        Self::Complete(value)
+    }
+}
+impl From<ApplyConfig> for Config {
+    fn from(value: ApplyConfig) -> Self {
+        Self::Apply(value)
+    }
+}
+impl From<DiffConfig> for Config {
+    fn from(value: DiffConfig) -> Self {
+        Self::Diff(value)
+    }
+}
 
 ///////////////////////////////
 
@@ -93,9 +148,17 @@ impl syn::parse::Parse for Config {
                 }.into())
             },
             "newtype" => Ok(PartialIsInnerConfig{partial_is_inner_keyword: ident}.into()),
+            "serde_repr" => Ok(SerdeReprConfig{serde_repr_keyword: ident}.into()),
+            "enum_repr" => Ok(EnumReprConfig{enum_repr_keyword: ident}.into()),
+            "complete" => Ok(CompleteConfig{complete_keyword: ident}.into()),
+            "apply" => Ok(ApplyConfig{apply_keyword: ident}.into()),
+            "diff" => Ok(DiffConfig{diff_keyword: ident}.into()),
             _ => Err(syn::Error::new(
                 ident.span(),
-                format!("Unrecognized AsPartial config. Expected 'name', 'newtype' or 'attrs', found '{ident}'")
+                format!(
+                    "Unrecognized AsPartial config. Expected 'name', 'newtype', 'attrs', 'serde_repr', 'enum_repr', \
+                    'complete', 'apply' or 'diff', found '{ident}'"
+                )
             ))
         }
     }
@@ -106,6 +169,11 @@ impl syn::parse::Parse for Config {
 pub struct ConfigsForAsPartial {
     pub mode: ModeConfig,
     pub attrs: Vec<syn::Attribute>,
+    pub serde_repr: Option<SerdeReprConfig>,
+    pub enum_repr: Option<EnumReprConfig>,
+    pub complete: Option<CompleteConfig>,
+    pub apply: Option<ApplyConfig>,
+    pub diff: Option<DiffConfig>,
 }
 
 impl ConfigsForAsPartial {
@@ -114,9 +182,14 @@ impl ConfigsForAsPartial {
             syn::Error::new(Span::call_site(), "must specify name or newtype mode")
         );
         let mut attrs_for_partial_config = Vec::<syn::Attribute>::new();
+        let mut serde_repr = None::<SerdeReprConfig>;
+        let mut enum_repr = None::<EnumReprConfig>;
+        let mut complete = None::<CompleteConfig>;
+        let mut apply = None::<ApplyConfig>;
+        let mut diff = None::<DiffConfig>;
 
         for attr in attrs {
-            if attr.path().segments.last().unwrap().ident.to_string() != "aspartial" {
+            if attr.path().segments.last().unwrap().ident != "aspartial" {
                 continue
             }
             let syn::Meta::List(meta_list) = &attr.meta else {
@@ -125,27 +198,79 @@ impl ConfigsForAsPartial {
             match meta_list.parse_args::<Config>()? {
                 Config::Name(conf) => {
                     let span = conf.partial_type_key.span();
-                    if let Ok(_) = std::mem::replace(&mut mode, Ok(conf.into())) {
+                    if std::mem::replace(&mut mode, Ok(conf.into())).is_ok() {
                         return Err(syn::Error::new(span, "Setting mode again"))
                     }
                 },
                 Config::PartialIsInner(conf) => {
                     let span = conf.partial_is_inner_keyword.span();
-                    if let Ok(_) = std::mem::replace(&mut mode, Ok(conf.into())) {
+                    if std::mem::replace(&mut mode, Ok(conf.into())).is_ok() {
                         return Err(syn::Error::new(span, "Setting mode again"))
                     }
                 },
                 Config::Attrs(new_attrs_conf) => {
                     attrs_for_partial_config.extend(new_attrs_conf.attrs);
                 },
+                Config::SerdeRepr(conf) => {
+                    let span = conf.serde_repr_keyword.span();
+                    if serde_repr.replace(conf).is_some() {
+                        return Err(syn::Error::new(span, "Setting serde_repr again"))
+                    }
+                },
+                Config::EnumRepr(conf) => {
+                    let span = conf.enum_repr_keyword.span();
+                    if enum_repr.replace(conf).is_some() {
+                        return Err(syn::Error::new(span, "Setting enum_repr again"))
+                    }
+                },
+                Config::Complete(conf) => {
+                    let span = conf.complete_keyword.span();
+                    if complete.replace(conf).is_some() {
+                        return Err(syn::Error::new(span, "Setting complete again"))
+                    }
+                },
+                Config::Apply(conf) => {
+                    let span = conf.apply_keyword.span();
+                    if apply.replace(conf).is_some() {
+                        return Err(syn::Error::new(span, "Setting apply again"))
+                    }
+                },
+                Config::Diff(conf) => {
+                    let span = conf.diff_keyword.span();
+                    if diff.replace(conf).is_some() {
+                        return Err(syn::Error::new(span, "Setting diff again"))
+                    }
+                },
             }
         }
 
         Ok(Self{
             mode: mode?,
             attrs: attrs_for_partial_config,
+            serde_repr,
+            enum_repr,
+            complete,
+            apply,
+            diff,
         })
     }
+
+    pub fn opt_in_traits(&self) -> OptInTraits {
+        OptInTraits {
+            // `ApplyPartial` needs `FromPartial`
+            complete: self.complete.is_some() || self.apply.is_some(),
+            apply: self.apply.is_some(),
+            diff: self.diff.is_some(),
+        }
+    }
+}
+
+/// The traits that are only derived when asked for, since they need every field to implement them too
+#[derive(Clone, Copy)]
+pub struct OptInTraits {
+    pub complete: bool,
+    pub apply: bool,
+    pub diff: bool,
 }
 
 
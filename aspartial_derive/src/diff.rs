@@ -0,0 +1,170 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_quote_spanned, spanned::Spanned};
+
+use crate::syn_extensions::{IEnumExt, IFieldExt, IVariantExt, PartialFieldKind};
+use crate::util::{destructure, field_member, generics_used_by};
+
+/// Adds `Diff` bounds for the type of every field that uses the type parameters to a where clause
+/// from `where_clause_for_partial`. Fields of other types need either `Diff` or `PartialEq`, see `diff_value`.
+fn where_clause_for_diff<'field>(
+    mut wc: syn::WhereClause,
+    generics: &syn::Generics,
+    fields: impl IntoIterator<Item=&'field syn::Field>,
+) -> syn::WhereClause {
+    for field in fields {
+        if generics_used_by(generics, [field]).type_params().next().is_none() {
+            continue
+        }
+        let span = field.ty.span();
+        let field_ty = &field.ty;
+        wc.predicates.push(parse_quote_spanned!{span=>
+            #field_ty : ::aspartial::Diff
+        });
+    }
+    wc
+}
+
+/// An expression diffing `old` (a reference) against `new`, with `Diff` if the type implements
+/// it, or else by comparing them and taking the whole new value if they differ
+fn diff_value(old: &TokenStream, new: &TokenStream) -> TokenStream {
+    quote!{{
+        #[allow(unused_imports)]
+        use ::aspartial::diff::{DiffFieldByDiff as _, DiffFieldByEq as _};
+        (&::aspartial::diff::DiffField(#old)).diff_field(#new)
+    }}
+}
+
+/// An expression diffing the fields in `olds` against the ones in `news`, evaluating to
+/// `Ok` with the partial built by `partial_ctor`, or to `Err` with the value built by `ctor`
+/// when nothing changed
+fn diff_fields(
+    fields: &syn::Fields,
+    olds: &[TokenStream],
+    news: &[TokenStream],
+    ctor: TokenStream,
+    partial_ctor: TokenStream,
+) -> TokenStream {
+    let mut members = Vec::<syn::Member>::new();
+    let mut bindings = Vec::<syn::Ident>::new();
+    let mut partial_inits = Vec::<TokenStream>::new();
+    for (field_idx, field) in fields.iter().enumerate() {
+        let member = field_member(field, field_idx);
+        let binding = format_ident!("field_{field_idx}");
+        partial_inits.push(match field.partial_kind() {
+            PartialFieldKind::Optional => quote!(#member: #binding.ok()),
+            PartialFieldKind::Tristate(_) => quote!(#member: ::aspartial::Tristate::from_nested(#binding.ok())),
+            PartialFieldKind::Defaulted => quote!(#member: match #binding {
+                Ok(changed) => ::aspartial::Defaulted::Supplied(changed),
+                Err(same) => ::aspartial::Defaulted::Default(::aspartial::AsPartial::to_partial(same)),
+            }),
+        });
+        members.push(member);
+        bindings.push(binding);
+    }
+    let diffs = olds.iter().zip(news).map(|(old, new)| diff_value(old, new));
+    quote!{
+        match ( #(#diffs,)* ) {
+            ( #(Err(#bindings),)* ) => Err(#ctor{ #(#members: #bindings),* }),
+            #[allow(unreachable_patterns)]
+            ( #(#bindings,)* ) => Ok(#partial_ctor{ #(#partial_inits),* }),
+        }
+    }
+}
+
+/// Unchanged fields are left out of the diff, except for fields with serde
+/// defaults, which hold the unchanged value marked as a default
+pub fn make_diff_struct(input: &syn::ItemStruct, partial: &syn::Ident, where_clause: syn::WhereClause) -> TokenStream {
+    let where_clause = where_clause_for_diff(where_clause, &input.generics, input.fields.iter());
+    let members: Vec<syn::Member> = input.fields.iter().enumerate()
+        .map(|(field_idx, field)| field_member(field, field_idx))
+        .collect();
+    let olds: Vec<TokenStream> = members.iter().map(|member| quote!(&self.#member)).collect();
+    let news: Vec<TokenStream> = members.iter().map(|member| quote!(new.#member)).collect();
+    let diff = diff_fields(&input.fields, &olds, &news, quote!(Self), quote!(#partial));
+
+    let struct_name = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    quote!{
+        impl #impl_generics ::aspartial::Diff for #struct_name #ty_generics
+            #where_clause
+        {
+            fn diff_or_same(&self, new: Self) -> Result<Self::Partial, Self> {
+                #diff
+            }
+        }
+    }
+}
+
+pub fn make_diff_newtype(input: &syn::ItemStruct, field: &syn::Field, where_clause: syn::WhereClause) -> TokenStream {
+    let where_clause = where_clause_for_diff(where_clause, &input.generics, [field]);
+    let member = field_member(field, 0);
+    let diff = diff_value(&quote!(&self.#member), &quote!(new.#member));
+    let struct_name = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    quote!{
+        impl #impl_generics ::aspartial::Diff for #struct_name #ty_generics
+            #where_clause
+        {
+            fn diff_or_same(&self, new: Self) -> Result<Self::Partial, Self> {
+                #diff.map_err(|value| Self{ #member: value })
+            }
+        }
+    }
+}
+
+/// Values of the same variant are diffed, while a change of variant produces the whole new value.
+/// `into_partial` wraps the diff of a variant, bound to `variant_partial`, into the partial enum.
+pub fn make_diff_enum(
+    input: &syn::ItemEnum,
+    partial: &syn::Ident,
+    into_partial: impl Fn(&syn::Variant, &syn::LitStr) -> TokenStream,
+    where_clause: syn::WhereClause,
+) -> TokenStream {
+    let where_clause = where_clause_for_diff(where_clause, &input.generics, input.variants.iter().flat_map(|v| v.fields()));
+
+    let same_variant_arms = input.tagged_variants().map(|(tag, variant)| {
+        let variant_ident = &variant.ident;
+        let (patterns, diff) = match &variant.fields {
+            syn::Fields::Unit => {
+                return quote!((Self::#variant_ident, Self::#variant_ident) => Err(Self::#variant_ident),)
+            },
+            syn::Fields::Unnamed(syn::FieldsUnnamed{unnamed, ..}) if unnamed.len() == 1 => {
+                let diff = diff_value(&quote!(old), &quote!(new));
+                (
+                    quote!((Self::#variant_ident(old), Self::#variant_ident(new))),
+                    quote!(#diff.map_err(Self::#variant_ident)),
+                )
+            },
+            _ => {
+                let (old_pattern, olds) = destructure(&variant.fields, "old");
+                let (new_pattern, news) = destructure(&variant.fields, "new");
+                let olds: Vec<TokenStream> = olds.iter().map(|old| quote!(#old)).collect();
+                let news: Vec<TokenStream> = news.iter().map(|new| quote!(#new)).collect();
+                let variant_partial = variant.partial_struct_ident(partial);
+                let diff = diff_fields(&variant.fields, &olds, &news, quote!(Self::#variant_ident), quote!(#variant_partial));
+                (quote!((Self::#variant_ident #old_pattern, Self::#variant_ident #new_pattern)), diff)
+            },
+        };
+        let into_partial = into_partial(variant, &tag);
+        quote!{
+            #patterns => #diff.map(|variant_partial| #into_partial),
+        }
+    });
+
+    let enum_ident = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    quote!{
+        impl #impl_generics ::aspartial::Diff for #enum_ident #ty_generics
+            #where_clause
+        {
+            fn diff_or_same(&self, new: Self) -> Result<Self::Partial, Self> {
+                match (self, new) {
+                    #(#same_variant_arms)*
+                    #[allow(unreachable_patterns)]
+                    (_, new) => Ok(::aspartial::AsPartial::to_partial(new)),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::serde_attributes::SerdeEnumTagParams;
+use crate::syn_extensions::{IEnumExt, IVariantExt};
+
+/// `Flatten` for a partial that serde reads and writes the same way whether it is flattened or not
+pub fn make_flatten_default(
+    partial: &syn::Ident, generics: &syn::Generics, where_clause: syn::WhereClause,
+) -> TokenStream {
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    quote!{
+        impl #impl_generics ::aspartial::flatten::Flatten for #partial #ty_generics #where_clause {}
+    }
+}
+
+/// `Flatten` for the partial of an enum. Externally and adjacently tagged enums take the entries
+/// of their tags (and content) like serde does, unless some variant is `#[serde(untagged)]`, in
+/// which case serde buffers the whole map to try it as well.
+pub fn make_flatten_enum(
+    input: &syn::ItemEnum, partial: &syn::Ident, enum_tag_style: &SerdeEnumTagParams, where_clause: syn::WhereClause,
+) -> TokenStream {
+    let keys: Vec<syn::LitStr> = match enum_tag_style {
+        _ if input.variants.iter().any(|variant| variant.is_serde_untagged()) => {
+            return make_flatten_default(partial, &input.generics, where_clause)
+        },
+        SerdeEnumTagParams::Untagged | SerdeEnumTagParams::InternallyTagged { .. } => {
+            return make_flatten_default(partial, &input.generics, where_clause)
+        },
+        SerdeEnumTagParams::AdjacentlyTagged { tag_key, content_key } => vec![tag_key.clone(), content_key.clone()],
+        SerdeEnumTagParams::ExternallyTagged => input.accepted_tags().into_iter().map(|(accepted, _)| accepted).collect(),
+    };
+    let partial_name = partial.to_string();
+    let serialize_flattened = match enum_tag_style {
+        SerdeEnumTagParams::ExternallyTagged => quote!{
+            fn serialize_flattened<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where for<'__partial> Self: ::serde::Serialize
+            {
+                ::aspartial::flatten::serialize_external(self, serializer)
+            }
+        },
+        _ => quote!(),
+    };
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    quote!{
+        impl #impl_generics ::aspartial::flatten::Flatten for #partial #ty_generics #where_clause {
+            fn deserialize_flattened<'de, D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>
+            where Self: ::serde::de::DeserializeOwned
+            {
+                ::aspartial::flatten::deserialize_entries(deserializer, #partial_name, &[#(#keys),*])
+            }
+
+            #serialize_flattened
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_quote_spanned, spanned::Spanned};
+
+use crate::serde_attributes::{SerdeEnumTagParams, SerdeOuterRenameParams};
+use crate::syn_extensions::{IEnumExt, IFieldExt, IVariantExt, PartialFieldKind};
+use crate::util::field_member;
+
+/// Adds `FromPartial` bounds for the type of every field to a where clause from `where_clause_for_partial`
+pub fn where_clause_for_from_partial<'field>(
+    mut wc: syn::WhereClause,
+    fields: impl IntoIterator<Item=&'field syn::Field>,
+) -> syn::WhereClause {
+    for field in fields {
+        let span = field.ty.span();
+        let field_ty = &field.ty;
+        wc.predicates.push(parse_quote_spanned!{span=>
+            #field_ty : ::aspartial::FromPartial
+        });
+    }
+    wc
+}
+
+/// Where the problems of a field are reported, relative to the struct that contains it
+pub fn field_segment(
+    field: &syn::Field, field_idx: usize, field_count: usize, outer_rename: Option<&SerdeOuterRenameParams>
+) -> TokenStream {
+    if field.is_serde_flatten() {
+        return quote!(None)
+    }
+    match field.wire_name(outer_rename) {
+        Some(wire_name) => quote!(Some(::aspartial::path::PathSegment::from(#wire_name))),
+        // a single unnamed field is serialized as the field itself
+        None if field_count == 1 => quote!(None),
+        None => quote!(Some(::aspartial::path::PathSegment::Index(#field_idx))),
+    }
+}
+
+/// Where the problems of a variant's contents are reported, relative to the enum
+pub fn variant_segment(enum_tag_style: &SerdeEnumTagParams, variant: &syn::Variant, tag: &syn::LitStr) -> TokenStream {
+    if variant.is_serde_untagged() {
+        return quote!(None)
+    }
+    match enum_tag_style {
+        SerdeEnumTagParams::ExternallyTagged => quote!(Some(::aspartial::path::PathSegment::from(#tag))),
+        SerdeEnumTagParams::AdjacentlyTagged { content_key, .. } => {
+            quote!(Some(::aspartial::path::PathSegment::from(#content_key)))
+        },
+        SerdeEnumTagParams::InternallyTagged { .. } | SerdeEnumTagParams::Untagged => quote!(None),
+    }
+}
+
+/// An expression telling whether the tag in `tag_match`, an `Option<&TagMatch>` that is
+/// `None` if no tag was seen, allows the variant with tag `tag`. Untagged variants
+/// are only ruled out by a tag that names one of the others.
+pub fn tag_allows(tag_match: TokenStream, variant: &syn::Variant, tag: &syn::LitStr) -> TokenStream {
+    if variant.is_serde_untagged() {
+        quote!(#tag_match.is_none_or(|tag_match| tag_match.is_open()))
+    } else {
+        quote!(#tag_match.is_none_or(|tag_match| tag_match.allows(#tag)))
+    }
+}
+
+fn impl_try_complete(
+    original: &syn::Ident, partial: &syn::Ident, generics: &syn::Generics, where_clause: &syn::WhereClause,
+) -> TokenStream {
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    quote!{
+        impl #impl_generics #partial #ty_generics #where_clause {
+            /// Rebuilds the full value, listing everything that is still missing if that isn't possible
+            pub fn try_complete(self) -> Result<#original #ty_generics, ::aspartial::CompletionError> {
+                <#original #ty_generics as ::aspartial::FromPartial>::from_partial(self)
+            }
+        }
+    }
+}
+
+/// An expression completing `fields` from the partial struct in `partial` into
+/// `Some(#constructor{ ... })`, or `None` after recording the problems in `errors`
+pub fn complete_fields(
+    fields: &syn::Fields, outer_rename: Option<&SerdeOuterRenameParams>, constructor: TokenStream, partial: TokenStream,
+) -> TokenStream {
+    let field_count = fields.len();
+    let mut members = Vec::<syn::Member>::new();
+    let mut bindings = Vec::<syn::Ident>::new();
+    let mut completions = Vec::<TokenStream>::new();
+    for (field_idx, field) in fields.iter().enumerate() {
+        let member = field_member(field, field_idx);
+        let segment = field_segment(field, field_idx, field_count, outer_rename);
+        let field_ty = &field.ty;
+        completions.push(match field.partial_kind() {
+            PartialFieldKind::Optional => quote!(errors.complete_field::<#field_ty>(#segment, #partial.#member)),
+            PartialFieldKind::Tristate(_) => quote!(
+                errors.complete_field::<#field_ty>(#segment, #partial.#member.into_nested())
+            ),
+            PartialFieldKind::Defaulted => quote!(
+                errors.complete_value::<#field_ty>(#segment, #partial.#member.into_inner())
+            ),
+        });
+        bindings.push(format_ident!("field_{field_idx}"));
+        members.push(member);
+    }
+    quote!{{
+        let fields = ( #(#completions,)* );
+        match fields {
+            ( #(Some(#bindings),)* ) => Some(#constructor{ #(#members: #bindings),* }),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }}
+}
+
+pub fn make_from_partial_struct(
+    input: &syn::ItemStruct, partial: &syn::Ident, where_clause: syn::WhereClause,
+) -> TokenStream {
+    let where_clause = where_clause_for_from_partial(where_clause, input.fields.iter());
+    let outer_rename = input.attrs.iter().find_map(SerdeOuterRenameParams::try_from_attr);
+    let completion = complete_fields(&input.fields, outer_rename.as_ref(), quote!(Self), quote!(partial));
+
+    let struct_name = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let impl_try_complete = impl_try_complete(struct_name, partial, &input.generics, &where_clause);
+    quote!{
+        impl #impl_generics ::aspartial::FromPartial for #struct_name #ty_generics
+            #where_clause
+        {
+            #[allow(unused_mut, unused_variables)]
+            fn from_partial(partial: Self::Partial) -> Result<Self, ::aspartial::CompletionError> {
+                let mut errors = ::aspartial::CompletionError::default();
+                let completed = #completion;
+                completed.ok_or(errors)
+            }
+        }
+
+        #impl_try_complete
+    }
+}
+
+pub fn make_from_partial_newtype(input: &syn::ItemStruct, field: &syn::Field, where_clause: syn::WhereClause) -> TokenStream {
+    let where_clause = where_clause_for_from_partial(where_clause, [field]);
+    let member = field_member(field, 0);
+    let field_ty = &field.ty;
+    let struct_name = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    quote!{
+        impl #impl_generics ::aspartial::FromPartial for #struct_name #ty_generics
+            #where_clause
+        {
+            fn from_partial(partial: Self::Partial) -> Result<Self, ::aspartial::CompletionError> {
+                Ok(Self{ #member: <#field_ty as ::aspartial::FromPartial>::from_partial(partial)? })
+            }
+
+            fn when_missing() -> Option<Self> {
+                Some(Self{ #member: <#field_ty as ::aspartial::FromPartial>::when_missing()? })
+            }
+        }
+    }
+}
+
+pub fn make_from_partial_enum(
+    input: &syn::ItemEnum,
+    partial: &syn::Ident,
+    enum_tag_style: &SerdeEnumTagParams,
+    where_clause: syn::WhereClause,
+) -> TokenStream {
+    let where_clause = where_clause_for_from_partial(where_clause, input.variants.iter().flat_map(|v| v.fields()));
+
+    let mut variant_indices = Vec::<usize>::new();
+    let mut completions = Vec::<TokenStream>::new();
+    let mut populated_checks = Vec::<TokenStream>::new();
+    for (variant_idx, (tag, variant)) in input.tagged_variants().enumerate() {
+        let variant_ident = &variant.ident;
+        let partial_field_name = variant.partial_field_name();
+        let segment = variant_segment(enum_tag_style, variant, &tag);
+        let completion = match variant.as_struct(input) {
+            Some(variant_struct) => {
+                let outer_rename = variant_struct.attrs.iter().find_map(SerdeOuterRenameParams::try_from_attr);
+                let completion = complete_fields(
+                    &variant.fields, outer_rename.as_ref(), quote!(Self::#variant_ident), quote!(variant_partial)
+                );
+                quote!(errors.complete_with(#segment, partial.#partial_field_name, |errors, variant_partial| #completion))
+            },
+            None => match variant.fields().next() {
+                Some(variant_field) => {
+                    let variant_ty = &variant_field.ty;
+                    quote!{
+                        errors.complete_field::<#variant_ty>(#segment, partial.#partial_field_name).map(Self::#variant_ident)
+                    }
+                },
+                None => quote!(errors.complete_with(#segment, partial.#partial_field_name, |_, ()| Some(Self::#variant_ident))),
+            },
+        };
+        variant_indices.push(variant_idx);
+        populated_checks.push(quote!(partial.#partial_field_name.is_some()));
+        completions.push(completion);
+    }
+
+    let enum_ident = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let turbofish = ty_generics.as_turbofish();
+    let impl_try_complete = impl_try_complete(enum_ident, partial, &input.generics, &where_clause);
+    quote!{
+        impl #impl_generics ::aspartial::FromPartial for #enum_ident #ty_generics
+            #where_clause
+        {
+            fn from_partial(partial: Self::Partial) -> Result<Self, ::aspartial::CompletionError> {
+                let variant_idx = ::aspartial::CompletionError::select_variant(
+                    #partial #turbofish::VARIANT_TAGS,
+                    &[#(#populated_checks),*],
+                    partial.tag_match.as_ref(),
+                    partial.unknown_tag.as_ref(),
+                )?;
+                let mut errors = ::aspartial::CompletionError::default();
+                let completed = match variant_idx {
+                    #(#variant_indices => #completions,)*
+                    _ => unreachable!("variant index out of bounds"),
+                };
+                completed.ok_or(errors)
+            }
+        }
+
+        #impl_try_complete
+    }
+}
+
+/// Completes the variant of an enum-shaped partial (see `aspartial(enum_repr)`)
+pub fn make_from_partial_enum_repr(
+    input: &syn::ItemEnum,
+    partial: &syn::Ident,
+    enum_tag_style: &SerdeEnumTagParams,
+    where_clause: syn::WhereClause,
+) -> TokenStream {
+    let where_clause = where_clause_for_from_partial(where_clause, input.variants.iter().flat_map(|v| v.fields()));
+
+    let completions = input.tagged_variants().map(|(tag, variant)| {
+        let variant_ident = &variant.ident;
+        let segment = variant_segment(enum_tag_style, variant, &tag);
+        match variant.as_struct(input) {
+            Some(variant_struct) => {
+                let outer_rename = variant_struct.attrs.iter().find_map(SerdeOuterRenameParams::try_from_attr);
+                let completion = complete_fields(
+                    &variant.fields, outer_rename.as_ref(), quote!(Self::#variant_ident), quote!(variant_partial)
+                );
+                quote!(#partial::#variant_ident(variant_partial) => errors.nested(#segment, |errors| #completion),)
+            },
+            None => match variant.fields().next() {
+                Some(variant_field) => {
+                    let variant_ty = &variant_field.ty;
+                    quote!{
+                        #partial::#variant_ident(variant_partial) => {
+                            errors.complete_value::<#variant_ty>(#segment, variant_partial).map(Self::#variant_ident)
+                        },
+                    }
+                },
+                None => quote!(#partial::#variant_ident => Some(Self::#variant_ident),),
+            },
+        }
+    });
+
+    let enum_ident = &input.ident;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let impl_try_complete = impl_try_complete(enum_ident, partial, &input.generics, &where_clause);
+    quote!{
+        impl #impl_generics ::aspartial::FromPartial for #enum_ident #ty_generics
+            #where_clause
+        {
+            #[allow(unused_mut)]
+            fn from_partial(partial: Self::Partial) -> Result<Self, ::aspartial::CompletionError> {
+                let mut errors = ::aspartial::CompletionError::default();
+                let completed = match partial {
+                    #(#completions)*
+                    #partial::Unknown => return Err(
+                        ::aspartial::complete::CompletionProblem::NoVariant(::aspartial::path::FieldPath::root()).into()
+                    ),
+                };
+                completed.ok_or(errors)
+            }
+        }
+
+        #impl_try_complete
+    }
+}
@@ -0,0 +1,179 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{parse_quote_spanned, spanned::Spanned};
+
+use crate::from_partial::{field_segment, tag_allows, variant_segment};
+use crate::serde_attributes::{SerdeEnumTagParams, SerdeOuterRenameParams};
+use crate::syn_extensions::{IEnumExt, IFieldExt, IVariantExt, PartialFieldKind};
+use crate::util::{field_member, generics_used_by};
+
+/// Adds `Introspect` bounds for the partial of every field that depends on the type
+/// parameters to a where clause from `where_clause_for_partial`. The partials of other
+/// fields without `Introspect` are reported as leaves.
+fn where_clause_for_introspect<'field>(
+    mut wc: syn::WhereClause,
+    generics: &syn::Generics,
+    fields: impl IntoIterator<Item=&'field syn::Field>,
+) -> syn::WhereClause {
+    for field in fields {
+        if generics_used_by(generics, [field]).type_params().next().is_none() {
+            continue
+        }
+        let span = field.ty.span();
+        let field_ty = &field.ty;
+        let introspected_ty = match field.partial_kind() {
+            PartialFieldKind::Optional => quote!(<#field_ty as ::aspartial::AsPartial>::Partial),
+            PartialFieldKind::Tristate(_) | PartialFieldKind::Defaulted => field.partial_type().into_token_stream(),
+        };
+        wc.predicates.push(parse_quote_spanned!{span=>
+            #introspected_ty : ::aspartial::Introspect
+        });
+    }
+    wc
+}
+
+/// Adds `Introspect` bounds for the partial payload of every variant that depends on the
+/// type parameters to a where clause from `where_clause_for_partial`
+pub fn where_clause_for_introspect_enum(
+    mut wc: syn::WhereClause,
+    input: &syn::ItemEnum,
+    partial: &syn::Ident,
+) -> syn::WhereClause {
+    for variant in &input.variants {
+        if generics_used_by(&input.generics, &variant.fields).type_params().next().is_none() {
+            continue
+        }
+        let payload_ty = variant.partial_payload_type(input, partial);
+        let span = variant.span();
+        wc.predicates.push(parse_quote_spanned!{span=>
+            #payload_ty : ::aspartial::Introspect
+        });
+    }
+    wc
+}
+
+/// A statement reporting `partial` (a reference) under `segment`, with `Introspect` if its
+/// type implements it, or else as a single leaf
+pub fn report_field(partial: &TokenStream, segment: &TokenStream) -> TokenStream {
+    quote!{{
+        #[allow(unused_imports)]
+        use ::aspartial::introspect::{
+            IntrospectFieldAsLeaf as _, IntrospectFieldByIntrospect as _, IntrospectFieldByWrapper as _,
+        };
+        (&&&::aspartial::introspect::IntrospectField(#partial)).report_field(report, #segment);
+    }}
+}
+
+pub fn make_introspect_struct(input: &syn::ItemStruct, partial: &syn::Ident, where_clause: syn::WhereClause) -> TokenStream {
+    let where_clause = where_clause_for_introspect(where_clause, &input.generics, input.fields.iter());
+    let outer_rename = input.attrs.iter().find_map(SerdeOuterRenameParams::try_from_attr);
+    let field_count = input.fields.len();
+
+    let reports = input.fields.iter().enumerate().map(|(field_idx, field)| {
+        let member = field_member(field, field_idx);
+        let segment = field_segment(field, field_idx, field_count, outer_rename.as_ref());
+        match field.partial_kind() {
+            PartialFieldKind::Optional => {
+                let report = report_field(&quote!(partial), &segment);
+                quote!{
+                    match &self.#member {
+                        Some(partial) => #report,
+                        None => report.leaf(#segment, false),
+                    }
+                }
+            },
+            PartialFieldKind::Tristate(_) | PartialFieldKind::Defaulted => report_field(&quote!(&self.#member), &segment),
+        }
+    });
+
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    quote!{
+        impl #impl_generics ::aspartial::Introspect for #partial #ty_generics
+            #where_clause
+        {
+            #[allow(unused_variables)]
+            fn report_fields(&self, report: &mut ::aspartial::introspect::FieldReport) {
+                #(#reports)*
+            }
+        }
+    }
+}
+
+/// Reports the fields of the variant that the tag resolves to or, failing that,
+/// of the first populated variant. The enum itself is missing if there is none.
+pub fn make_introspect_enum(
+    input: &syn::ItemEnum,
+    partial: &syn::Ident,
+    enum_tag_style: &SerdeEnumTagParams,
+    where_clause: syn::WhereClause,
+) -> TokenStream {
+    let where_clause = where_clause_for_introspect_enum(where_clause, input, partial);
+
+    let variant_reports = input.tagged_variants().map(|(tag, variant)| {
+        let partial_field_name = variant.partial_field_name();
+        let segment = variant_segment(enum_tag_style, variant, &tag);
+        let allowed = match variant.is_serde_untagged() {
+            true => tag_allows(quote!(self.tag_match.as_ref()), variant, &tag),
+            false => quote!(tagged_variant.is_none_or(|tagged_variant| tagged_variant == #tag)),
+        };
+        let report = report_field(&quote!(variant_partial), &segment);
+        quote!{
+            if #allowed {
+                if let Some(variant_partial) = &self.#partial_field_name {
+                    #report
+                    return
+                }
+            }
+        }
+    });
+
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    quote!{
+        impl #impl_generics ::aspartial::Introspect for #partial #ty_generics
+            #where_clause
+        {
+            fn report_fields(&self, report: &mut ::aspartial::introspect::FieldReport) {
+                let tagged_variant = self.tag_match.as_ref().and_then(::aspartial::TagMatch::variant);
+                #(#variant_reports)*
+                report.missing()
+            }
+        }
+    }
+}
+
+/// Reports the fields of the variant of an enum-shaped partial (see `aspartial(enum_repr)`).
+/// The enum itself is missing while its variant is unknown.
+pub fn make_introspect_enum_repr(
+    input: &syn::ItemEnum,
+    partial: &syn::Ident,
+    enum_tag_style: &SerdeEnumTagParams,
+    where_clause: syn::WhereClause,
+) -> TokenStream {
+    let where_clause = where_clause_for_introspect_enum(where_clause, input, partial);
+
+    let variant_reports = input.tagged_variants().map(|(tag, variant)| {
+        let variant_ident = &variant.ident;
+        let segment = variant_segment(enum_tag_style, variant, &tag);
+        match variant.fields {
+            syn::Fields::Unit => quote!(Self::#variant_ident => report.field(#segment, &()),),
+            _ => {
+                let report = report_field(&quote!(variant_partial), &segment);
+                quote!(Self::#variant_ident(variant_partial) => #report,)
+            },
+        }
+    });
+
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    quote!{
+        impl #impl_generics ::aspartial::Introspect for #partial #ty_generics
+            #where_clause
+        {
+            fn report_fields(&self, report: &mut ::aspartial::introspect::FieldReport) {
+                match self {
+                    #(#variant_reports)*
+                    Self::Unknown => report.missing(),
+                }
+            }
+        }
+    }
+}
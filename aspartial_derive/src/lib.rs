@@ -3,13 +3,23 @@
 use proc_macro::TokenStream;
 
 mod syn_extensions;
+mod apply;
 mod as_partial;
+mod from_partial;
+mod introspect;
+mod merge;
+mod resolve;
 mod serde_attributes;
+mod serialize;
 mod derive_config;
+mod diff;
+mod flatten;
 mod util;
 
 /// Generates a 'partial' version of the annotated type and implements `::aspartial::AsPartial`
-/// for the annotated type.
+/// for the annotated type, as well as `::aspartial::Merge` and `::aspartial::Introspect` for the
+/// partial type. `::aspartial::FromPartial`, `::aspartial::ApplyPartial` and `::aspartial::Diff`
+/// are implemented on request, see below.
 ///
 /// # Attributes
 /// ## `aspartial(name = MyPartial)`
@@ -21,6 +31,34 @@ mod util;
 ///
 /// ## `aspartial(attrs(#[some_attr1] #[some_attr2]))`
 /// Optional. Appends the specified attributes to the generated partial struct
+///
+/// ## `aspartial(serde_repr)`
+/// Optional, only for enums with unit variants and a `#[repr(...)]` integer type.
+/// The partial enum reads and writes the variants' discriminants, for enums that
+/// are (de)serialized with `serde_repr`.
+///
+/// ## `aspartial(enum_repr)`
+/// Optional, only for tagged enums. The partial is an enum with the same variants, holding
+/// the partials of their contents, plus an `Unknown` variant for as long as the tag doesn't
+/// name a variant (it is missing, still arriving, or names none of them).
+///
+/// ## `aspartial(complete)`
+/// Optional. Implements `::aspartial::FromPartial`, which needs the type of every field to
+/// implement it too.
+///
+/// ## `aspartial(apply)`
+/// Optional. Implements `::aspartial::ApplyPartial`, which needs the type of every field to
+/// implement it too. Implies `aspartial(complete)`.
+///
+/// ## `aspartial(diff)`
+/// Optional. Implements `::aspartial::Diff`. Fields whose type implements `Diff` are diffed
+/// with it, and fields of other types with `PartialEq` are replaced as a whole when they change.
+/// Fields whose type uses the type parameters must implement `Diff`.
+///
+/// # Flattened fields
+/// The partials of `#[serde(flatten)]` fields must implement `::aspartial::flatten::Flatten`,
+/// which derived partials and `String`-keyed maps do. Partial enums then take the entries of
+/// the map like serde does, so that a catch-all map flattened after them doesn't see those.
 #[proc_macro_derive(AsPartial, attributes(aspartial))]
 pub fn derive_as_partial(input: TokenStream) -> TokenStream {
     match as_partial::do_derive_as_partial(input) {
@@ -0,0 +1,135 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote_spanned, spanned::Spanned};
+
+use crate::syn_extensions::IVariantExt;
+use crate::util::{field_member, generics_used_by};
+
+/// Adds `Merge` bounds for the type of every field of a partial type that depends on
+/// its type parameters to its where clause. Other fields without `Merge` are replaced.
+fn where_clause_for_merge<'field>(
+    mut wc: syn::WhereClause,
+    generics: &syn::Generics,
+    partial_fields: impl IntoIterator<Item=&'field syn::Field>,
+) -> syn::WhereClause {
+    for field in partial_fields {
+        if generics_used_by(generics, [field]).type_params().next().is_none() {
+            continue
+        }
+        let span = field.ty.span();
+        let field_ty = &field.ty;
+        wc.predicates.push(parse_quote_spanned!{span=>
+            #field_ty : ::aspartial::Merge
+        });
+    }
+    wc
+}
+
+/// A statement merging `overlay` into `base` (a place), with `Merge` if the type implements
+/// it, or else by replacing `base` unless `overlay` is absent
+fn merge_value(base: &TokenStream, overlay: &TokenStream) -> TokenStream {
+    quote!{{
+        #[allow(unused_imports)]
+        use ::aspartial::merge::{MergeFieldByMerge as _, MergeFieldByOverlay as _, MergeFieldByReplace as _};
+        (&&&::aspartial::merge::MergeField::of(&#base)).merge_field(&mut #base, #overlay);
+    }}
+}
+
+pub fn make_merge_struct(partial_struct: &syn::ItemStruct, where_clause: syn::WhereClause) -> TokenStream {
+    let where_clause = where_clause_for_merge(where_clause, &partial_struct.generics, partial_struct.fields.iter());
+    let merges = partial_struct.fields.iter()
+        .enumerate()
+        .map(|(field_idx, field)| field_member(field, field_idx))
+        .map(|member| merge_value(&quote!(self.#member), &quote!(overlay.#member)));
+    let partial_ident = &partial_struct.ident;
+    let (impl_generics, ty_generics, _) = partial_struct.generics.split_for_impl();
+    quote!{
+        impl #impl_generics ::aspartial::Merge for #partial_ident #ty_generics
+            #where_clause
+        {
+            fn merge(&mut self, overlay: Self) {
+                #(#merges)*
+            }
+        }
+    }
+}
+
+/// Merges the variants of partial enums one by one. A tag in the overlay discards
+/// the variants of `self` that it rules out, according to the `allowed` expressions
+/// (one per variant, over a `tag_match: Option<&TagMatch>`).
+pub fn make_merge_enum(
+    partial_ident: &syn::Ident,
+    generics: &syn::Generics,
+    where_clause: syn::WhereClause,
+    partial_fields: &[syn::Field],
+    allowed: &[TokenStream],
+) -> TokenStream {
+    let where_clause = where_clause_for_merge(where_clause, generics, partial_fields);
+    let members: Vec<_> = partial_fields.iter()
+        .enumerate()
+        .map(|(field_idx, field)| field_member(field, field_idx))
+        .collect();
+    let merges = members.iter().map(|member| merge_value(&quote!(self.#member), &quote!(overlay.#member)));
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    quote!{
+        impl #impl_generics ::aspartial::Merge for #partial_ident #ty_generics
+            #where_clause
+        {
+            fn merge(&mut self, overlay: Self) {
+                if overlay.tag_match.is_some() {
+                    let tag_match = overlay.tag_match.as_ref();
+                    #(if !(#allowed) {
+                        self.#members = None;
+                    })*
+                } else if overlay.unknown_tag.is_some() {
+                    // a tag naming no variant rules out all of them
+                    #(self.#members = None;)*
+                }
+                #(#merges)*
+                if overlay.tag_match.is_some() || overlay.unknown_tag.is_some() {
+                    self.tag_match = overlay.tag_match;
+                    self.unknown_tag = overlay.unknown_tag;
+                }
+            }
+        }
+    }
+}
+
+/// Merges enum-shaped partials (see `aspartial(enum_repr)`): partials of the same
+/// variant are merged, and any other variant in the overlay replaces `self`
+pub fn make_merge_enum_repr(input: &syn::ItemEnum, partial_ident: &syn::Ident, mut where_clause: syn::WhereClause) -> TokenStream {
+    let mut arms = Vec::<TokenStream>::new();
+    for variant in &input.variants {
+        let variant_ident = &variant.ident;
+        if matches!(variant.fields, syn::Fields::Unit) {
+            arms.push(quote!((Self::#variant_ident, Self::#variant_ident) => (),));
+            continue
+        }
+        if generics_used_by(&input.generics, &variant.fields).type_params().next().is_some() {
+            let payload_ty = variant.partial_payload_type(input, partial_ident);
+            let span = variant.span();
+            where_clause.predicates.push(parse_quote_spanned!{span=>
+                #payload_ty : ::aspartial::Merge
+            });
+        }
+        let merge = merge_value(&quote!(*base), &quote!(overlay));
+        arms.push(quote!{
+            (Self::#variant_ident(base), Self::#variant_ident(overlay)) => #merge,
+        });
+    }
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    quote!{
+        impl #impl_generics ::aspartial::Merge for #partial_ident #ty_generics
+            #where_clause
+        {
+            fn merge(&mut self, overlay: Self) {
+                match (self, overlay) {
+                    #(#arms)*
+                    (_, Self::Unknown) => (),
+                    #[allow(unreachable_patterns)]
+                    (base, overlay) => *base = overlay,
+                }
+            }
+        }
+    }
+}
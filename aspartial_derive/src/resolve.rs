@@ -0,0 +1,81 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_quote, spanned::Spanned};
+
+use crate::from_partial::tag_allows;
+use crate::introspect::{report_field, where_clause_for_introspect_enum};
+use crate::syn_extensions::{IEnumExt, IVariantExt};
+
+/// The name of the enum that views the partial of a single variant of `partial`
+pub fn view_ident(partial: &syn::Ident) -> syn::Ident {
+    format_ident!("{partial}Ref")
+}
+
+/// An enum borrowing the partial of one variant, and methods on the partial enum
+/// that rank its populated variants as such views
+pub fn make_resolve_enum(input: &syn::ItemEnum, partial: &syn::Ident, where_clause: syn::WhereClause) -> syn::Result<TokenStream> {
+    let view = view_ident(partial);
+    if let Some(clashing) = input.variants.iter().find(|variant| variant.as_struct(input).is_some() && variant.partial_struct_ident(partial) == view) {
+        return Err(syn::Error::new(clashing.span(), format!("the partial of this variant clashes with '{view}'")))
+    }
+    let mut view_generics = input.generics.clone();
+    view_generics.params.insert(0, parse_quote!('partial));
+    let (_, view_ty_generics, _) = view_generics.split_for_impl();
+
+    let view_variants = input.variants.iter().map(|variant| {
+        let variant_ident = &variant.ident;
+        let payload_ty = variant.partial_payload_type(input, partial);
+        quote!(#variant_ident(&'partial #payload_ty))
+    });
+    let candidates = input.tagged_variants().map(|(tag, variant)| {
+        let variant_ident = &variant.ident;
+        let partial_field_name = variant.partial_field_name();
+        let allowed = tag_allows(quote!(tag_match), variant, &tag);
+        let report_variant = report_field(&quote!(variant_partial), &quote!(None));
+        quote!{
+            if let Some(variant_partial) = &self.#partial_field_name
+                && #allowed
+            {
+                candidates.push(::aspartial::resolve::Candidate{
+                    tag: #tag,
+                    variant: #view::#variant_ident(variant_partial),
+                    score: {
+                        let report = &mut ::aspartial::introspect::FieldReport::default();
+                        #report_variant
+                        ::aspartial::resolve::VariantScore::from_report(#tag, tag_match, report)
+                    },
+                });
+            }
+        }
+    });
+
+    let impl_where_clause = where_clause_for_introspect_enum(where_clause.clone(), input, partial);
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    Ok(quote!{
+        /// The partial of a single variant of [#partial]
+        pub enum #view #view_generics #where_clause {
+            #(#view_variants,)*
+        }
+
+        impl #impl_generics #partial #ty_generics #impl_where_clause {
+            /// The populated variants that the tag allows, best first
+            pub fn candidates<'partial>(&'partial self) -> Vec<::aspartial::resolve::Candidate<#view #view_ty_generics>> {
+                let tag_match = self.tag_match.as_ref();
+                let mut candidates = Vec::new();
+                #(#candidates)*
+                ::aspartial::resolve::rank(&mut candidates);
+                candidates
+            }
+
+            /// The most likely variant and the other candidates, if any variant is populated
+            pub fn resolve<'partial>(&'partial self) -> Option<::aspartial::resolve::Resolution<#view #view_ty_generics>> {
+                ::aspartial::resolve::Resolution::from_ranked(self.candidates())
+            }
+
+            /// The partial of the most likely variant, if any variant is populated
+            pub fn best_variant<'partial>(&'partial self) -> Option<#view #view_ty_generics> {
+                self.resolve().map(|resolution| resolution.best.variant)
+            }
+        }
+    })
+}
@@ -1,13 +1,15 @@
-use crate::{syn_extensions::IAttrExt, util::KeyEqualsLitStr};
+use syn::punctuated::Punctuated;
 
+use crate::syn_extensions::IAttrExt;
 
+#[allow(dead_code)]
 pub struct SerdeDefaultAttrParams;
 
 impl syn::parse::Parse for SerdeDefaultAttrParams {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let first_token_span = input.span();
         let default_token: syn::Ident = input.parse()?;
-        if default_token.to_string() != "default" {
+        if default_token != "default" {
             return Err(syn::Error::new(first_token_span, "Expected 'default' token"))
         }
         if input.is_empty() {
@@ -27,99 +29,78 @@ pub enum SerdeEnumTagParams{
 }
 
 impl SerdeEnumTagParams {
-    pub fn try_from_attr(attr: &syn::Attribute) -> Option<Self>{
-        if !attr.is_serde_attr(){
-            return None
-        }
-        let syn::Meta::List(meta_list) = &attr.meta else {
-            return None
-        };
-        meta_list.parse_args::<Self>().ok()
-    }
-
+    /// The representation of an enum, from the `untagged`, `tag` and `content` arguments
+    /// of its serde attributes, wherever they are among the other arguments
     pub fn from_attributes(attributes: &[syn::Attribute]) -> Self{
-        for attr in attributes{
-            if let Some(params) = Self::try_from_attr(attr) {
-                return params
+        let mut tag_key = None::<syn::LitStr>;
+        let mut content_key = None::<syn::LitStr>;
+        for meta in serde_metas(attributes) {
+            if meta.path().is_ident("untagged") {
+                return Self::Untagged
             }
-        }
-        return Self::ExternallyTagged
-    }
-}
-
-impl syn::parse::Parse for SerdeEnumTagParams {
-    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        if let Ok(ident) = input.fork().parse::<syn::Ident>(){
-            if ident.to_string().as_str() == "untagged" {
-                return Ok(Self::Untagged)
+            if meta.path().is_ident("tag") {
+                tag_key = meta_lit_str(&meta);
+            } else if meta.path().is_ident("content") {
+                content_key = meta_lit_str(&meta);
             }
         }
-
-        let params_span = input.span();
-        let mut key_vals = input.parse_terminated(KeyEqualsLitStr::parse, syn::Token![,])?.into_iter();
-        let Some(key_val1) = key_vals.next() else {
-            return Err(syn::Error::new(params_span, "Expected at least one param"))
-        };
-        let Some(key_val2) = key_vals.next() else {
-            if key_val1.key != "tag" {
-                return Err(syn::Error::new(key_val1.key.span(), "expected key to be 'tag'"))
-            }
-            return Ok(Self::InternallyTagged { tag_key: key_val1.value })
-        };
-        if let Some(extra_keyval) = key_vals.next() {
-            return Err(syn::Error::new(extra_keyval.key.span(), "Expected at most one param"))
+        match (tag_key, content_key) {
+            (Some(tag_key), Some(content_key)) => Self::AdjacentlyTagged { tag_key, content_key },
+            (Some(tag_key), None) => Self::InternallyTagged { tag_key },
+            (None, _) => Self::ExternallyTagged,
         }
+    }
+}
 
-        let (content_keyval, tag_keyval) = if key_val1.key.to_string() < key_val2.key.to_string() {
-            (key_val1, key_val2)
-        }else{
-            (key_val2, key_val1)
-        };
-
-        if content_keyval.key.to_string() != "content"{
-            return Err(syn::Error::new(content_keyval.key.span(), "expected key to be 'content'"))
-        }
-        if tag_keyval.key.to_string() != "tag"{
-            return Err(syn::Error::new(tag_keyval.key.span(), "expected key to be 'tag'"))
-        }
+/// The arguments of all serde attributes in `attributes`
+pub fn serde_metas(attributes: &[syn::Attribute]) -> impl Iterator<Item=syn::Meta> + '_ {
+    attributes.iter()
+        .filter(|attr| attr.is_serde_attr())
+        .filter_map(|attr| attr.parse_args_with(Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated).ok())
+        .flatten()
+}
 
-        Ok(Self::AdjacentlyTagged { tag_key: tag_keyval.value, content_key: content_keyval.value })
+/// `"bla"` in `key = "bla"`
+fn meta_lit_str(meta: &syn::Meta) -> Option<syn::LitStr> {
+    let syn::Meta::NameValue(name_value) = meta else {
+        return None
+    };
+    match &name_value.value {
+        syn::Expr::Lit(syn::ExprLit{ lit: syn::Lit::Str(value), .. }) => Some(value.clone()),
+        _ => None,
     }
 }
 
-pub struct SerdeInnerRenameParams{
-    /// `rename` in #[serde(rename = "bla")]
-    #[allow(dead_code)]
-    pub rename_marker: syn::Ident,
-    #[allow(dead_code)]
-    pub equals_sign: syn::Token![=],
-    /// `"bla"` in #[serde(rename = "bla")]
-    pub new_name: syn::LitStr,
+/// The serialize and deserialize values of `key = "bla"` (both "bla") or of
+/// `key(serialize = "ser", deserialize = "de")` (either of which may be missing)
+fn split_lit_strs(meta: &syn::Meta) -> (Option<syn::LitStr>, Option<syn::LitStr>) {
+    let syn::Meta::List(meta_list) = meta else {
+        let value = meta_lit_str(meta);
+        return (value.clone(), value)
+    };
+    let Ok(metas) = meta_list.parse_args_with(Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated) else {
+        return (None, None)
+    };
+    let find = |key: &str| metas.iter().filter(|meta| meta.path().is_ident(key)).find_map(meta_lit_str);
+    (find("serialize"), find("deserialize"))
 }
 
-impl syn::parse::Parse for SerdeInnerRenameParams {
-    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        let key_val: KeyEqualsLitStr = input.parse()?;
-        if key_val.key.to_string() != "rename" {
-            return Err(syn::Error::new(key_val.key.span(), "Expected key 'rename'"))
-        }
-        Ok(Self{
-            rename_marker: key_val.key,
-            equals_sign: key_val.equals_token,
-            new_name: key_val.value
-        })
-    }
+/// `#[serde(rename = "bla")]` or `#[serde(rename(serialize = "ser", deserialize = "de"))]`
+/// on a field or a variant
+pub struct SerdeInnerRenameParams{
+    pub serialize: Option<syn::LitStr>,
+    pub deserialize: Option<syn::LitStr>,
 }
 
 impl SerdeInnerRenameParams {
-    pub fn try_from_attr(attr: &syn::Attribute) -> Option<Self> {
-        if !attr.is_serde_attr(){
-            return None
+    pub fn from_attributes(attributes: &[syn::Attribute]) -> Self {
+        let mut out = Self{ serialize: None, deserialize: None };
+        for meta in serde_metas(attributes).filter(|meta| meta.path().is_ident("rename")) {
+            let (serialize, deserialize) = split_lit_strs(&meta);
+            out.serialize = serialize.or(out.serialize);
+            out.deserialize = deserialize.or(out.deserialize);
         }
-        let syn::Meta::List(meta_list) = &attr.meta else {
-            return None
-        };
-        meta_list.parse_args::<Self>().ok()
+        out
     }
 }
 
@@ -170,38 +151,36 @@ impl TryFrom<&syn::LitStr> for RenameStyle {
     }
 }
 
+/// `#[serde(rename_all = "style")]` or `#[serde(rename_all(serialize = "style", deserialize = "style"))]`
+/// on a struct, an enum or a variant
 pub struct SerdeOuterRenameParams {
-    /// `rename_all` in #[serde(rename_all = "bla")]
-    #[allow(dead_code)]
-    pub rename_marker: syn::Ident,
-    #[allow(dead_code)]
-    pub equals_sign: syn::Token![=],
-    pub rename_style: RenameStyle,
+    pub serialize: Option<RenameStyle>,
+    pub deserialize: Option<RenameStyle>,
 }
 
-impl syn::parse::Parse for SerdeOuterRenameParams {
-    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        let key_val: KeyEqualsLitStr = input.parse()?;
-        if key_val.key.to_string() != "rename_all" {
-            return Err(syn::Error::new(key_val.key.span(), "Expected key 'rename_all'"))
-        }
-        let rename_style = RenameStyle::try_from(&key_val.value)?;
-        Ok(Self{
-            rename_marker: key_val.key,
-            equals_sign: key_val.equals_token,
-            rename_style,
+impl SerdeOuterRenameParams {
+    pub fn try_from_attr(attr: &syn::Attribute) -> Option<Self> {
+        let meta = serde_metas(std::slice::from_ref(attr)).find(|meta| meta.path().is_ident("rename_all"))?;
+        let (serialize, deserialize) = split_lit_strs(&meta);
+        Some(Self{
+            serialize: serialize.and_then(|style| RenameStyle::try_from(&style).ok()),
+            deserialize: deserialize.and_then(|style| RenameStyle::try_from(&style).ok()),
         })
     }
-}
 
-impl SerdeOuterRenameParams {
-    pub fn try_from_attr(attr: &syn::Attribute) -> Option<Self> {
-        if !attr.is_serde_attr(){
-            return None
+    /// `name` as it is serialized
+    pub fn serialized(&self, name: &syn::LitStr) -> syn::LitStr {
+        match &self.serialize {
+            Some(style) => style.transform(name),
+            None => name.clone(),
+        }
+    }
+
+    /// `name` as it is expected when deserializing
+    pub fn deserialized(&self, name: &syn::LitStr) -> syn::LitStr {
+        match &self.deserialize {
+            Some(style) => style.transform(name),
+            None => name.clone(),
         }
-        let syn::Meta::List(meta_list) = &attr.meta else {
-            return None
-        };
-        meta_list.parse_args::<Self>().ok()        
     }
 }
@@ -0,0 +1,157 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote_spanned, spanned::Spanned};
+
+use crate::from_partial::tag_allows;
+use crate::serde_attributes::SerdeEnumTagParams;
+use crate::syn_extensions::{IEnumExt, IVariantExt};
+
+/// Adds `Serialize` bounds for the partial payload of every variant to a where clause from `where_clause_for_partial`.
+/// Like those of partial structs, they are higher-ranked so that payloads without `Serialize` only
+/// keep the partial from being serialized.
+fn where_clause_for_serialize(
+    mut wc: syn::WhereClause,
+    input: &syn::ItemEnum,
+    partial: &syn::Ident,
+) -> syn::WhereClause {
+    for variant in &input.variants {
+        let payload_ty = variant.partial_payload_type(input, partial);
+        let span = variant.span();
+        wc.predicates.push(parse_quote_spanned!{span=>
+            for<'__partial> #payload_ty : ::serde::Serialize
+        });
+    }
+    wc
+}
+
+fn tag_style(enum_tag_style: &SerdeEnumTagParams) -> TokenStream {
+    match enum_tag_style {
+        SerdeEnumTagParams::ExternallyTagged => quote!(::aspartial::tag::TagStyle::External),
+        SerdeEnumTagParams::InternallyTagged { tag_key } => quote!(::aspartial::tag::TagStyle::Internal{ tag_key: #tag_key }),
+        SerdeEnumTagParams::AdjacentlyTagged { tag_key, content_key } => quote!(
+            ::aspartial::tag::TagStyle::Adjacent{ tag_key: #tag_key, content_key: #content_key }
+        ),
+        SerdeEnumTagParams::Untagged => quote!(::aspartial::tag::TagStyle::Untagged),
+    }
+}
+
+/// Partial enums are serialized in the shape of the original enum, with the tag
+/// as it was received and the first populated variant that the tag allows. An
+/// unknown tag is sent back with the payload it came with.
+pub fn make_serialize_enum(
+    input: &syn::ItemEnum,
+    partial: &syn::Ident,
+    enum_tag_style: &SerdeEnumTagParams,
+    where_clause: syn::WhereClause,
+) -> TokenStream {
+    let where_clause = where_clause_for_serialize(where_clause, input, partial);
+    let tag_style = tag_style(enum_tag_style);
+
+    let wire_values = input.tagged_variants().map(|(tag, variant)| {
+        let partial_field_name = variant.partial_field_name();
+        let variant_tag_style = match variant.is_serde_untagged() {
+            true => quote!(::aspartial::tag::TagStyle::Untagged),
+            false => tag_style.clone(),
+        };
+        let to_wire_value = match variant.fields {
+            syn::Fields::Unit => quote!(|_| Ok(#variant_tag_style.to_unit_wire_value(tag))),
+            _ => quote!(|payload| ::serde_json::to_value(payload).map(|payload| #variant_tag_style.to_wire_value(tag, Some(payload)))),
+        };
+        let allowed = tag_allows(quote!(tag_match), variant, &tag);
+        quote!{
+            .or_else(|| self.#partial_field_name.as_ref()
+                .filter(|_| #allowed)
+                .map(#to_wire_value)
+            )
+        }
+    });
+
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    quote!{
+        impl #impl_generics ::serde::Serialize for #partial #ty_generics
+            #where_clause
+        {
+            fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let tag_match = self.tag_match.as_ref();
+                let tag = tag_match.map(::aspartial::TagMatch::tag);
+                let value = None::<Result<::serde_json::Value, ::serde_json::Error>> #(#wire_values)*
+                    .unwrap_or_else(|| Ok(match &self.unknown_tag {
+                        Some(unknown_tag) => unknown_tag.to_wire_value(#tag_style),
+                        None => #tag_style.to_wire_value(tag, None),
+                    }))
+                    .map_err(<S::Error as ::serde::ser::Error>::custom)?;
+                ::serde::Serialize::serialize(&value, serializer)
+            }
+        }
+    }
+}
+
+/// Partials of `serde_repr` enums are serialized as the discriminant of their populated variant
+pub fn make_serialize_repr_enum(input: &syn::ItemEnum, partial: &syn::Ident, repr: &syn::Ident) -> TokenStream {
+    let enum_ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let turbofish = ty_generics.as_turbofish();
+    let discriminants = input.variants.iter().map(|variant| {
+        let partial_field_name = variant.partial_field_name();
+        let variant_ident = &variant.ident;
+        quote!{
+            if self.#partial_field_name.is_some() {
+                return ::serde::Serialize::serialize(&(#enum_ident #turbofish::#variant_ident as #repr), serializer)
+            }
+        }
+    });
+    quote!{
+        impl #impl_generics ::serde::Serialize for #partial #ty_generics
+            #where_clause
+        {
+            fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                #(#discriminants)*
+                serializer.serialize_none()
+            }
+        }
+    }
+}
+
+/// Enum-shaped partials (see `aspartial(enum_repr)`) are serialized in the shape of
+/// the original enum. An unknown variant is sent as an empty payload without a tag.
+pub fn make_serialize_enum_repr(
+    input: &syn::ItemEnum,
+    partial: &syn::Ident,
+    enum_tag_style: &SerdeEnumTagParams,
+    where_clause: syn::WhereClause,
+) -> TokenStream {
+    let where_clause = where_clause_for_serialize(where_clause, input, partial);
+    let tag_style = tag_style(enum_tag_style);
+
+    let wire_values = input.tagged_variants().map(|(tag, variant)| {
+        let variant_ident = &variant.ident;
+        let variant_tag_style = match variant.is_serde_untagged() {
+            true => quote!(::aspartial::tag::TagStyle::Untagged),
+            false => tag_style.clone(),
+        };
+        match variant.fields {
+            syn::Fields::Unit => quote!(Self::#variant_ident => #variant_tag_style.to_unit_wire_value(Some(#tag)),),
+            _ => quote!{
+                Self::#variant_ident(payload) => {
+                    let payload = ::serde_json::to_value(payload).map_err(<S::Error as ::serde::ser::Error>::custom)?;
+                    #variant_tag_style.to_wire_value(Some(#tag), Some(payload))
+                },
+            },
+        }
+    });
+
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    quote!{
+        impl #impl_generics ::serde::Serialize for #partial #ty_generics
+            #where_clause
+        {
+            fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let value = match self {
+                    #(#wire_values)*
+                    Self::Unknown => #tag_style.to_wire_value(None, None),
+                };
+                ::serde::Serialize::serialize(&value, serializer)
+            }
+        }
+    }
+}
@@ -1,39 +1,104 @@
-use syn::{parse_quote, spanned::Spanned};
+use quote::format_ident;
+use syn::{parse_quote, punctuated::Punctuated};
 
-use crate::{serde_attributes::{SerdeInnerRenameParams, SerdeOuterRenameParams}, util::KeyEqualsLitStr};
+use crate::{serde_attributes::{serde_metas, SerdeInnerRenameParams, SerdeOuterRenameParams}, util::{generics_used_by, KeyEqualsLitStr}};
 
 pub trait IAttrExt{
     fn is_serde_attr(&self) -> bool;
     fn is_serde_any_default(&self) -> bool;
     fn is_serde_regular_default(&self) -> bool;
     fn as_serde_default_func_path(&self) -> Option<syn::Path>;
+    fn is_serde_flatten(&self) -> bool;
+    fn is_serde_other(&self) -> bool;
+    fn derives(&self, trait_ident: &str) -> bool;
+    fn retain_serde_args(&self, keep: impl Fn(&syn::Meta) -> bool) -> Option<syn::Attribute>;
+    fn without_serde_skip_serializing_if(&self) -> Option<syn::Attribute> {
+        self.retain_serde_args(|meta| !meta.path().is_ident("skip_serializing_if"))
+    }
     fn is_serde_default_to_func(&self) -> bool {
         self.as_serde_default_func_path().is_some()
     }
 }
 
+/// How the partial of a field represents a field that hasn't arrived
+pub enum PartialFieldKind<'field> {
+    /// `Option<T::Partial>`
+    Optional,
+    /// `Tristate<Inner::Partial>`, for fields of type `Option<Inner>`
+    Tristate(&'field syn::Type),
+    /// `Defaulted<T::Partial>`, for fields with a serde default
+    Defaulted,
+}
+
 pub trait IFieldExt {
-    fn partial_is_optional(&self) -> bool;
+    fn partial_kind(&self) -> PartialFieldKind<'_>;
     fn is_serde_default(&self) -> bool;
     fn partial_type(&self) -> syn::Type;
+    fn is_serde_flatten(&self) -> bool;
+    fn wire_name(&self, outer_rename: Option<&SerdeOuterRenameParams>) -> Option<syn::LitStr>;
 }
 impl IFieldExt for syn::Field{
-    fn partial_is_optional(&self) -> bool{
-        !self.is_serde_default()
+    fn partial_kind(&self) -> PartialFieldKind<'_> {
+        if self.is_serde_default() {
+            return PartialFieldKind::Defaulted
+        }
+        if self.is_serde_flatten() {
+            return PartialFieldKind::Optional
+        }
+        match option_inner_type(&self.ty) {
+            Some(inner) => PartialFieldKind::Tristate(inner),
+            None => PartialFieldKind::Optional,
+        }
     }
     fn is_serde_default(&self) -> bool{
         self.attrs.iter().any(|attr| attr.is_serde_any_default())
     }
     fn partial_type(&self) -> syn::Type {
         let field_ty = &self.ty;
-        let partial_type: syn:: Type = parse_quote!(<#field_ty as ::aspartial::AsPartial>::Partial);
-
-        if self.is_serde_default() {
-            partial_type
-        } else {
-            parse_quote!(Option< #partial_type >)
+        match self.partial_kind() {
+            PartialFieldKind::Optional => parse_quote!(Option< <#field_ty as ::aspartial::AsPartial>::Partial >),
+            PartialFieldKind::Tristate(inner) => parse_quote!(
+                ::aspartial::Tristate< <#inner as ::aspartial::AsPartial>::Partial >
+            ),
+            PartialFieldKind::Defaulted => parse_quote!(
+                ::aspartial::Defaulted< <#field_ty as ::aspartial::AsPartial>::Partial >
+            ),
         }
     }
+    fn is_serde_flatten(&self) -> bool {
+        self.attrs.iter().any(|attr| attr.is_serde_flatten())
+    }
+    /// The key of this field in the serialized struct, if it is a named field
+    fn wire_name(&self, outer_rename: Option<&SerdeOuterRenameParams>) -> Option<syn::LitStr> {
+        let ident = self.ident.as_ref()?;
+        let renamed = SerdeInnerRenameParams::from_attributes(&self.attrs).serialize;
+        Some(renamed.unwrap_or_else(|| {
+            let raw_ident = ident.to_string();
+            let default_name = syn::LitStr::new(raw_ident.strip_prefix("r#").unwrap_or(&raw_ident), ident.span());
+            match outer_rename {
+                Some(rename) => rename.serialized(&default_name),
+                None => default_name,
+            }
+        }))
+    }
+}
+
+/// The `T` in a type spelled `Option<T>` (or `std::option::Option<T>`, etc)
+pub fn option_inner_type(ty: &syn::Type) -> Option<&syn::Type> {
+    let syn::Type::Path(syn::TypePath{ qself: None, path }) = ty else {
+        return None
+    };
+    let last_segment = path.segments.last()?;
+    if last_segment.ident != "Option" {
+        return None
+    }
+    let syn::PathArguments::AngleBracketed(args) = &last_segment.arguments else {
+        return None
+    };
+    match args.args.iter().collect::<Vec<_>>().as_slice() {
+        [syn::GenericArgument::Type(inner)] => Some(inner),
+        _ => None,
+    }
 }
 
 impl IAttrExt for syn::Attribute{
@@ -41,7 +106,7 @@ impl IAttrExt for syn::Attribute{
         let Some(last_segment) = self.path().segments.last() else {
             return false;
         };
-        return last_segment.ident.to_string() == "serde"
+        last_segment.ident == "serde"
     }
     fn is_serde_any_default(&self) ->bool {
         self.is_serde_regular_default() || self.is_serde_default_to_func()
@@ -59,7 +124,64 @@ impl IAttrExt for syn::Attribute{
         let Ok(default_token) = meta_list.parse_args::<syn::Ident>() else {
             return false
         };
-        return default_token.to_string() == "default"
+        default_token == "default"
+    }
+    fn is_serde_flatten(&self) -> bool {
+        if !self.is_serde_attr() {
+            return false
+        }
+        let syn::Meta::List(meta_list) = &self.meta else {
+            return false;
+        };
+        let Ok(flatten_token) = meta_list.parse_args::<syn::Ident>() else {
+            return false
+        };
+        flatten_token == "flatten"
+    }
+    fn is_serde_other(&self) -> bool {
+        if !self.is_serde_attr() {
+            return false
+        }
+        let syn::Meta::List(meta_list) = &self.meta else {
+            return false;
+        };
+        let Ok(other_token) = meta_list.parse_args::<syn::Ident>() else {
+            return false
+        };
+        other_token == "other"
+    }
+    /// Whether this is a `#[derive(...)]` listing a trait named `trait_ident`, by any path
+    fn derives(&self, trait_ident: &str) -> bool {
+        if !self.path().is_ident("derive") {
+            return false
+        }
+        let Ok(paths) = self.parse_args_with(Punctuated::<syn::Path, syn::Token![,]>::parse_terminated) else {
+            return false
+        };
+        paths.iter().any(|path| path.segments.last().is_some_and(|segment| segment.ident == trait_ident))
+    }
+    /// This attribute with only the serde arguments that pass `keep`, e.g. to drop
+    /// a `skip_serializing_if` that would be checking the original field type.
+    /// `None` if no arguments are left.
+    fn retain_serde_args(&self, keep: impl Fn(&syn::Meta) -> bool) -> Option<syn::Attribute> {
+        let syn::Meta::List(meta_list) = &self.meta else {
+            return Some(self.clone());
+        };
+        let Ok(metas) = meta_list.parse_args_with(Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated) else {
+            return Some(self.clone());
+        };
+        let kept: Vec<&syn::Meta> = metas.iter()
+            .filter(|meta| keep(meta))
+            .collect();
+        if kept.len() == metas.len() {
+            return Some(self.clone())
+        }
+        if kept.is_empty() {
+            return None
+        }
+        let mut attr = self.clone();
+        attr.meta = parse_quote!(serde( #(#kept),* ));
+        Some(attr)
     }
     fn as_serde_default_func_path(&self) -> Option<syn::Path> {
         if !self.is_serde_attr() {
@@ -74,7 +196,7 @@ impl IAttrExt for syn::Attribute{
         let Ok(keyval) = meta_list.parse_args::<KeyEqualsLitStr>() else {
             return None
         };
-        if keyval.key.to_string() != "default" {
+        if keyval.key != "default" {
             return None
         }
         keyval.value.parse().ok()
@@ -83,9 +205,15 @@ impl IAttrExt for syn::Attribute{
 
 pub trait IVariantExt {
     fn partial_field_name(&self) -> syn::Ident;
-    fn as_partial_field(&self) -> syn::Result<syn::Field>;
+    fn as_struct(&self, input: &syn::ItemEnum) -> Option<syn::ItemStruct>;
+    fn partial_struct_ident(&self, enum_partial: &syn::Ident) -> syn::Ident;
+    fn partial_payload_type(&self, input: &syn::ItemEnum, enum_partial: &syn::Ident) -> syn::Type;
+    fn as_partial_field(&self, input: &syn::ItemEnum, enum_partial: &syn::Ident) -> syn::Field;
     fn tag(&self, outer_rename: Option<&SerdeOuterRenameParams>) -> syn::LitStr;
+    fn accepted_tags(&self, outer_rename: Option<&SerdeOuterRenameParams>) -> Vec<syn::LitStr>;
     fn fields(&self) -> impl Iterator<Item=&syn::Field>;
+    fn is_serde_other(&self) -> bool;
+    fn is_serde_untagged(&self) -> bool;
 }
 
 impl IVariantExt for syn::Variant {
@@ -94,32 +222,101 @@ impl IVariantExt for syn::Variant {
         syn::Ident::new(&ident, self.ident.span())
     }
     
-    fn as_partial_field(&self) -> syn::Result<syn::Field> {
-        let unnamed_fields = match &self.fields{
-            syn::Fields::Unnamed(syn::FieldsUnnamed{unnamed, ..}) => {
-                unnamed
+    /// A struct with the fields of a struct-like or multi-field tuple variant, and only
+    /// the generics that they use. Field names are renamed by the variant's `rename_all`
+    /// or else by the enum's `rename_all_fields`.
+    fn as_struct(&self, input: &syn::ItemEnum) -> Option<syn::ItemStruct> {
+        match &self.fields {
+            syn::Fields::Unit => return None,
+            syn::Fields::Unnamed(syn::FieldsUnnamed{unnamed, ..}) if unnamed.len() == 1 => return None,
+            _ => (),
+        }
+        let variant_rename = self.attrs.iter()
+            .filter(|attr| attr.is_serde_attr())
+            .find_map(|attr| attr.retain_serde_args(|meta| meta.path().is_ident("rename_all")));
+        let enum_rename = || input.attrs.iter()
+            .filter(|attr| attr.is_serde_attr())
+            .filter_map(|attr| attr.parse_args_with(Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated).ok())
+            .flatten()
+            .find_map(|meta| match meta {
+                syn::Meta::NameValue(name_value) if name_value.path.is_ident("rename_all_fields") => {
+                    let style = name_value.value;
+                    Some(parse_quote!(#[serde(rename_all = #style)]))
+                },
+                _ => None,
+            });
+        let ident = &self.ident;
+        let vis = &input.vis;
+        let fields = &self.fields;
+        let semi_token = matches!(fields, syn::Fields::Unnamed(_)).then(<syn::Token![;]>::default);
+        let mut out: syn::ItemStruct = parse_quote!(#vis struct #ident #fields #semi_token);
+        out.attrs = variant_rename.or_else(enum_rename).into_iter().collect();
+        out.generics = generics_used_by(&input.generics, self.fields());
+        Some(out)
+    }
+
+    fn partial_struct_ident(&self, enum_partial: &syn::Ident) -> syn::Ident {
+        format_ident!("{}{}", enum_partial, self.ident)
+    }
+
+    /// The partial of the contents of this variant. Unit variants have nothing but
+    /// their presence, so their partial is `()`.
+    fn partial_payload_type(&self, input: &syn::ItemEnum, enum_partial: &syn::Ident) -> syn::Type {
+        match &self.fields{
+            syn::Fields::Unit => parse_quote!( () ),
+            syn::Fields::Unnamed(syn::FieldsUnnamed{unnamed, ..}) if unnamed.len() == 1 => {
+                parse_quote!( <#unnamed as ::aspartial::AsPartial>::Partial )
             },
-            _ => return Err(syn::Error::new(self.span(), "Only unnamed fields supported for now"))
-        };
+            _ => {
+                let partial_struct_ident = self.partial_struct_ident(enum_partial);
+                let generics = generics_used_by(&input.generics, self.fields());
+                let (_, ty_generics, _) = generics.split_for_impl();
+                parse_quote!( #partial_struct_ident #ty_generics )
+            },
+        }
+    }
+
+    fn as_partial_field(&self, input: &syn::ItemEnum, enum_partial: &syn::Ident) -> syn::Field {
         let ident = self.partial_field_name();
-        let field_type: syn::Type = parse_quote!{
-            Option<  <#unnamed_fields as ::aspartial::AsPartial>::Partial  >
-        };
-        Ok(parse_quote!(pub #ident : #field_type))
+        let payload_type = self.partial_payload_type(input, enum_partial);
+        parse_quote!(pub #ident : Option<#payload_type>)
     }
 
+    /// The tag that this variant is serialized with
     fn tag(&self, outer_rename: Option<&SerdeOuterRenameParams>) -> syn::LitStr {
-        self.attrs.iter()
-            .find_map(|attr| SerdeInnerRenameParams::try_from_attr(attr))
-            .map(|params| params.new_name)
+        SerdeInnerRenameParams::from_attributes(&self.attrs).serialize
             .unwrap_or_else(||{
                 let default_tag = syn::LitStr::new(&self.ident.to_string(), self.ident.span());
                 match outer_rename {
-                    Some(rename) => rename.rename_style.transform(&default_tag),
+                    Some(rename) => rename.serialized(&default_tag),
                     None => default_tag
                 }
             })
     }
+    /// The tags that this variant is deserialized from: its (deserialize) name and its
+    /// aliases. None for `#[serde(untagged)]` variants.
+    fn accepted_tags(&self, outer_rename: Option<&SerdeOuterRenameParams>) -> Vec<syn::LitStr> {
+        if self.is_serde_untagged() {
+            return vec![]
+        }
+        let name = SerdeInnerRenameParams::from_attributes(&self.attrs).deserialize
+            .unwrap_or_else(|| {
+                let default_tag = syn::LitStr::new(&self.ident.to_string(), self.ident.span());
+                match outer_rename {
+                    Some(rename) => rename.deserialized(&default_tag),
+                    None => default_tag
+                }
+            });
+        let aliases = serde_metas(&self.attrs)
+            .filter_map(|meta| match meta {
+                syn::Meta::NameValue(name_value) if name_value.path.is_ident("alias") => match name_value.value {
+                    syn::Expr::Lit(syn::ExprLit{ lit: syn::Lit::Str(alias), .. }) => Some(alias),
+                    _ => None,
+                },
+                _ => None,
+            });
+        std::iter::once(name).chain(aliases).collect()
+    }
     fn fields(&self) -> impl Iterator<Item=&syn::Field>{
         let out: Box<dyn Iterator<Item=_>> = match &self.fields{
             syn::Fields::Unnamed(unnamed_fields) => Box::new(unnamed_fields.unnamed.iter()),
@@ -128,16 +325,49 @@ impl IVariantExt for syn::Variant {
         };
         out
     }
+    /// Whether this is the `#[serde(other)]` variant, which unknown tags deserialize into
+    fn is_serde_other(&self) -> bool {
+        self.attrs.iter().any(|attr| attr.is_serde_other())
+    }
+    /// Whether this variant is `#[serde(untagged)]` in an otherwise tagged enum, so
+    /// that it is tried on the whole payload when the tag names none of the others
+    fn is_serde_untagged(&self) -> bool {
+        serde_metas(&self.attrs).any(|meta| meta.path().is_ident("untagged"))
+    }
 }
 
 pub trait IEnumExt {
     // fn partial_fields(&self) -> impl Iterator<Item=syn::Field>;
     fn tagged_variants(&self) -> impl Iterator<Item=(syn::LitStr, &syn::Variant)>;
+    fn accepted_tags(&self) -> Vec<(syn::LitStr, syn::LitStr)>;
+    fn int_repr(&self) -> Option<syn::Ident>;
 }
 
 impl IEnumExt for syn::ItemEnum {
     fn tagged_variants(&self) -> impl Iterator<Item=(syn::LitStr, &syn::Variant)> {
-        let rename_params = self.attrs.iter().find_map(|attr| SerdeOuterRenameParams::try_from_attr(attr));
+        let rename_params = self.attrs.iter().find_map(SerdeOuterRenameParams::try_from_attr);
         self.variants.iter().map(move |v| (v.tag(rename_params.as_ref()), v) )
     }
+
+    /// Every tag that a variant is deserialized from, along with the tag the variant is serialized with
+    fn accepted_tags(&self) -> Vec<(syn::LitStr, syn::LitStr)> {
+        let rename_params = self.attrs.iter().find_map(SerdeOuterRenameParams::try_from_attr);
+        self.tagged_variants()
+            .flat_map(|(tag, variant)| {
+                variant.accepted_tags(rename_params.as_ref()).into_iter().map(move |accepted| (accepted, tag.clone()))
+            })
+            .collect()
+    }
+
+    /// The integer type in `#[repr(...)]`, if there is one
+    fn int_repr(&self) -> Option<syn::Ident> {
+        const INT_TYPES: &[&str] = &[
+            "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+        ];
+        self.attrs.iter()
+            .filter(|attr| attr.path().is_ident("repr"))
+            .filter_map(|attr| attr.parse_args_with(Punctuated::<syn::Ident, syn::Token![,]>::parse_terminated).ok())
+            .flatten()
+            .find(|ident| INT_TYPES.iter().any(|int_type| ident == int_type))
+    }
 }
@@ -1,5 +1,8 @@
+use syn::spanned::Spanned;
+
 pub struct KeyEqualsLitStr{
     pub key: syn::Ident,
+    #[allow(dead_code)]
     pub equals_token: syn::Token![=],
     pub value: syn::LitStr,
 }
@@ -14,3 +17,63 @@ impl syn::parse::Parse for KeyEqualsLitStr {
     }
 }
 
+/// How `field` is accessed in expressions like `self.field`
+pub fn field_member(field: &syn::Field, field_idx: usize) -> syn::Member {
+    match &field.ident {
+        Some(ident) => syn::Member::Named(ident.clone()),
+        None => syn::Member::Unnamed(syn::Index{ index: field_idx as u32, span: field.span() }),
+    }
+}
+
+fn mentions(tokens: proc_macro2::TokenStream, name: &str) -> bool {
+    tokens.into_iter().any(|token| match token {
+        proc_macro2::TokenTree::Ident(ident) => ident == name,
+        proc_macro2::TokenTree::Group(group) => mentions(group.stream(), name),
+        _ => false,
+    })
+}
+
+/// The generic parameters (and where predicates) of `generics` that are
+/// mentioned by the types of `fields`
+pub fn generics_used_by<'field>(
+    generics: &syn::Generics, fields: impl IntoIterator<Item=&'field syn::Field>,
+) -> syn::Generics {
+    use quote::ToTokens;
+
+    let field_types: proc_macro2::TokenStream = fields.into_iter()
+        .map(|field| field.ty.to_token_stream())
+        .collect();
+    let param_name = |param: &syn::GenericParam| match param {
+        syn::GenericParam::Type(ty) => ty.ident.to_string(),
+        syn::GenericParam::Lifetime(lifetime) => lifetime.lifetime.ident.to_string(),
+        syn::GenericParam::Const(constant) => constant.ident.to_string(),
+    };
+    let (used, unused): (Vec<_>, Vec<_>) = generics.params.iter()
+        .partition(|param| mentions(field_types.clone(), &param_name(param)));
+    let unused: Vec<String> = unused.into_iter().map(param_name).collect();
+
+    let mut out = generics.clone();
+    out.params = used.into_iter().cloned().collect();
+    if let Some(where_clause) = &mut out.where_clause {
+        where_clause.predicates = where_clause.predicates.iter()
+            .filter(|predicate| !unused.iter().any(|name| mentions(predicate.to_token_stream(), name)))
+            .cloned()
+            .collect();
+    }
+    out
+}
+
+/// A pattern binding every field in `fields` to `{prefix}_{field_idx}`, to be
+/// appended to a variant path, e.g. `{ radius: field_0 }` or `(field_0, field_1)`
+pub fn destructure(fields: &syn::Fields, prefix: &str) -> (proc_macro2::TokenStream, Vec<syn::Ident>) {
+    let bindings: Vec<syn::Ident> = (0..fields.len())
+        .map(|field_idx| quote::format_ident!("{prefix}_{field_idx}"))
+        .collect();
+    let members = fields.iter().enumerate().map(|(field_idx, field)| field_member(field, field_idx));
+    let pattern = match fields {
+        syn::Fields::Named(_) => quote::quote!({ #(#members: #bindings),* }),
+        syn::Fields::Unnamed(_) => quote::quote!(( #(#bindings),* )),
+        syn::Fields::Unit => quote::quote!(),
+    };
+    (pattern, bindings)
+}